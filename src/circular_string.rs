@@ -8,10 +8,15 @@
 #![allow(dead_code)]
 //! fixed strings with circular-queue backing
 
+use crate::const_fns::ConstBytes;
+use crate::CapacityError;
+use crate::{FixedstrMake, SplitInto, StrPattern};
+use crate::{OverflowMode, OverflowPolicy, Truncate, ELLIPSIS};
 use core::cmp::{min, Ordering, PartialOrd};
+use core::marker::PhantomData;
 #[cfg(not(feature = "no-alloc"))]
 extern crate alloc;
-use core::ops::Add;
+use core::ops::{Add, Range, RangeBounds};
 
 /// **This type is only available with the `circular-str` option.**
 /// A *circular string* is represented underneath by a fixed-size u8
@@ -21,14 +26,24 @@ use core::ops::Add;
 /// push, trim *in front* of the string.  However, `Deref<str>` is not
 /// implemented as it cannot be done efficiently.  Instead, the
 /// [cstr::to_strs] function returns a pair of string slices, the second
-/// of which is non-empty if the string is not contiguous.  Additionally,
-/// only single-byte characters are currently allowed, although this might
-/// change in the future by using a "ghost vector" at the end of the array.
-/// An iterator [cstr::chars] is provided over all single-byte chars, which
-/// also forms the foundation of other traits such as Eq, Ord, Hash, etc.
+/// of which is non-empty if the string is not contiguous.  All of UTF-8 is
+/// supported: a multibyte character that wraps around the end of the
+/// array is mirrored by a three-byte "ghost vector" that follows the
+/// array, so that both slices returned by `to_strs` always begin and end
+/// at character boundaries.
+/// An iterator [cstr::chars] is provided over all chars, which
+/// also forms the foundation of other traits such as Ord.
 /// The Serialization (serde) and no-std options are both supported.
 ///
 /// Each `cstr<N>` can hold up to N bytes and the maximum N is 65535.
+/// The ghost vector costs three bytes: along with the two `u16` fields
+/// that locate the string in the array, a `cstr<N>` takes N+7 bytes
+/// (rounded up to an even number), where it took N+4 before UTF-8 support.
+/// Values of N outside of 1..=65535 are rejected at compile time:
+/// ```compile_fail
+///  # use fixedstr::*;
+///  let cb = cstr::<65536>::new();
+/// ```
 /// **Values of N that are exact powers of 2 are recommended** to speed up
 /// the `%` operation for computing indices in a ciruclar queue.
 ///
@@ -51,122 +66,154 @@ use core::ops::Add;
 ///  cb.trim_whitespaces();
 ///  assert!("23xyijklmno" == &cb);
 ///  assert!(&cb < "4abc");   // Ord trait
-///  let mut a = cstr8::from("abc");
+///  let mut a = cstr8::make("abc");
 ///  let ba:cstr8 = "123" + a; // concat &str on the left efficiently
 ///  assert_eq!(ba,"123abc");
+///  let mut cu = cstr::<8>::make("abcdefg");
+///  cu.truncate_left(7);
+///  cu.push_str("€λ");        // € wraps around the end of the array
+///  assert_eq!(cu.to_strs(), ("€", "λ"));
+///  cu.push_char_front('λ');
+///  assert_eq!(cu.to_strs(), ("λ€", "λ"));
+///  assert_eq!(cu.pop_char(), Some('λ'));
+/// ```
+///
+/// The optional second parameter selects the [OverflowPolicy] (by default
+/// [Truncate]) that determines what happens when the capacity is exceeded,
+/// at either end of the string.  The [crate::Saturate] policy puts the
+/// ellipsis at the end that was truncated:
+/// ```
+///  # use fixedstr::*;
+///  let mut cb = cstr::<8, Saturate>::make("abcdefghij");
+///  assert_eq!(cb, "abcde…");
+///  let mut cb = cstr::<8, Saturate>::make("abc");
+///  assert_eq!(cb.push_front("uvwxyz"), "uvwx");
+///  assert_eq!(cb, "…yzabc");
 /// ```
 #[derive(Copy, Clone)]
-pub struct cstr<const N: usize = 32> {
+#[repr(C)]
+pub struct cstr<const N: usize = 32, P: OverflowPolicy = Truncate> {
     chrs: [u8; N],
+    ghost: [u8; 3], // copy of chrs[..3]: must immediately follow chrs
     front: u16,
     len: u16,
+    policy: PhantomData<P>,
 } //cstr
 
-impl<const N: usize> cstr<N> {
-    /// create `cstr` from `&str` with silent truncation; panics if
-    /// N is greater than 65535
-    pub fn make(src: &str) -> cstr<N> {
-        if N < 1 || N > 65535 {
-            panic!("cstr strings are limited to a capacity between 1 and 65535");
-        }
-        let mut m = cstr::<N>::new();
-        let length = core::cmp::min(N, src.len());
-        m.chrs[..length].copy_from_slice(&src.as_bytes()[..length]);
-        m.len = length as u16;
+impl<const N: usize, P: OverflowPolicy> cstr<N, P> {
+    /// create `cstr` from `&str`, handling overflow according to the
+    /// policy `P` (by default, with silent truncation)
+    pub fn make(src: &str) -> cstr<N, P> {
+        let mut m = cstr::<N, P>::new();
+        m.push_policy(src, false);
         m
     } //make
 
     /// version of make that also panics if the input string is not ascii.
-    pub fn from_ascii(src: &str) -> cstr<N> {
-        if N < 1 || N > 65535 {
-            panic!("cstr strings are limited to a maximum capacity of 65535");
-        }
+    pub fn from_ascii(src: &str) -> cstr<N, P> {
         if !src.is_ascii() {
             panic!("cstr string is not ascii");
         }
-        let mut m = cstr::<N>::new();
-        let length = core::cmp::min(N, src.len());
-        m.chrs[..length].copy_from_slice(&src.as_bytes()[..length]);
-        m.len = length as u16;
-        m
+        Self::make(src)
     } //from_ascii
 
-    /// version of make that does not truncate: returns original str slice
-    /// as error.  Also checks if N is no greater than 65535 without panic.
-    pub fn try_make(src: &str) -> Result<cstr<N>, &str> {
+    /// truncating constructor that takes the place of the `From`
+    /// conversions of versions before 0.6.0, which are now `TryFrom`
+    /// conversions that fail instead of truncating.  Since a type cannot
+    /// implement both `From<&str>` and `TryFrom<&str>`, this function keeps
+    /// calls of the form `cstr::from(s)` working (but not `s.into()`) until it
+    /// is removed.
+    #[deprecated(since = "0.6.0", note = "use `make` to truncate, or `try_make` or `try_from` to detect overflow")]
+    pub fn from<T: AsRef<str>>(s: T) -> Self {
+        Self::make(s.as_ref())
+    }
+
+    /// version of make that does not truncate: returns a [CapacityError]
+    /// if the string does not fit.
+    pub fn try_make(src: &str) -> Result<cstr<N, P>, CapacityError> {
         let length = src.len();
-        if length > N || N > 65535 || N < 1 {
-            return Err(src);
+        if length > N {
+            return Err(CapacityError::Overflow {
+                required: length,
+                available: N,
+            });
         }
         let mut m = cstr::new();
-        m.chrs[..].copy_from_slice(&src.as_bytes()[..length]);
+        m.put_bytes(0, &src.as_bytes()[..length]);
         m.len = length as u16;
         Ok(m)
     } //try_make
 
     /// version of `try_make` that also checks if the input string is ascii.
-    pub fn try_make_ascii(src: &str) -> Option<cstr<N>> {
-        let length = src.len();
-        if length > N || N > 65535 || N < 1 || !src.is_ascii() {
-            return None;
+    pub fn try_make_ascii(src: &str) -> Result<cstr<N, P>, CapacityError> {
+        if let Some(position) = src.bytes().position(|b| !b.is_ascii()) {
+            return Err(CapacityError::NonAscii { position });
         }
-        let mut m = cstr::new();
-        m.chrs[..].copy_from_slice(&src.as_bytes()[..length]);
-        m.len = length as u16;
-        Some(m)
-    } //try_make
+        Self::try_make(src)
+    } //try_make_ascii
+
+    /// version of [FromIterator] that returns a [CapacityError] instead of
+    /// applying the [OverflowPolicy] if the items do not fit.
+    /// See [crate::StrPiece] for the types of items.
+    pub fn try_from_iter<T: crate::StrPiece>(iter: impl IntoIterator<Item = T>) -> Result<cstr<N, P>, CapacityError> {
+        let mut s = cstr::new();
+        s.try_extend(iter)?;
+        Ok(s)
+    }
+
+    /// version of [Extend] that pushes either all of the items or, if they
+    /// do not fit, none of them, returning a [CapacityError]
+    pub fn try_extend<T: crate::StrPiece>(&mut self, iter: impl IntoIterator<Item = T>) -> Result<(), CapacityError> {
+        crate::try_extend_pieces(self, N, false, iter)
+    }
 
     /// version of make that returns a pair consisting of the made
-    /// `cstr` and the remainder `&str` that was truncated; panics if
-    /// N is greater than 65535 (but does not check for ascii strings)
-    pub fn make_remainder(src: &str) -> (cstr<N>, &str) {
-        if N > 65535 || N < 1 {
-            panic!("cstr strings are limited to a capacity between 1 and 65535");
-        }
+    /// `cstr` and the remainder `&str` that was not pushed
+    pub fn make_remainder(src: &str) -> (cstr<N, P>, &str) {
         let mut m = cstr::new();
-        let length = core::cmp::min(N, src.len());
-        m.chrs[..].copy_from_slice(&src.as_bytes()[..length]);
-        m.len = length as u16;
-        (m, &src[length..])
+        let rest = m.push_str(src);
+        (m, rest)
     } //try_make
 
-    /// make from a pair of str slices, does not truncate, and checks that
-    /// N is not greater than 65535 without panic.  The returned cstr will
-    /// be contiguous.
-    pub fn from_pair(left: &str, right: &str) -> Option<cstr<N>> {
+    /// make from a pair of str slices, does not truncate.
+    /// The returned cstr will be contiguous.
+    pub fn from_pair(left: &str, right: &str) -> Result<cstr<N, P>, CapacityError> {
         let (llen, rlen) = (left.len(), right.len());
-        if llen + rlen > N || N > 65535 || N < 1 {
-            return None;
+        if llen + rlen > N {
+            return Err(CapacityError::Overflow {
+                required: llen + rlen,
+                available: N,
+            });
         }
         let mut m = cstr::new();
         m.len = (llen + rlen) as u16;
-        m.chrs[..llen].copy_from_slice(&left.as_bytes()[..llen]);
-        m.chrs[llen..].copy_from_slice(&right.as_bytes()[llen..]);
-        Some(m)
+        m.put_bytes(0, &left.as_bytes()[..llen]);
+        m.put_bytes(llen, right.as_bytes());
+        Ok(m)
     } //from_pair
 
 /// const constructor, to be called from const contexts.  However, as
 /// const constructors are restricted from using iterators, it's slightly
 /// better to call the non-const constructors in non-const contexts.
-/// Truncates automatically.
-    pub const fn const_make(src: &str) -> cstr<N> {
-        let mut m = cstr::<N>::new();
-        let mut len = src.len();
-        if len>N {len=N;}
-        //m.chrs[..length].copy_from_slice(&src.as_bytes()[..length]);
+/// Overflow is handled according to the policy `P`, so that under
+/// [crate::Strict] or [crate::Panic] an oversized string fails to compile
+/// when evaluated in a const context.
+    pub const fn const_make(src: &str) -> cstr<N, P> {
+        let mut m = cstr::<N, P>::new();
         let bytes = src.as_bytes();
-        let mut i = 0;
-        while i<len {
-          m.chrs[i] = bytes[i];
-          i += 1;
-        }
+        let (_, len, ellipsis) = crate::plan_push(0, bytes, N, P::MODE, false);
+        m.put_bytes(0, bytes.split_at(len).0);
         m.len = len as u16;
+        if ellipsis {
+          m.put_bytes(len, ELLIPSIS.as_bytes());
+          m.len += ELLIPSIS.len() as u16;
+        }
         m
     }// const_make
 
     /// version of `const_make` that does not truncate.
-    pub const fn const_try_make(s:&str) ->  Result<cstr<N>, &str> {
-      if s.len()>N || N<1 || N>65535 {Err(s)}
+    pub const fn const_try_make(s:&str) ->  Result<cstr<N, P>, CapacityError> {
+      if s.len()>N {Err(CapacityError::Overflow{required: s.len(), available: N})}
       else { Ok(cstr::const_make(s)) }
     }
 
@@ -186,10 +233,10 @@ impl<const N: usize> cstr<N> {
             return;
         }
         let mut mhrs = [0; N];
-        for i in 0..self.len as usize {
-            mhrs[i] = self.chrs[self.index(i)];
+        for (i, m) in mhrs.iter_mut().take(self.len as usize).enumerate() {
+            *m = self.chrs[self.index(i)];
         }
-        self.chrs = mhrs;
+        self.put_bytes(0, &mhrs[..self.len as usize]);
         self.front = 0;
     } //reset
 
@@ -201,7 +248,7 @@ impl<const N: usize> cstr<N> {
     /// resets string to empty string and clears underlying buffer to contain
     /// all zeros.
     pub fn zero(&mut self) {
-        self.chrs = [0; N];
+        self.put_bytes(0, &[0; N]);
         self.front = 0;
         self.len = 0;
     }
@@ -214,16 +261,11 @@ impl<const N: usize> cstr<N> {
         }
     }
 
-    /// returns the nth char of the fstr.  Since only single-byte characters
-    /// are currently supported by the cstr type, this function is the same
-    /// as [Self::nth_bytechar] except that n is checked against the length of the
-    /// string.
+    /// returns the nth char of the string, if it exists.  This is not a
+    /// constant-time operation unless the string is ascii, in which case
+    /// [Self::nth_bytechar] can be used instead.
     pub fn nth(&self, n: usize) -> Option<char> {
-        if n < self.len as usize {
-            Some(self.chrs[self.index(n)] as char)
-        } else {
-            None
-        }
+        self.chars().nth(n)
     }
 
     /// returns the nth byte of the string as a char, does not check n
@@ -233,112 +275,255 @@ impl<const N: usize> cstr<N> {
         self.chrs[self.index(n)] as char
     }
 
-    /// sets the nth byte of the string to the supplied character.
-    /// the character must fit in a single byte.  Returns true on success.
+    /// changes the character at character position n to c.  This function
+    /// requires that c has the same utf8 length as the character being
+    /// replaced.  It never shuffles the bytes underneath.  Returns true
+    /// on success.  See [cstr::replace_nth] for a version without this
+    /// requirement.
     pub fn set(&mut self, n: usize, c: char) -> bool {
-        if c.len_utf8() > 1 || n >= self.len as usize {
-            false
-        } else {
-            self.chrs[self.index(n)] = c as u8;
-            true
+        let mut bi = 0;
+        let mut found = None;
+        for (i, d) in self.chars().enumerate() {
+            if i == n {
+                found = Some(d);
+                break;
+            }
+            bi += d.len_utf8();
+        }
+        match found {
+            Some(d) if d.len_utf8() == c.len_utf8() => {
+                let mut buf = [0u8; 4];
+                for (k, b) in c.encode_utf8(&mut buf).bytes().enumerate() {
+                    self.put(self.index(bi + k), b);
+                }
+                true
+            }
+            _ => false,
         }
     } //set
 
-    /// pushes given string to the end of the string, returns remainder
+    /// pushes given string to the end of the string, returns remainder.
+    /// How much is pushed when the capacity is exceeded depends on the
+    /// [OverflowPolicy] `P`.
     pub fn push_str<'t>(&mut self, src: &'t str) -> &'t str {
-        let srclen = src.len();
-        let slen = self.len as usize;
-        let bytes = &src.as_bytes();
-        let length = core::cmp::min(slen + srclen, N);
-        let remain = if N > (slen + srclen) {
-            0
-        } else {
-            (srclen + slen) - N
-        };
-        let mut i = 0;
-        while i < srclen && i + slen < N {
-            self.chrs[self.index(slen + i)] = bytes[i];
-            i += 1;
-        } //while
-        self.len += i as u16;
-        &src[srclen - remain..]
+        self.push_policy(src, true)
     } //push_str
 
-    /// Pushes string to the **front** of the string, returns remainder.
+    // push according to the overflow policy; `fallible` is false for
+    // callers that cannot report the part that was not pushed
+    fn push_policy<'t>(&mut self, src: &'t str, fallible: bool) -> &'t str {
+        let (keep, n, ellipsis) = crate::plan_push(self.len(), src.as_bytes(), N, P::MODE, fallible);
+        let mut keep = keep;
+        while !self.is_char_boundary(keep) {
+            keep -= 1;
+        }
+        self.truncate_right(keep);
+        self.push_bytes(&src.as_bytes()[..n]);
+        if ellipsis {
+            self.push_bytes(ELLIPSIS.as_bytes());
+        }
+        &src[n..]
+    } //push_policy
+
+    fn push_bytes(&mut self, bytes: &[u8]) {
+        let slen = self.len();
+        for (i, b) in bytes.iter().enumerate() {
+            self.put(self.index(slen + i), *b);
+        }
+        self.len += bytes.len() as u16;
+    }
+
+    /// Pushes string to the **front** of the string, returns remainder,
+    /// which is the front part of `src` that was not pushed.
     /// because of the circular-queue backing, this operation has the same
     /// cost as pushing to the back of the string ([Self::push_str]).
-    /// This function does not check if the input string is ascii.
     pub fn push_front<'t>(&mut self, src: &'t str) -> &'t str {
-        let srclen = src.len();
-        let slen = self.len as usize;
-        let bytes = &src.as_bytes();
-        let length = core::cmp::min(slen + srclen, N);
-        let remain = if N >= (slen + srclen) {
-            0
-        } else {
-            (srclen + slen) - N
-        };
-        let mut i = 0;
-        while i < srclen && i + slen < N {
-            //self.front =(self.front + (N as u16) -1) % (N as u16);
-            self.front = ((self.front as usize + N - 1) % N) as u16;
-            self.chrs[self.front as usize] = bytes[srclen - 1 - i];
-            i += 1;
-        } //while
-        self.len += i as u16;
-        &src[..remain]
+        self.push_front_policy(src, true)
     } //push_front
 
+    // version of push_policy for the front: the tail of `src` is kept and
+    // the truncation marker goes in front
+    fn push_front_policy<'t>(&mut self, src: &'t str, fallible: bool) -> &'t str {
+        let (keep, n, ellipsis) = crate::plan_push(self.len(), src.as_bytes(), N, P::MODE, fallible);
+        if keep < self.len() {
+            let mut drop = self.len() - keep;
+            while !self.is_char_boundary(drop) {
+                drop += 1;
+            }
+            self.truncate_left(drop);
+        }
+        // plan_push measured n from the start of src: recompute it for the
+        // tail, which is what is kept at the front
+        let mut start = src.len() - n;
+        if n < src.len() && P::MODE != OverflowMode::Strict {
+            let marker = if ellipsis { ELLIPSIS.len() } else { 0 };
+            start = src.len() - (N - keep - marker);
+        }
+        while !src.is_char_boundary(start) {
+            start += 1;
+        }
+        self.push_front_bytes(&src.as_bytes()[start..]);
+        if ellipsis {
+            self.push_front_bytes(ELLIPSIS.as_bytes());
+        }
+        &src[..start]
+    } //push_front_policy
+
+    fn push_front_bytes(&mut self, bytes: &[u8]) {
+        for b in bytes.iter().rev() {
+            self.front = ((self.front as usize + N - 1) % N) as u16;
+            self.put(self.front as usize, *b);
+        }
+        self.len += bytes.len() as u16;
+    }
+
     /// alias for [Self::push_front]
     pub fn push_str_front<'t>(&mut self, src: &'t str) -> &'t str {
         self.push_front(src)
     }
 
     /// Pushes a single character to the end of the string, returning
-    /// true on success.  This function checks if the given character
-    /// occupies a single-byte.
+    /// true on success.
     pub fn push_char(&mut self, c: char) -> bool {
-        let clen = c.len_utf8();
-        if clen > 1 || self.len as usize + clen > N {
-            return false;
-        }
         let mut buf = [0u8; 4]; // char buffer
-        let bstr = c.encode_utf8(&mut buf);
-        self.push_str(bstr);
-        true
+        self.push_str(c.encode_utf8(&mut buf)).is_empty()
     } // push_char
 
     /// Pushes a single character to the front of the string, returning
-    /// true on success.  This function checks if the given character
-    /// occupies a single-byte.
+    /// true on success.
     pub fn push_char_front(&mut self, c: char) -> bool {
-        let clen = c.len_utf8();
-        if clen > 1 || self.len as usize + clen > N {
-            return false;
-        }
-        let newfront = (self.front as usize + N - 1) % N;
-        self.chrs[newfront] = c as u8;
-        self.front = newfront as u16;
-        self.len += 1;
-        true
+        let mut buf = [0u8; 4]; // char buffer
+        self.push_front(c.encode_utf8(&mut buf)).is_empty()
     } //push_char_front
 
+    /// Pushes each of the items (see [crate::StrPiece]) to the **front**
+    /// of the string in turn, so that they end up in reverse order, until
+    /// one does not fit.  That item is pushed as by [Self::push_front],
+    /// keeping its tail, under the [OverflowPolicy] `P`.
+    /// ```
+    ///  # use fixedstr::*;
+    ///  let mut c = cstr::<8>::make("def");
+    ///  c.extend_front("cba".chars());
+    ///  assert_eq!(c, "abcdef");
+    ///  c.extend_front(["0", "4321"]);
+    ///  assert_eq!(c, "10abcdef");
+    /// ```
+    pub fn extend_front<T: crate::StrPiece>(&mut self, iter: impl IntoIterator<Item = T>) {
+        crate::extend_pieces(iter, P::MODE, |s| self.push_front(s).is_empty());
+    } //extend_front
+
+    /// Pushes `src` to the end of the string, evicting as many bytes
+    /// (whole characters) from the front of the string as necessary to
+    /// make room, so that the string keeps the newest bytes like a ring
+    /// log.  If `src` itself is longer than the capacity, the whole string
+    /// is evicted and only the longest tail of `src` that fits is kept.
+    /// Returns the total number of bytes dropped, from the string
+    /// and from `src`.  The [OverflowPolicy] does not apply, as eviction
+    /// never fails.
+    /// ```
+    ///  # use fixedstr::*;
+    ///  let mut log = cstr::<8>::make("abcdef");
+    ///  assert_eq!(log.push_str_evicting("ghij"), 2);
+    ///  assert_eq!(log, "cdefghij");
+    ///  assert_eq!(log.push_str_evicting("0123456789"), 10);
+    ///  assert_eq!(log, "23456789");
+    /// ```
+    pub fn push_str_evicting(&mut self, src: &str) -> usize {
+        let skip = Self::tail_start(src);
+        let src = &src[skip..];
+        // once part of src is dropped, nothing before it can be kept
+        let need = if skip > 0 { self.len() } else { (self.len() + src.len()).saturating_sub(N) };
+        let mut evicted = need;
+        while !self.is_char_boundary(evicted) {
+            evicted += 1;
+        }
+        self.truncate_left(evicted);
+        self.push_bytes(src.as_bytes());
+        skip + evicted
+    } //push_str_evicting
+
+    /// version of [Self::push_str_evicting] that evicts whole lines: as
+    /// many lines (each ending in `'\n'`) as necessary are dropped from the
+    /// front of the string.  An unfinished line at the end of the string
+    /// is dropped entirely if dropping the complete lines is not enough.
+    /// ```
+    ///  # use fixedstr::*;
+    ///  let mut log = cstr::<16>::make("one\ntwo\nthree\n");
+    ///  assert_eq!(log.push_str_evicting_lines("four\n"), 4);
+    ///  assert_eq!(log, "two\nthree\nfour\n");
+    /// ```
+    pub fn push_str_evicting_lines(&mut self, src: &str) -> usize {
+        let skip = Self::tail_start(src);
+        let src = &src[skip..];
+        let need = if skip > 0 { self.len() } else { (self.len() + src.len()).saturating_sub(N) };
+        let mut evicted = need;
+        if need > 0 {
+            while evicted < self.len() && self.chrs[self.index(evicted - 1)] != b'\n' {
+                evicted += 1;
+            }
+        }
+        self.truncate_left(evicted);
+        self.push_bytes(src.as_bytes());
+        skip + evicted
+    } //push_str_evicting_lines
+
+    /// Pushes a single character to the end of the string, evicting
+    /// characters from the front as necessary.  Returns the number of
+    /// bytes evicted.
+    pub fn push_char_evicting(&mut self, c: char) -> usize {
+        let mut buf = [0u8; 4];
+        self.push_str_evicting(c.encode_utf8(&mut buf))
+    }
+
+    /// Pushes `src` to the **front** of the string, evicting as many bytes
+    /// (whole characters) from the end of the string as necessary: the
+    /// mirror image of [Self::push_str_evicting].  If `src` itself is
+    /// longer than the capacity, the whole string is evicted and only the
+    /// longest front part of `src` that fits is kept.  Returns
+    /// the total number of bytes dropped, from the string and from `src`.
+    pub fn push_front_evicting(&mut self, src: &str) -> usize {
+        let keep = crate::floor_char_boundary(src.as_bytes(), N);
+        let skip = src.len() - keep;
+        let src = &src[..keep];
+        // once part of src is dropped, nothing after it can be kept
+        let need = if skip > 0 { self.len() } else { (self.len() + src.len()).saturating_sub(N) };
+        let mut newlen = self.len() - need;
+        while !self.is_char_boundary(newlen) {
+            newlen -= 1;
+        }
+        let evicted = self.len() - newlen;
+        self.truncate_right(newlen);
+        self.push_front_bytes(src.as_bytes());
+        skip + evicted
+    } //push_front_evicting
+
+    /// Pushes a single character to the front of the string, evicting
+    /// characters from the end as necessary.  Returns the number of
+    /// bytes evicted.
+    pub fn push_char_front_evicting(&mut self, c: char) -> usize {
+        let mut buf = [0u8; 4];
+        self.push_front_evicting(c.encode_utf8(&mut buf))
+    }
+
+    // the start of the longest tail of src that fits in N bytes
+    fn tail_start(src: &str) -> usize {
+        let mut start = src.len().saturating_sub(N);
+        while !src.is_char_boundary(start) {
+            start += 1;
+        }
+        start
+    }
+
     /// remove and return last character in string, if it exists
     pub fn pop_char(&mut self) -> Option<char> {
         if self.len() == 0 {
             return None;
         }
-        let lasti = ((self.front + self.len - 1) as usize) % N;
-        let firstchar = self.chrs[lasti] as char;
-        self.len -= 1;
-        Some(firstchar)
-        /*
-        let (l,r) = self.to_strs();
-        let right = if r.len()>0 {r} else {l};
-        let (ci,lastchar) = right.char_indices().last().unwrap();
-        self.len = if r.len()>0 {(l.len() + ci) as u16} else {ci as u16};
+        let (l, r) = self.to_strs();
+        let right = if !r.is_empty() { r } else { l };
+        let lastchar = right.chars().next_back()?;
+        self.len -= lastchar.len_utf8() as u16;
         Some(lastchar)
-        */
     } //pop
 
     /// remove and return first character in string, if it exists
@@ -346,15 +531,17 @@ impl<const N: usize> cstr<N> {
         if self.len() == 0 {
             return None;
         }
-        let firstchar = self.chrs[self.front as usize] as char;
-        self.front = self.index16(1);
-        self.len -= 1;
+        let firstchar = self.to_strs().0.chars().next()?;
+        let clen = firstchar.len_utf8() as u16;
+        self.front = self.index16(clen);
+        self.len -= clen;
         Some(firstchar)
     } //pop_char_front
 
     /// alias for [Self::truncate]
     pub fn truncate_right(&mut self, n: usize) {
         if (n < self.len as usize) {
+            assert!(self.is_char_boundary(n), "cstr truncation is not at a char boundary");
             self.len = n as u16;
         }
     }
@@ -362,6 +549,7 @@ impl<const N: usize> cstr<N> {
     /// right-truncates string up to byte position n. Only the first
     /// n bytes will be kept.
     /// No effect if n is greater than or equal to the length of the string.
+    /// **Panics** if n is not on a character boundary.
     #[inline]
     pub fn truncate(&mut self, n: usize) {
         self.truncate_right(n);
@@ -371,17 +559,10 @@ impl<const N: usize> cstr<N> {
     /// n bytes will be truncated.  Because of the circular queue backing,
     /// this is an O(1) operation.
     /// No effect if n is greater than the length of the string.
+    /// **Panics** if n is not on a character boundary.
     pub fn truncate_left(&mut self, n: usize) {
         if (n > 0 && n <= self.len as usize) {
-            /*
-            let (a,b) = self.to_strs();
-            if n<a.len() {
-              assert!(a.is_char_boundary(n));
-            }
-            else {
-              assert!(b.is_char_boundary(n-a.len()));
-            }
-            */
+            assert!(self.is_char_boundary(n), "cstr truncation is not at a char boundary");
             self.front = self.index16(n as u16);
             self.len -= n as u16;
         }
@@ -393,32 +574,28 @@ impl<const N: usize> cstr<N> {
     }
 
     /// finds the position of first character that satisfies given predicate
-    pub fn find<P>(&self, predicate: P) -> Option<usize>
+    pub fn find<F>(&self, predicate: F) -> Option<usize>
     where
-        P: Fn(char) -> bool,
+        F: Fn(char) -> bool,
     {
         let (a, b) = self.to_strs();
         if let Some(pos) = a.find(|x: char| predicate(x)) {
             Some(pos)
-        } else if let Some(pos) = b.find(|x: char| predicate(x)) {
-            Some(a.len() + pos)
         } else {
-            None
+            b.find(|x: char| predicate(x)).map(|pos| a.len() + pos)
         }
     } //find
 
     /// finds the position of last character that satisfies given predicate
-    pub fn rfind<P>(&self, predicate: P) -> Option<usize>
+    pub fn rfind<F>(&self, predicate: F) -> Option<usize>
     where
-        P: Fn(char) -> bool,
+        F: Fn(char) -> bool,
     {
         let (a, b) = self.to_strs();
         if let Some(pos) = b.find(|x: char| predicate(x)) {
             Some(a.len() + pos)
-        } else if let Some(pos) = a.find(|x: char| predicate(x)) {
-            Some(pos)
         } else {
-            None
+            a.find(|x: char| predicate(x))
         }
     } //find
 
@@ -437,11 +614,7 @@ impl<const N: usize> cstr<N> {
                 }
             } // for each intermediate position
         }
-        if let Some(pos) = b.find(s) {
-            return Some(a.len() + pos);
-        } else {
-            None
-        }
+        b.find(s).map(|pos| a.len() + pos)
     } //find_substr
 
     /// finds position of last matching substring
@@ -459,12 +632,163 @@ impl<const N: usize> cstr<N> {
                 }
             } //for
         }
-        if let Some(pos) = a.find(s) {
-            Some(pos)
+        a.find(s)
+    } //find_substr
+
+    /// iterator over the parts of the string separated by the [StrPattern]
+    /// `pat`, as with [str::split], but which yields strings of type `T`.
+    /// Matches that straddle the wrap point are found without making the
+    /// string contiguous.  See [SplitInto].
+    pub fn split_into<T: FixedstrMake>(&self, pat: impl StrPattern) -> SplitInto<'_, T, impl StrPattern> {
+        let (a, b) = self.to_strs();
+        SplitInto::new(a, b, pat, usize::MAX)
+    }
+
+    /// version of [cstr::split_into] that yields at most `n` parts, the
+    /// last being the rest of the string, as with [str::splitn]
+    pub fn splitn_into<T: FixedstrMake>(&self, n: usize, pat: impl StrPattern) -> SplitInto<'_, T, impl StrPattern> {
+        let (a, b) = self.to_strs();
+        SplitInto::new(a, b, pat, n)
+    }
+
+    /// iterator over the whitespace-separated words of the string, as with
+    /// [str::split_whitespace], that yields strings of type `T`
+    pub fn split_whitespace_into<T: FixedstrMake>(&self) -> SplitInto<'_, T, fn(char) -> bool> {
+        let (a, b) = self.to_strs();
+        SplitInto::new(a, b, char::is_whitespace as fn(char) -> bool, usize::MAX).skipping_empty()
+    }
+
+    /// iterator over the lines of the string, as with [str::lines], that
+    /// yields strings of type `T`
+    pub fn lines_into<T: FixedstrMake>(&self) -> SplitInto<'_, T, char> {
+        let (a, b) = self.to_strs();
+        SplitInto::new(a, b, '\n', usize::MAX).splitting_lines()
+    }
+
+    // replaces the bytes in range r with `with`, moving whichever of the
+    // parts before and after the range is shorter
+    fn splice(&mut self, r: Range<usize>, with: &str) -> Result<(), CapacityError> {
+        let len = self.len();
+        let required = len - r.len() + with.len();
+        if required > N {
+            return Err(CapacityError::Overflow { required, available: N });
+        }
+        let mut buf = [0u8; N];
+        if r.start < len - r.end {
+            for (i, b) in buf[..r.start].iter_mut().enumerate() {
+                *b = self.chrs[self.index(i)];
+            }
+            self.truncate_left(r.end);
+            self.push_front_bytes(with.as_bytes());
+            self.push_front_bytes(&buf[..r.start]);
         } else {
-            None
+            for (i, b) in buf[..len - r.end].iter_mut().enumerate() {
+                *b = self.chrs[self.index(r.end + i)];
+            }
+            self.truncate_right(r.start);
+            self.push_bytes(with.as_bytes());
+            self.push_bytes(&buf[..len - r.end]);
         }
-    } //find_substr
+        Ok(())
+    } //splice
+
+    // the parts of the two slices of the string that make up range r
+    fn range_strs(&self, r: Range<usize>) -> (&str, &str) {
+        let (a, b) = self.to_strs();
+        let al = a.len();
+        (
+            &a[min(r.start, al)..min(r.end, al)],
+            &b[r.start.saturating_sub(al)..r.end.saturating_sub(al)],
+        )
+    }
+
+    /// inserts a character at byte index `idx`, as `String::insert` does.
+    /// Returns false (leaving the string unchanged) if the result would
+    /// not fit.  Because of the circular-queue backing, only the shorter
+    /// of the parts before and after `idx` is moved, so that inserting
+    /// near the front is as cheap as inserting near the end.  **Panics**
+    /// if `idx` is larger than the length or not on a character boundary.
+    pub fn insert(&mut self, idx: usize, c: char) -> bool {
+        let mut buf = [0u8; 4];
+        self.insert_str(idx, c.encode_utf8(&mut buf)).is_ok()
+    }
+
+    /// inserts a string at byte index `idx`, as `String::insert_str` does,
+    /// or returns a [CapacityError] (leaving the string unchanged) if the
+    /// result would not fit.  See [cstr::insert].
+    pub fn insert_str(&mut self, idx: usize, s: &str) -> Result<(), CapacityError> {
+        self.replace_range(idx..idx, s)
+    }
+
+    /// removes and returns the character at byte index `idx`, as
+    /// `String::remove` does, moving only the shorter part of the string.
+    /// **Panics** if `idx` is not less than the length or not on a
+    /// character boundary.
+    pub fn remove(&mut self, idx: usize) -> char {
+        assert!(idx < self.len() && self.is_char_boundary(idx), "cannot remove a char at byte {}", idx);
+        let (a, b) = self.range_strs(idx..self.len());
+        let c = a.chars().chain(b.chars()).next().unwrap();
+        self.drain(idx..idx + c.len_utf8());
+        c
+    }
+
+    /// removes the given range of bytes from the string and returns it as
+    /// a new string, moving only the shorter of the parts before and after
+    /// the range.  **Panics** if the range is out of bounds or not on
+    /// character boundaries.
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> cstr<N, P> {
+        let r = crate::byte_range(self.len(), range, |i| self.is_char_boundary(i));
+        let (a, b) = self.range_strs(r.clone());
+        let drained = cstr::from_pair(a, b).unwrap();
+        self.splice(r, "").unwrap();
+        drained
+    }
+
+    /// keeps only the characters for which `f` returns true, as
+    /// `String::retain` does
+    pub fn retain<F: FnMut(char) -> bool>(&mut self, mut f: F) {
+        let mut kept = cstr::<N, P>::new();
+        for c in self.chars().filter(|c| f(*c)) {
+            kept.push_char(c);
+        }
+        *self = kept;
+    }
+
+    /// replaces the given range of bytes with a string, as
+    /// `String::replace_range` does, or returns a [CapacityError] (leaving
+    /// the string unchanged) if the result would not fit.  **Panics** if
+    /// the range is out of bounds or not on character boundaries.
+    pub fn replace_range<R: RangeBounds<usize>>(&mut self, range: R, with: &str) -> Result<(), CapacityError> {
+        let r = crate::byte_range(self.len(), range, |i| self.is_char_boundary(i));
+        self.splice(r, with)
+    }
+
+    /// changes the character at *character position* n to c, returning
+    /// true on success.  Unlike [cstr::set], the new character may have a
+    /// different utf8 length than the one it replaces, in which case the
+    /// shorter part of the string is shifted.  Returns false (leaving the
+    /// string unchanged) only if n is out of range or the result would not
+    /// fit.
+    pub fn replace_nth(&mut self, n: usize, c: char) -> bool {
+        let mut idx = 0;
+        for old in self.chars().take(n) {
+            idx += old.len_utf8();
+        }
+        self.replace_at(idx, c)
+    }
+
+    /// version of [cstr::replace_nth] that changes the character starting
+    /// at *byte* index `idx`.  Returns false if `idx` is not the start of a
+    /// character of the string or the result would not fit.
+    pub fn replace_at(&mut self, idx: usize, c: char) -> bool {
+        if idx >= self.len() || !self.is_char_boundary(idx) {
+            return false;
+        }
+        let (a, b) = self.range_strs(idx..self.len());
+        let old = a.chars().chain(b.chars()).next().unwrap();
+        let mut buf = [0u8; 4];
+        self.replace_range(idx..idx + old.len_utf8(), c.encode_utf8(&mut buf)).is_ok()
+    }
 
     /// **in-place** trimming of white spaces at the front of the string
     pub fn trim_left(&mut self) {
@@ -477,6 +801,9 @@ impl<const N: usize> cstr<N> {
         } else {
             offset = (a.len() + b.len()) as u16;
         }
+        if offset == 0 {
+            return;
+        }
         self.front = self.index16(offset); //((self.front as usize + offset)%N) as u16;
         self.len -= offset;
     } //trim_left
@@ -485,20 +812,14 @@ impl<const N: usize> cstr<N> {
     pub fn trim_right(&mut self) {
         let (a, b) = self.to_strs();
         let offset;
-        if b.len() == 0 {
-            if let Some(k) = a.rfind(|c: char| !c.is_whitespace()) {
-                offset = a.len() - k - 1;
-            } else {
-                offset = a.len();
-            }
+        if b.is_empty() {
+            offset = a.len() - a.trim_end().len();
         }
         //contiguous
-        else if let Some(i) = b.rfind(|c: char| !c.is_whitespace()) {
-            offset = b.len() - i - 1;
-        } else if let Some(k) = a.rfind(|c: char| !c.is_whitespace()) {
-            offset = b.len() + (a.len() - k - 1)
+        else if !b.trim_end().is_empty() {
+            offset = b.len() - b.trim_end().len();
         } else {
-            offset = a.len() + b.len();
+            offset = b.len() + (a.len() - a.trim_end().len());
         }
         self.len -= offset as u16;
     } //trim_right
@@ -524,23 +845,62 @@ impl<const N: usize> cstr<N> {
 
     /// length of string in bytes
     #[inline(always)]
+    #[allow(clippy::len_without_is_empty)] // is_empty is provided by Fixedstr
     pub const fn len(&self) -> usize {
         self.len as usize
     }
 
+    /// checks if byte position n of the string is at a character
+    /// boundary.  Positions 0 and [Self::len] are boundaries.
+    pub const fn is_char_boundary(&self, n: usize) -> bool {
+        n == 0 || n >= self.len as usize || (self.chrs[self.index(n)] & 0xC0) != 0x80
+    }
+
+    // every write to the underlying array goes through here, to maintain
+    // the ghost vector
+    #[inline(always)]
+    const fn put(&mut self, i: usize, b: u8) {
+        self.chrs[i] = b;
+        if i < 3 {
+            self.ghost[i] = b;
+        }
+    }
+
+    // writes the bytes to the array starting at index i, through `put`
+    const fn put_bytes(&mut self, i: usize, bytes: &[u8]) {
+        let mut k = 0;
+        while k < bytes.len() {
+            self.put(i + k, bytes[k]);
+            k += 1;
+        }
+    }
+
+    // the array followed by the ghost vector
+    fn chrs_and_ghost(&self) -> &[u8] {
+        debug_assert!(self.ghost[..min(3, N)] == self.chrs[..min(3, N)], "stale ghost vector");
+        // Safety: with repr(C), the u8 array `ghost` immediately follows
+        // the u8 array `chrs` and both are initialized.  The pointer is
+        // derived from the whole struct, not from `self.chrs`.
+        unsafe { core::slice::from_raw_parts((self as *const Self).cast::<u8>(), N + 3) }
+    }
+
     /// construct new, empty string (same as `cstr::default`)
     #[inline(always)]
     pub const fn new() -> Self {
+        const { assert!(1 <= N && N <= 65535, "cstr<N, P> requires 1 <= N <= 65535") };
         cstr {
             chrs: [0; N],
+            ghost: [0; 3],
             front: 0,
             len: 0,
+            policy: PhantomData,
         }
     } //new
 
     /// returns a pair of string slices `(left,right)` which, when concatenated,
     /// will yield an equivalent string underneath.  In case of no wraparound,
-    /// the right str will be empty.
+    /// the right str will be empty.  A character that wraps around the end
+    /// of the underlying array is always part of the left slice.
     pub fn to_strs(&self) -> (&str, &str) {
         let answer;
         if self.len() == 0 {
@@ -552,9 +912,16 @@ impl<const N: usize> cstr<N> {
                 "",
             )
         } else {
+            // continuation bytes at the start of the array belong to a
+            // char that begins at the end, read through the ghost vector
+            let end = self.endi();
+            let mut g = 0;
+            while g < end && (self.chrs[g] & 0xC0) == 0x80 {
+                g += 1;
+            }
             answer = (
-                core::str::from_utf8(&self.chrs[self.front as usize..]).unwrap(),
-                core::str::from_utf8(&self.chrs[..self.endi()]).unwrap(),
+                core::str::from_utf8(&self.chrs_and_ghost()[self.front as usize..N + g]).unwrap(),
+                core::str::from_utf8(&self.chrs[g..end]).unwrap(),
             )
         }
         answer
@@ -562,19 +929,10 @@ impl<const N: usize> cstr<N> {
 
     /// returns iterator over the characters of the string
     pub fn chars<'a>(&'a self) -> CircCharIter<'a> {
-        let contig = self.is_contiguous();
+        let (a, b) = self.to_strs();
         CircCharIter {
-            first: if contig {
-                &self.chrs[self.front as usize..(self.front + self.len) as usize]
-            } else {
-                &self.chrs[self.front as usize..]
-            },
-            second: if contig {
-                &[]
-            } else {
-                &self.chrs[..self.endi()]
-            },
-            index: 0,
+            first: a.chars(),
+            second: b.chars(),
         }
     } //chars
 
@@ -585,7 +943,7 @@ impl<const N: usize> cstr<N> {
 
     /// returns a copy of the same string that is contiguous underneath.
     /// This may call [cstr::reset], which is an O(n) operation.
-    pub fn to_contiguous(&self) -> cstr<N> {
+    pub fn to_contiguous(&self) -> cstr<N, P> {
         let mut c = *self;
         if !c.is_contiguous() {
             c.reset();
@@ -597,7 +955,7 @@ impl<const N: usize> cstr<N> {
     /// otherwise panics.
     pub fn force_str(&self) -> &str {
         let (a, b) = self.to_strs();
-        if b.len() > 0 {
+        if !b.is_empty() {
             panic!("cstr cannot be transformed into a single str slice without calling reset()");
         }
         a
@@ -605,24 +963,29 @@ impl<const N: usize> cstr<N> {
 
     /// converts cstr to an owned string
     #[cfg(not(feature = "no-alloc"))]
+    #[allow(clippy::inherent_to_string_shadow_display)] // no ToString import needed
     pub fn to_string(&self) -> alloc::string::String {
         let (a, b) = self.to_strs();
         let mut s = alloc::string::String::from(a);
-        if b.len() > 0 {
+        if !b.is_empty() {
             s.push_str(b);
         }
         s
     } //to_string
 
     /// returns a copy of the portion of the string.  Will return empty
-    /// string if indices are invalid. The returned string will be contiguous.
-    pub fn substr(&self, start: usize, end: usize) -> cstr<N> {
-        let mut s = cstr::<N>::default();
-        if (end <= start || start as u16 > self.len - 1 || end > self.len as usize) {
+    /// string if indices are invalid or not at character boundaries. The
+    /// returned string will be contiguous.
+    pub fn substr(&self, start: usize, end: usize) -> cstr<N, P> {
+        let mut s = cstr::<N, P>::default();
+        if (end <= start || start as u16 > self.len - 1 || end > self.len as usize)
+            || !self.is_char_boundary(start)
+            || !self.is_char_boundary(end)
+        {
             return s;
         }
         for i in start..end {
-            s.chrs[i - start] = self.chrs[self.index(i)];
+            s.put(i - start, self.chrs[self.index(i)]);
         }
         s.len = (end - start) as u16;
         s
@@ -631,9 +994,9 @@ impl<const N: usize> cstr<N> {
     /// in-place modification of ascii characters to lower-case.
     pub fn make_ascii_lowercase(&mut self) {
         for i in 0..self.len as usize {
-            let b = &mut self.chrs[self.index(i)];
-            if *b >= 65 && *b <= 90 {
-                *b |= 32;
+            let b = self.chrs[self.index(i)];
+            if b.is_ascii_uppercase() {
+                self.put(self.index(i), b | 32);
             }
         }
     } //make_ascii_lowercase
@@ -641,9 +1004,9 @@ impl<const N: usize> cstr<N> {
     /// in-place modification of ascii characters to upper-case.
     pub fn make_ascii_uppercase(&mut self) {
         for i in 0..self.len as usize {
-            let b = &mut self.chrs[self.index(i)];
-            if *b >= 97 && *b <= 122 {
-                *b -= 32;
+            let b = self.chrs[self.index(i)];
+            if b.is_ascii_lowercase() {
+                self.put(self.index(i), b - 32);
             }
         }
     } //make_ascii_uppercase
@@ -658,14 +1021,13 @@ impl<const N: usize> cstr<N> {
             return false;
         }
         let obytes = other.as_ref().as_bytes();
-        for i in 0..self.len() {
+        for (i, mut d) in obytes.iter().copied().enumerate() {
             let mut c = self.chrs[(self.front as usize + i) % N];
             if (c > 64 && c < 91) {
-                c = c | 32;
+                c |= 32;
             } // make lowercase
-            let mut d = obytes[i];
             if (d > 64 && d < 91) {
-                d = d | 32;
+                d |= 32;
             } // make lowercase
             if c != d {
                 return false;
@@ -674,20 +1036,20 @@ impl<const N: usize> cstr<N> {
         true
     } //case_insensitive_eq
 
-    /// Decodes a UTF-16 encodeded slice. If a decoding error is encountered
-    /// or capacity exceeded, an `Err(s)` is returned where s is the
-    /// the encoded string up to the point of the error.  The returned
-    /// string will be contiguous.
-    pub fn from_utf16(v: &[u16]) -> Result<Self, Self> {
+    /// Decodes a UTF-16 encodeded slice.  Returns an error if the slice is
+    /// not valid utf16 or if the decoded string exceeds the capacity.
+    /// The returned string will be contiguous.
+    pub fn from_utf16(v: &[u16]) -> Result<Self, CapacityError> {
+        let required = crate::utf16_len(v)?;
+        if required > N {
+            return Err(CapacityError::Overflow {
+                required,
+                available: N,
+            });
+        }
         let mut s = Self::new();
-        for c in char::decode_utf16(v.iter().cloned()) {
-            if let Ok(c1) = c {
-                if !s.push_char(c1) {
-                    return Err(s);
-                }
-            } else {
-                return Err(s);
-            }
+        for c in char::decode_utf16(v.iter().cloned()).flatten() {
+            s.push_char(c);
         }
         Ok(s)
     } //from_utf16
@@ -725,44 +1087,175 @@ impl<const N: usize> cstr<N> {
 } //main impl
   ///////////////////////////////////////////////////////////////
 
-impl<const M: usize> cstr<M> {
+impl<const M: usize, P: OverflowPolicy> cstr<M, P> {
     /// converts an `cstr<M>` to an `cstr<N>`. If the length of the string being
-    /// converted is greater than N, the extra characters are ignored.
+    /// converted is greater than N, the overflow is handled according to
+    /// the policy `P` (by default, the extra characters are ignored).
     /// This operation produces a new string that is contiguous underneath.
-    pub fn resize<const N: usize>(&self) -> cstr<N> {
-        let slen = self.len();
-        let length = if (slen < N) { slen } else { N };
-        let mut s = cstr::<N>::default();
+    pub fn resize<const N: usize>(&self) -> cstr<N, P> {
+        let mut s = cstr::<N, P>::new();
         let (a, b) = self.to_strs();
-        s.chrs[..a.len()].copy_from_slice(a.as_bytes());
-        if b.len() > 0 {
-            s.chrs[a.len()..].copy_from_slice(b.as_bytes());
-        }
-        s.len = self.len;
+        s.push_policy(a, false);
+        s.push_policy(b, false);
         s
     } //resize
 
     /// version of resize that does not allow string truncation due to length
-    pub fn reallocate<const N: usize>(&self) -> Option<cstr<N>> {
+    pub fn reallocate<const N: usize>(&self) -> Option<cstr<N, P>> {
         if self.len() < N {
             Some(self.resize())
         } else {
             None
         }
     }
+
+    /// converts to a `cstr<N>` at least as large as `self`, which unlike
+    /// [Self::resize] can never truncate: it is checked at compile time
+    /// that a `cstr<N>` can hold any `cstr<M>`.
+    /// ```
+    ///  # use fixedstr::*;
+    ///  let s = cstr::<8>::make("abcdefgh");
+    ///  let t: cstr<16> = s.grow(); // s.grow::<4>() would not compile
+    ///  assert_eq!(t, "abcdefgh");
+    /// ```
+    pub fn grow<const N: usize>(&self) -> cstr<N, P> {
+        crate::assert_grows::<Self, cstr<N, P>>();
+        self.resize()
+    }
+
+    /// concatenates `self` and `other` into a new `cstr<N>`.  It is checked
+    /// at compile time that a `cstr<N>` can hold the result, so nothing is
+    /// ever truncated.  `other` can be of any size and of any type
+    /// implementing [FixedCapacity](crate::FixedCapacity).
+    /// ```
+    ///  # use fixedstr::*;
+    ///  let a = cstr::<8>::make("abc");
+    ///  let b = cstr::<4>::make("def");
+    ///  let c = a.concat::<12>(&b); // a.concat::<11>(&b) would not compile
+    ///  assert_eq!(c, "abcdef");
+    /// ```
+    pub fn concat<const N: usize>(&self, other: &impl crate::FixedCapacity) -> cstr<N, P> {
+        crate::assert_concat_fits::<Self, _, cstr<N, P>>(other);
+        let mut cat: cstr<N, P> = self.resize();
+        crate::append_all(&mut cat, other);
+        cat
+    }
 } //impl cstr<M>
 
-impl<const N: usize> Default for cstr<N> {
-    fn default() -> Self {
-        cstr {
-            chrs: [0; N],
-            front: 0,
-            len: 0,
+impl<const N: usize, P: OverflowPolicy> cstr<N, P> {
+    /// version of [cstr::push_str] that can be called from a const context
+    pub const fn const_push_str<'t>(&mut self, src: &'t str) -> &'t str {
+        let (mut keep, n, ellipsis) = crate::plan_push(self.len(), src.as_bytes(), N, P::MODE, true);
+        while !self.is_char_boundary(keep) {
+            keep -= 1;
+        }
+        if keep < self.len() {
+            self.len = keep as u16;
+        }
+        self.const_push_bytes(src.as_bytes(), n);
+        if ellipsis {
+            self.const_push_bytes(ELLIPSIS.as_bytes(), ELLIPSIS.len());
+        }
+        src.split_at(n).1
+    } //const_push_str
+
+    /// version of `==` with a `&str` that can be called from a const context
+    pub const fn const_eq(&self, other: &str) -> bool {
+        crate::const_fns::eq(self.const_bytes(), ConstBytes::of(other))
+    }
+
+    /// version of [Ord::cmp] with a `&str` that can be called from a
+    /// const context
+    pub const fn const_cmp(&self, other: &str) -> Ordering {
+        crate::const_fns::cmp(self.const_bytes(), ConstBytes::of(other))
+    }
+
+    /// version of [str::starts_with] that can be called from a const context
+    pub const fn const_starts_with(&self, prefix: &str) -> bool {
+        crate::const_fns::starts_with(self.const_bytes(), ConstBytes::of(prefix))
+    }
+
+    /// returns the byte position of the first occurrence of `pat`, like
+    /// [str::find] but callable from a const context
+    pub const fn const_find(&self, pat: &str) -> Option<usize> {
+        crate::const_fns::find(self.const_bytes(), ConstBytes::of(pat))
+    }
+
+    /// version of [cstr::substr] that can be called from a const context:
+    /// returns a copy of bytes `start..end`, or an empty string if the
+    /// indices are invalid or not at character boundaries
+    pub const fn const_substr(&self, start: usize, end: usize) -> cstr<N, P> {
+        if end <= start || end > self.len() || !self.is_char_boundary(start) || !self.is_char_boundary(end) {
+            return cstr::new();
+        }
+        self.const_range(start, end)
+    }
+
+    /// returns a copy of the string with only upper-case ascii characters,
+    /// like [str::to_ascii_uppercase] but callable from a const context
+    pub const fn const_to_ascii_upper(&self) -> cstr<N, P> {
+        self.const_map_ascii(true)
+    }
+
+    /// returns a copy of the string with only lower-case ascii characters,
+    /// like [str::to_ascii_lowercase] but callable from a const context
+    pub const fn const_to_ascii_lower(&self) -> cstr<N, P> {
+        self.const_map_ascii(false)
+    }
+
+    /// returns a copy of the string without leading and trailing
+    /// whitespace, like [str::trim] but callable from a const context
+    pub const fn const_trim(&self) -> cstr<N, P> {
+        let (start, end) = crate::const_fns::trim(self.const_bytes());
+        self.const_range(start, end)
+    }
+
+    // the bytes of the string, for the const methods
+    const fn const_bytes(&self) -> ConstBytes<'_> {
+        ConstBytes::new(&self.chrs, self.front as usize, self.len())
+    }
+
+    // pushes the first n bytes
+    const fn const_push_bytes(&mut self, bytes: &[u8], n: usize) {
+        let mut i = 0;
+        while i < n {
+            self.put(self.index(self.len() + i), bytes[i]);
+            i += 1;
+        }
+        self.len += n as u16;
+    }
+
+    // contiguous copy of bytes start..end, which must be at character boundaries
+    const fn const_range(&self, start: usize, end: usize) -> cstr<N, P> {
+        let mut s = cstr::new();
+        let mut i = start;
+        while i < end {
+            s.put(i - start, self.chrs[self.index(i)]);
+            i += 1;
+        }
+        s.len = (end - start) as u16;
+        s
+    }
+
+    const fn const_map_ascii(&self, upper: bool) -> cstr<N, P> {
+        let mut s = *self;
+        let mut i = 0;
+        while i < self.len() {
+            let b = s.chrs[s.index(i)];
+            s.put(s.index(i), if upper { b.to_ascii_uppercase() } else { b.to_ascii_lowercase() });
+            i += 1;
         }
+        s
+    }
+} //const methods
+
+impl<const N: usize, P: OverflowPolicy> Default for cstr<N, P> {
+    fn default() -> Self {
+        cstr::new()
     }
 } //impl default
 
-impl<const N: usize> core::fmt::Debug for cstr<N> {
+impl<const N: usize, P: OverflowPolicy> core::fmt::Debug for cstr<N, P> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         let (a, b) = self.to_strs();
         f.pad(a)?;
@@ -770,7 +1263,7 @@ impl<const N: usize> core::fmt::Debug for cstr<N> {
     }
 } // Debug impl
 
-impl<const N: usize> core::fmt::Display for cstr<N> {
+impl<const N: usize, P: OverflowPolicy> core::fmt::Display for cstr<N, P> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         let (a, b) = self.to_strs();
         //write!(f, "{}{}", a, b)
@@ -780,30 +1273,14 @@ impl<const N: usize> core::fmt::Display for cstr<N> {
 }
 
 /////////// need Eq, Ord, etc.  and special iterator implementation
-impl<const N: usize> PartialEq<&str> for cstr<N> {
+impl<const N: usize, P: OverflowPolicy> PartialEq<&str> for cstr<N, P> {
     fn eq(&self, other: &&str) -> bool {
-        &self == other
+        self == *other
     } //eq
 }
 
-impl<const N: usize> PartialEq<&str> for &cstr<N> {
-    fn eq(&self, other: &&str) -> bool {
-        let (a, b) = self.to_strs();
-        let (alen, blen) = (a.len(), b.len());
-        alen + blen == other.len() && a == &other[..alen] && (blen == 0 || b == &other[alen..])
-    } //eq
-}
-
-impl<const N: usize> PartialEq<cstr<N>> for &str {
-    fn eq(&self, other: &cstr<N>) -> bool {
-        let (a, b) = other.to_strs();
-        let (alen, blen) = (a.len(), b.len());
-        alen + blen == self.len() && a == &self[..alen] && (blen == 0 || b == &self[alen..])
-    } //eq
-}
-
-impl<const N: usize> PartialEq<&cstr<N>> for &str {
-    fn eq(&self, other: &&cstr<N>) -> bool {
+impl<const N: usize, P: OverflowPolicy> PartialEq<cstr<N, P>> for &str {
+    fn eq(&self, other: &cstr<N, P>) -> bool {
         let (a, b) = other.to_strs();
         let (alen, blen) = (a.len(), b.len());
         alen + blen == self.len() && a == &self[..alen] && (blen == 0 || b == &self[alen..])
@@ -812,30 +1289,28 @@ impl<const N: usize> PartialEq<&cstr<N>> for &str {
 
 /// character interator, returned by [cstr::chars] (available with `circular-str` option along with [cstr])
 pub struct CircCharIter<'a> {
-    first: &'a [u8],
-    second: &'a [u8],
-    index: usize,
+    first: core::str::Chars<'a>,
+    second: core::str::Chars<'a>,
 }
 impl<'a> Iterator for CircCharIter<'a> {
     type Item = char;
     fn next(&mut self) -> Option<Self::Item> {
-        if self.index < self.first.len() {
-            self.index += 1;
-            Some(self.first[self.index - 1] as char)
-        } else if self.index - self.first.len() < self.second.len() {
-            self.index += 1;
-            Some(self.second[self.index - self.first.len() - 1] as char)
-        } else {
-            None
-        }
+        self.first.next().or_else(|| self.second.next())
     } //next
 } // impl CircCharIter
+impl<'a> DoubleEndedIterator for CircCharIter<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.second.next_back().or_else(|| self.first.next_back())
+    }
+}
 
 /// The implementation of this trait allows comparison between
 /// circular strings of different capacity.  This could affect the
 /// type inference of the [cstr::resize] function.
-impl<const N: usize, const M: usize> PartialEq<cstr<M>> for cstr<N> {
-    fn eq(&self, other: &cstr<M>) -> bool {
+impl<const N: usize, const M: usize, P: OverflowPolicy, Q: OverflowPolicy> PartialEq<cstr<M, Q>>
+    for cstr<N, P>
+{
+    fn eq(&self, other: &cstr<M, Q>) -> bool {
         if self.len != other.len {
             return false;
         }
@@ -865,9 +1340,9 @@ impl<const N: usize, const M: usize> PartialEq<cstr<M>> for cstr<N> {
         */
     } //eq for Self
 } // PartialEq
-impl<const N: usize> Eq for cstr<N> {}
+impl<const N: usize, P: OverflowPolicy> Eq for cstr<N, P> {}
 
-impl<const N: usize> Ord for cstr<N> {
+impl<const N: usize, P: OverflowPolicy> Ord for cstr<N, P> {
     fn cmp(&self, other: &Self) -> Ordering {
         let mut schars = self.chars();
         let mut ochars = other.chars();
@@ -896,7 +1371,7 @@ impl<const N: usize> Ord for cstr<N> {
     } //cmp
 } //Ord
 
-impl<const N: usize> PartialOrd for cstr<N> {
+impl<const N: usize, P: OverflowPolicy> PartialOrd for cstr<N, P> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
         /*
@@ -917,156 +1392,164 @@ impl<const N: usize> PartialOrd for cstr<N> {
     } //partial_cmp
 } // PartialOrd
 
-impl<const N: usize> PartialOrd<&str> for cstr<N> {
-    fn partial_cmp(&self, other: &&str) -> Option<Ordering> {
-        let mut schars = self.chars();
-        let mut ochars = other.chars();
-        let mut answer = Ordering::Equal;
-        loop {
-            match (schars.next(), ochars.next()) {
-                (Some(x), Some(y)) if x.cmp(&y) == Ordering::Equal => {}
-                (Some(x), Some(y)) => {
-                    answer = x.cmp(&y);
-                    break;
-                }
-                (None, None) => {
-                    break;
-                }
-                (None, _) => {
-                    answer = Ordering::Less;
-                    break;
-                }
-                (_, None) => {
-                    answer = Ordering::Greater;
-                    break;
-                }
-            } //match
-        } //loop
-        Some(answer)
-    } //partial_cmp
-} // PartialOrd
-
-impl<const N: usize> PartialOrd<&str> for &cstr<N> {
-    fn partial_cmp(&self, other: &&str) -> Option<Ordering> {
-        let mut schars = self.chars();
-        let mut ochars = other.chars();
-        let mut answer = Ordering::Equal;
-        loop {
-            match (schars.next(), ochars.next()) {
-                (Some(x), Some(y)) if x.cmp(&y) == Ordering::Equal => {}
-                (Some(x), Some(y)) => {
-                    answer = x.cmp(&y);
-                    break;
-                }
-                (None, None) => {
-                    break;
-                }
-                (None, _) => {
-                    answer = Ordering::Less;
-                    break;
-                }
-                (_, None) => {
-                    answer = Ordering::Greater;
-                    break;
-                }
-            } //match
-        } //loop
-        Some(answer)
-    } //partial_cmp
-} // PartialOrd
-
-/// Hashing is implemented character-by-character, starting with the
-/// last char and ending with the first
-impl<const N: usize> core::hash::Hash for cstr<N> {
+/// Hashing is consistent with that of `str`, so that a `cstr` hashes the
+/// same as its contents.  A string that wraps around is first copied to
+/// a contiguous one.  Since a `cstr` cannot implement `Borrow<str>`, maps
+/// keyed by `cstr` can be looked up by `&str` with the `equivalent` option,
+/// as in [hashbrown](https://docs.rs/hashbrown) and
+/// [indexmap](https://docs.rs/indexmap).
+impl<const N: usize, P: OverflowPolicy> core::hash::Hash for cstr<N, P> {
     fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
-        for i in (0..self.len as usize).rev() {
-            self.nth_bytechar(i).hash(state);
+        match self.to_strs() {
+            (a, "") => a.hash(state),
+            _ => self.to_contiguous().to_strs().0.hash(state),
         }
-        //for c in self.chars() { c.hash(state); }
     }
 } //hash
 
-impl<T: AsRef<str> + ?Sized, const N: usize> core::convert::From<&T> for cstr<N> {
-    fn from(s: &T) -> cstr<N> {
-        cstr::make(s.as_ref())
+/// Collects characters or strings (see [crate::StrPiece]), truncating at
+/// the first item that does not fit as the [OverflowPolicy] dictates
+///
+/// # Panics
+/// Panics if an item does not fit under the [crate::Strict] or
+/// [crate::Panic] policy: use [cstr::try_from_iter] to detect overflow.
+impl<T: crate::StrPiece, const N: usize, P: OverflowPolicy> core::iter::FromIterator<T> for cstr<N, P> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut s = cstr::new();
+        s.extend(iter);
+        s
     }
 }
-impl<T: AsMut<str> + ?Sized, const N: usize> core::convert::From<&mut T> for cstr<N> {
-    fn from(s: &mut T) -> cstr<N> {
-        cstr::make(s.as_mut())
+
+/// Pushes the items to the back of the string until one does not fit,
+/// which is handled according to the [OverflowPolicy].  See also
+/// [cstr::extend_front].
+///
+/// # Panics
+/// Panics if an item does not fit under the [crate::Strict] or
+/// [crate::Panic] policy: use [cstr::try_extend] to detect overflow.
+impl<T: crate::StrPiece, const N: usize, P: OverflowPolicy> core::iter::Extend<T> for cstr<N, P> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        crate::extend_pieces(iter, P::MODE, |s| self.push_str(s).is_empty());
     }
 }
 
-impl<const N: usize> core::fmt::Write for cstr<N> {
+/// Allows maps keyed by `cstr`, such as those of hashbrown and indexmap,
+/// to be looked up by `&str` (available with the `equivalent` option).
+#[cfg(feature = "equivalent")]
+impl<const N: usize, P: OverflowPolicy> equivalent::Equivalent<cstr<N, P>> for str {
+    fn equivalent(&self, key: &cstr<N, P>) -> bool {
+        key == self
+    }
+}
+
+impl<const N: usize, P: OverflowPolicy> core::fmt::Write for cstr<N, P> {
+    /// pushes `s` according to the overflow policy (by default, as much
+    /// as fits), returning an error if anything had to be left out.
     fn write_str(&mut self, s: &str) -> core::fmt::Result {
-        if s.len() + self.len() > N {
-            return Err(core::fmt::Error::default());
+        if !self.push_str(s).is_empty() {
+            return Err(core::fmt::Error);
         }
-        self.push_str(s);
         Ok(())
     } //write_str
 } //core::fmt::Write trait
 
-impl<const N: usize, TA: AsRef<str>> Add<TA> for cstr<N> {
-    type Output = cstr<N>;
-    fn add(self, other: TA) -> cstr<N> {
+impl<const N: usize, TA: AsRef<str> + ?Sized, P: OverflowPolicy> Add<&TA> for cstr<N, P> {
+    type Output = cstr<N, P>;
+    fn add(self, other: &TA) -> cstr<N, P> {
         let mut a2 = self;
-        a2.push_str(other.as_ref());
+        a2.push_policy(other.as_ref(), false);
         a2
     }
 } //Add AsRef<str>
 
-impl<const N: usize> Add<&cstr<N>> for &str {
-    type Output = cstr<N>;
-    fn add(self, other: &cstr<N>) -> cstr<N> {
+impl<const N: usize, P: OverflowPolicy> Add<&cstr<N, P>> for &str {
+    type Output = cstr<N, P>;
+    fn add(self, other: &cstr<N, P>) -> cstr<N, P> {
         let mut a2 = *other;
-        a2.push_front(self);
+        a2.push_front_policy(self, false);
         a2
     }
 } //Add &str on left
 
-impl<const N: usize> Add<cstr<N>> for &str {
-    type Output = cstr<N>;
-    fn add(self, mut other: cstr<N>) -> cstr<N> {
-        other.push_front(self);
+impl<const N: usize, P: OverflowPolicy> Add<cstr<N, P>> for &str {
+    type Output = cstr<N, P>;
+    fn add(self, mut other: cstr<N, P>) -> cstr<N, P> {
+        other.push_front_policy(self, false);
         other
     }
 } //Add &str on left
 
-impl<const N: usize> Add for &cstr<N> {
-    type Output = cstr<N>;
-    fn add(self, other: &cstr<N>) -> cstr<N> {
+impl<const N: usize, P: OverflowPolicy> Add for &cstr<N, P> {
+    type Output = cstr<N, P>;
+    fn add(self, other: &cstr<N, P>) -> cstr<N, P> {
         let mut a2 = *self;
         let (l, r) = other.to_strs();
-        a2.push_str(l);
-        if r.len() > 0 {
-            a2.push_str(r);
-        }
+        a2.push_policy(l, false);
+        a2.push_policy(r, false);
         a2
     }
 } //Add &str
 
-impl<const N: usize> Add for cstr<N> {
-    type Output = cstr<N>;
-    fn add(self, other: cstr<N>) -> cstr<N> {
+impl<const N: usize, P: OverflowPolicy> Add<&cstr<N, P>> for cstr<N, P> {
+    type Output = cstr<N, P>;
+    fn add(self, other: &cstr<N, P>) -> cstr<N, P> {
         let mut a2 = self;
         let (l, r) = other.to_strs();
-        a2.push_str(l);
-        if r.len() > 0 {
-            a2.push_str(r);
-        }
+        a2.push_policy(l, false);
+        a2.push_policy(r, false);
         a2
     }
 } //Add
 
-impl<const N: usize> core::str::FromStr for cstr<N> {
-    type Err = &'static str;
+impl<const N: usize, P: OverflowPolicy> core::str::FromStr for cstr<N, P> {
+    type Err = CapacityError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.len() <= N {
-            Ok(cstr::from(s))
-        } else {
-            Err("capacity exceeded")
-        }
+        cstr::try_make(s)
+    }
+}
+
+impl<const N: usize, P: OverflowPolicy> crate::FixedCapacity for cstr<N, P> {
+    const CAPACITY: usize = N;
+}
+
+impl<const N: usize, P: OverflowPolicy> crate::Fixedstr for cstr<N, P> {
+    fn len(&self) -> usize {
+        self.len()
+    }
+    fn capacity(&self) -> usize {
+        N
+    }
+    fn with_segments(&self, f: &mut dyn FnMut(&str, &str)) {
+        let (a, b) = self.to_strs();
+        f(a, b)
+    }
+    fn push_str<'t>(&mut self, src: &'t str) -> &'t str {
+        self.push_str(src)
+    }
+    fn push_char(&mut self, c: char) -> bool {
+        self.push_char(c)
+    }
+    fn pop_char(&mut self) -> Option<char> {
+        self.pop_char()
+    }
+    fn truncate_bytes(&mut self, n: usize) {
+        self.truncate(n)
+    }
+    fn clear(&mut self) {
+        self.clear()
+    }
+}
+
+impl<const N: usize, P: OverflowPolicy> crate::FixedstrMake for cstr<N, P> {
+    fn make(s: &str) -> Self {
+        cstr::make(s)
+    }
+    fn try_make(s: &str) -> Result<Self, CapacityError> {
+        cstr::try_make(s)
+    }
+    fn new() -> Self {
+        cstr::new()
     }
 }
 
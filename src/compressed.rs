@@ -0,0 +1,377 @@
+//! **This module is only available with the `compressed-str` option.**
+//! Packed string types over restricted alphabets that store the entire
+//! string in a single `u64` or `u128`.  See [r40str12] for an overview.
+
+use crate::{CapacityError, OverflowPolicy, tstr, zstr};
+
+/// symbols of the radix-40 alphabet in ascii order, code 0 being padding
+const RADIX40: &[u8] = b"\0-.0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ_";
+/// symbols of the 6-bit alphabet in ascii order, code 0 being padding
+const BASE64: &[u8] = b"\x000123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ_abcdefghijklmnopqrstuvwxyz";
+
+/// inverse of an alphabet: the code of each byte, or 0 if not encodable
+const fn codes(alphabet: &[u8]) -> [u8; 256] {
+    let mut table = [0u8; 256];
+    let mut i = 1;
+    while i < alphabet.len() {
+        table[alphabet[i] as usize] = i as u8;
+        i += 1;
+    }
+    table
+}
+const RADIX40_CODES: [u8; 256] = codes(RADIX40);
+const BASE64_CODES: [u8; 256] = codes(BASE64);
+
+macro_rules! packed_str {
+    ($(#[$doc:meta])* $name:ident, $word:ty, $chars:ident, $codes:ident, $cap:literal, $unpacked:literal) => {
+        $(#[$doc])*
+        #[derive(Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name($word);
+
+        impl $name {
+            const RADIX: $word = $chars.len() as $word;
+
+            // the value of a code at character position i
+            const fn weight(i: usize) -> $word {
+                Self::RADIX.pow(($cap - 1 - i) as u32)
+            }
+
+            // the code at character position i
+            const fn code(&self, i: usize) -> usize {
+                ((self.0 / Self::weight(i)) % Self::RADIX) as usize
+            }
+
+            /// creates an empty string
+            pub const fn new() -> Self {
+                $name(0)
+            }
+
+            /// const constructor that returns a [CapacityError] if the
+            /// string is too long or has a character outside of the alphabet
+            pub const fn const_try_make(s: &str) -> Result<Self, CapacityError> {
+                let b = s.as_bytes();
+                if b.len() > $cap {
+                    return Err(CapacityError::Overflow {
+                        required: b.len(),
+                        available: $cap,
+                    });
+                }
+                let mut w = 0;
+                let mut i = 0;
+                while i < b.len() {
+                    let c = $codes[b[i] as usize];
+                    if c == 0 {
+                        return Err(CapacityError::Unencodable { position: i });
+                    }
+                    w += c as $word * Self::weight(i);
+                    i += 1;
+                }
+                Ok($name(w))
+            } //const_try_make
+
+            /// const constructor that **panics** (fails to compile in a
+            /// const context) if the string cannot be represented
+            pub const fn const_make(s: &str) -> Self {
+                match Self::const_try_make(s) {
+                    Ok(p) => p,
+                    Err(_) => panic!("string cannot be represented by the packed type"),
+                }
+            }
+
+            /// creates a packed string, returning a [CapacityError] if the
+            /// string is too long or has a character outside of the alphabet
+            pub fn try_make(s: &str) -> Result<Self, CapacityError> {
+                Self::const_try_make(s)
+            }
+
+            /// creates a packed string from the longest prefix of `s` that
+            /// can be represented
+            pub fn make(s: &str) -> Self {
+                let mut p = Self::new();
+                p.push_str(s);
+                p
+            }
+
+            /// length of the string in bytes (and characters)
+            pub const fn len(&self) -> usize {
+                let mut n = 0;
+                while n < $cap && self.code(n) != 0 {
+                    n += 1;
+                }
+                n
+            }
+
+            /// whether the string is empty
+            pub const fn is_empty(&self) -> bool {
+                self.0 == 0
+            }
+
+            /// the maximum number of characters
+            pub const fn capacity(&self) -> usize {
+                $cap
+            }
+
+            /// returns the nth character, if it exists
+            pub const fn nth(&self, n: usize) -> Option<char> {
+                if n < $cap && self.code(n) != 0 {
+                    Some($chars[self.code(n)] as char)
+                } else {
+                    None
+                }
+            }
+
+            /// appends the longest prefix of `src` that can be represented
+            /// and fits, returning the rest
+            pub fn push_str<'t>(&mut self, src: &'t str) -> &'t str {
+                let mut len = self.len();
+                for (i, b) in src.bytes().enumerate() {
+                    let c = $codes[b as usize];
+                    if c == 0 || len == $cap {
+                        return &src[i..];
+                    }
+                    self.0 += c as $word * Self::weight(len);
+                    len += 1;
+                }
+                ""
+            } //push_str
+
+            /// appends a character, returning false if it cannot be
+            /// represented or does not fit
+            pub fn push_char(&mut self, c: char) -> bool {
+                let mut buf = [0u8; 4];
+                self.push_str(c.encode_utf8(&mut buf)).is_empty()
+            }
+
+            /// removes and returns the last character, if any
+            pub fn pop_char(&mut self) -> Option<char> {
+                let n = self.len();
+                if n == 0 {
+                    return None;
+                }
+                let c = self.code(n - 1);
+                self.0 -= c as $word * Self::weight(n - 1);
+                Some($chars[c] as char)
+            }
+
+            /// keeps only the first n characters; no effect if n is not
+            /// less than the length
+            pub fn truncate(&mut self, n: usize) {
+                if n < $cap {
+                    let w = Self::weight(n) * Self::RADIX;
+                    self.0 = self.0 / w * w;
+                }
+            }
+
+            /// resets the string to empty
+            pub fn clear(&mut self) {
+                self.0 = 0;
+            }
+
+            /// the underlying integer representation
+            pub const fn to_bits(&self) -> $word {
+                self.0
+            }
+
+            /// recreates a packed string from [Self::to_bits], returning
+            /// `None` if the integer is not a valid representation
+            pub const fn from_bits(w: $word) -> Option<Self> {
+                if w / Self::weight(0) >= Self::RADIX {
+                    return None;
+                }
+                let p = $name(w);
+                let mut i = p.len();
+                while i < $cap {
+                    if p.code(i) != 0 {
+                        return None;
+                    }
+                    i += 1;
+                }
+                Some(p)
+            } //from_bits
+
+            // writes the characters into buf, returning the str
+            fn decode<'b>(&self, buf: &'b mut [u8; $cap]) -> &'b str {
+                let n = self.len();
+                for (i, b) in buf[..n].iter_mut().enumerate() {
+                    *b = $chars[self.code(i)];
+                }
+                core::str::from_utf8(&buf[..n]).unwrap()
+            }
+
+            /// calls the closure with the unpacked string
+            pub fn with_str<F, U>(&self, f: F) -> U
+            where
+                F: FnOnce(&str) -> U,
+            {
+                let mut buf = [0u8; $cap];
+                f(self.decode(&mut buf))
+            }
+
+            /// unpacks into a [zstr] of sufficient capacity
+            pub fn to_zstr(&self) -> zstr<$unpacked> {
+                self.with_str(zstr::make)
+            }
+
+            /// unpacks into a [tstr] of sufficient capacity
+            pub fn to_tstr(&self) -> tstr<$unpacked> {
+                self.with_str(tstr::make)
+            }
+
+            /// unpacks into an owned string
+            #[cfg(not(feature = "no-alloc"))]
+            pub fn to_string(&self) -> alloc::string::String {
+                self.with_str(|s| alloc::string::String::from(s))
+            }
+        } //impl $name
+
+        impl core::fmt::Display for $name {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                self.with_str(|s| f.pad(s))
+            }
+        }
+
+        impl core::fmt::Debug for $name {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                self.with_str(|s| f.pad(s))
+            }
+        }
+
+        impl core::str::FromStr for $name {
+            type Err = CapacityError;
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                $name::try_make(s)
+            }
+        }
+
+        impl TryFrom<&str> for $name {
+            type Error = CapacityError;
+            fn try_from(s: &str) -> Result<Self, Self::Error> {
+                $name::try_make(s)
+            }
+        }
+
+        impl<const N: usize, P: OverflowPolicy> TryFrom<zstr<N, P>> for $name {
+            type Error = CapacityError;
+            fn try_from(s: zstr<N, P>) -> Result<Self, Self::Error> {
+                $name::try_make(s.as_str())
+            }
+        }
+
+        impl<const N: usize, P: OverflowPolicy> TryFrom<tstr<N, P>> for $name {
+            type Error = CapacityError;
+            fn try_from(s: tstr<N, P>) -> Result<Self, Self::Error> {
+                $name::try_make(s.as_str())
+            }
+        }
+
+        impl From<$name> for zstr<$unpacked> {
+            fn from(p: $name) -> Self {
+                p.to_zstr()
+            }
+        }
+
+        impl From<$name> for tstr<$unpacked> {
+            fn from(p: $name) -> Self {
+                p.to_tstr()
+            }
+        }
+
+        impl PartialEq<&str> for $name {
+            fn eq(&self, other: &&str) -> bool {
+                self.with_str(|s| s == *other)
+            }
+        }
+
+        impl PartialEq<$name> for &str {
+            fn eq(&self, other: &$name) -> bool {
+                other == self
+            }
+        }
+
+        impl crate::Fixedstr for $name {
+            fn len(&self) -> usize {
+                self.len()
+            }
+            fn capacity(&self) -> usize {
+                $cap
+            }
+            fn with_segments(&self, f: &mut dyn FnMut(&str, &str)) {
+                self.with_str(|s| f(s, ""))
+            }
+            fn push_str<'t>(&mut self, src: &'t str) -> &'t str {
+                self.push_str(src)
+            }
+            fn push_char(&mut self, c: char) -> bool {
+                self.push_char(c)
+            }
+            fn pop_char(&mut self) -> Option<char> {
+                self.pop_char()
+            }
+            fn truncate_bytes(&mut self, n: usize) {
+                self.truncate(n)
+            }
+            fn clear(&mut self) {
+                self.clear()
+            }
+        }
+
+        impl crate::FixedstrMake for $name {
+            fn make(s: &str) -> Self {
+                $name::make(s)
+            }
+            fn try_make(s: &str) -> Result<Self, CapacityError> {
+                $name::try_make(s)
+            }
+            fn new() -> Self {
+                $name::new()
+            }
+        }
+    };
+} //packed_str
+
+#[cfg(not(feature = "no-alloc"))]
+extern crate alloc;
+
+packed_str!(
+    /// Up to 12 characters of `A-Z`, `0-9`, `-`, `.` and `_`, packed in a
+    /// `u64` using a radix-40 encoding.
+    ///
+    /// This is one of the packed string types available with the
+    /// `compressed-str` option, which store short identifiers (ticker
+    /// symbols, airport codes, enum tags, etc.) over a restricted alphabet
+    /// in a single integer: [r40str12] and [r40str24] use a radix-40
+    /// encoding, while [b6str10] and [b6str21] use 6 bits per character
+    /// for the alphabet `A-Z`, `a-z`, `0-9` and `_`.  The encodings
+    /// preserve the ordering of `&str`, so that comparisons and sorting are
+    /// done on the underlying integer.  Conversions from other string types
+    /// fail with [CapacityError::Unencodable] if a character is outside of
+    /// the alphabet, while conversions into [zstr] and [tstr] of sufficient
+    /// capacity are lossless:
+    /// ```
+    ///   # use fixedstr::*;
+    ///   const ICAO: r40str12 = r40str12::const_make("KJFK");
+    ///   let t: r40str12 = "KLAX".parse().unwrap();
+    ///   assert!(ICAO < t && t == "KLAX" && t.len() == 4);
+    ///   assert_eq!(core::mem::size_of::<r40str12>(), 8);
+    ///   let z: zstr<16> = t.into();
+    ///   assert_eq!(r40str12::try_from(z), Ok(t));
+    ///   assert_eq!(r40str12::try_make("klax"), Err(CapacityError::Unencodable { position: 0 }));
+    ///   assert_eq!(b6str10::try_make("klax").unwrap().to_string(), "klax");
+    /// ```
+    r40str12, u64, RADIX40, RADIX40_CODES, 12, 16
+);
+packed_str!(
+    /// Up to 24 characters of `A-Z`, `0-9`, `-`, `.` and `_`, packed in a
+    /// `u128` using a radix-40 encoding.  See [r40str12].
+    r40str24, u128, RADIX40, RADIX40_CODES, 24, 32
+);
+packed_str!(
+    /// Up to 10 characters of `A-Z`, `a-z`, `0-9` and `_`, packed in a
+    /// `u64` with 6 bits per character.  See [r40str12].
+    b6str10, u64, BASE64, BASE64_CODES, 10, 16
+);
+packed_str!(
+    /// Up to 21 characters of `A-Z`, `a-z`, `0-9` and `_`, packed in a
+    /// `u128` with 6 bits per character.  See [r40str12].
+    b6str21, u128, BASE64, BASE64_CODES, 21, 32
+);
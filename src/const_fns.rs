@@ -0,0 +1,139 @@
+//! Byte-level helpers behind the `const_` methods of [tstr](crate::tstr),
+//! [zstr](crate::zstr), `fstr` and `cstr`, which can use neither iterators
+//! nor the methods of `str` that are not `const`.
+
+use core::cmp::Ordering;
+
+/// `len` bytes starting at index `front` of `buf`, wrapping around the end
+/// of `buf` as in a `cstr`.  Contiguous strings never wrap.
+#[derive(Clone, Copy)]
+pub(crate) struct ConstBytes<'a> {
+    buf: &'a [u8],
+    front: usize,
+    len: usize,
+}
+
+impl<'a> ConstBytes<'a> {
+    pub(crate) const fn new(buf: &'a [u8], front: usize, len: usize) -> Self {
+        ConstBytes { buf, front, len }
+    }
+
+    pub(crate) const fn of(s: &'a str) -> Self {
+        ConstBytes::new(s.as_bytes(), 0, s.len())
+    }
+
+    // the ith byte, i < len
+    const fn at(&self, i: usize) -> u8 {
+        self.buf[(self.front + i) % self.buf.len()]
+    }
+
+    // decodes the character starting at byte i, returning it with its length
+    const fn char_at(&self, i: usize) -> (char, usize) {
+        let b = self.at(i);
+        let n = if b < 0x80 { 1 } else if b < 0xE0 { 2 } else if b < 0xF0 { 3 } else { 4 };
+        let mut c = if n == 1 { b as u32 } else { (b & (0x7F >> n)) as u32 };
+        let mut k = 1;
+        while k < n {
+            c = (c << 6) | (self.at(i + k) & 0x3F) as u32;
+            k += 1;
+        }
+        match char::from_u32(c) {
+            Some(c) => (c, n),
+            None => (char::REPLACEMENT_CHARACTER, n),
+        }
+    }
+
+    // whether `pat` occurs at byte i
+    const fn matches_at(&self, pat: ConstBytes, i: usize) -> bool {
+        if i + pat.len > self.len {
+            return false;
+        }
+        let mut k = 0;
+        while k < pat.len {
+            if self.at(i + k) != pat.at(k) {
+                return false;
+            }
+            k += 1;
+        }
+        true
+    }
+} //impl ConstBytes
+
+/// lexicographic comparison, which for utf8 agrees with that of `str`
+pub(crate) const fn cmp(a: ConstBytes, b: ConstBytes) -> Ordering {
+    let mut i = 0;
+    while i < a.len && i < b.len {
+        let (x, y) = (a.at(i), b.at(i));
+        if x != y {
+            return if x < y { Ordering::Less } else { Ordering::Greater };
+        }
+        i += 1;
+    }
+    if a.len < b.len {
+        Ordering::Less
+    } else if a.len > b.len {
+        Ordering::Greater
+    } else {
+        Ordering::Equal
+    }
+} //cmp
+
+pub(crate) const fn eq(a: ConstBytes, b: ConstBytes) -> bool {
+    a.len == b.len && a.matches_at(b, 0)
+}
+
+pub(crate) const fn starts_with(s: ConstBytes, prefix: ConstBytes) -> bool {
+    s.matches_at(prefix, 0)
+}
+
+/// byte position of the first occurrence of `pat`
+pub(crate) const fn find(s: ConstBytes, pat: ConstBytes) -> Option<usize> {
+    let mut i = 0;
+    while i + pat.len <= s.len {
+        if s.matches_at(pat, i) {
+            return Some(i);
+        }
+        i += 1;
+    }
+    None
+} //find
+
+/// byte position of the character at (character) position `n`, or the
+/// length of the string if there are not that many characters
+pub(crate) const fn char_to_byte(s: ConstBytes, n: usize) -> usize {
+    let (mut i, mut k) = (0, 0);
+    while i < s.len {
+        if (s.at(i) & 0xC0) != 0x80 {
+            if k == n {
+                return i;
+            }
+            k += 1;
+        }
+        i += 1;
+    }
+    s.len
+} //char_to_byte
+
+/// byte range of the string without its leading and trailing whitespace
+pub(crate) const fn trim(s: ConstBytes) -> (usize, usize) {
+    let mut start = 0;
+    while start < s.len {
+        let (c, n) = s.char_at(start);
+        if !c.is_whitespace() {
+            break;
+        }
+        start += n;
+    }
+    let mut end = s.len;
+    while end > start {
+        let mut i = end - 1;
+        while (s.at(i) & 0xC0) == 0x80 {
+            i -= 1;
+        }
+        if !s.char_at(i).0.is_whitespace() {
+            break;
+        }
+        end = i;
+    }
+    (start, end)
+} //trim
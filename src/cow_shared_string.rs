@@ -0,0 +1,518 @@
+#![allow(unused_variables)]
+#![allow(non_snake_case)]
+#![allow(non_camel_case_types)]
+#![allow(unused_parens)]
+#![allow(unused_assignments)]
+#![allow(unused_mut)]
+#![allow(unused_imports)]
+#![allow(dead_code)]
+use crate::shared_structs::Strunion;
+use crate::shared_structs::Strunion::*;
+use crate::tstr;
+use crate::CapacityError;
+use core::cmp::Ordering;
+use core::ops::Add;
+
+extern crate alloc;
+use alloc::rc::Rc;
+use alloc::string::String;
+
+/// **This type is only available with the 'shared-str' option.**
+/// A `CowSharedstr` is a copy-on-write alternative to [crate::Sharedstr].
+/// Cloning is still done in constant time by sharing an [Rc], but a
+/// mutation through a handle that is not the only pointer to the string
+/// first detaches a private copy (as with [Rc::make_mut]), so that the
+/// other clones are never affected.  A `CowSharedstr` therefore behaves
+/// like an immutable value with cheap sharing, and, unlike a `Sharedstr`,
+/// it can safely lend out a `&str` and implements `Deref<Target=str>`.
+/// Like `Sharedstr<N>`, a `CowSharedstr<N>` is represented either by a
+/// `tstr<N>` or by an owned string if its length is greater than N-1, for
+/// N up to 256.
+/// Example:
+/// ```
+///  # use fixedstr::*;
+///  let mut a = CowSharedstr::<8>::from("abc12");
+///  let b = a.clone();
+///  assert!(a.ptr_eq(&b) && !a.is_unique());
+///  a.push('3');   // a detaches, b is unchanged
+///  assert!(a == "abc123" && b == "abc12");
+///  assert!(a.is_unique() && b.is_unique());
+/// ```
+/// This type **does not support serde**.
+#[derive(Clone, Eq)]
+pub struct CowSharedstr<const N: usize = 32> {
+    inner: Rc<Strunion<N>>,
+}
+impl<const N: usize> CowSharedstr<N> {
+    /// Creates a new `CowSharedstr<N>` with given &str.
+    pub fn make(s: &str) -> Self {
+        CowSharedstr {
+            inner: Rc::new(Strunion::make(s)),
+        }
+    } //make
+
+    /// Creates a `CowSharedstr<N>` by consuming a given string.  However,
+    /// if the string has length less than N, then a fixed representation
+    /// will be used.
+    pub fn from_string(s: String) -> Self {
+        CowSharedstr {
+            inner: Rc::new(Strunion::from_string(s)),
+        }
+    }
+
+    /// creates a `CowSharedstr<N>` from a given `tstr<N>`
+    pub fn from_tstr(s: tstr<N>) -> Self {
+        CowSharedstr {
+            inner: Rc::new(fixed(s)),
+        }
+    }
+
+    /// Provided for uniformity with the other string types: since a
+    /// `CowSharedstr` never truncates, this function always succeeds.
+    pub fn try_make(s: &str) -> Result<CowSharedstr<N>, CapacityError> {
+        Ok(CowSharedstr::make(s))
+    }
+
+    /// creates an empty string, equivalent to [CowSharedstr::default]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// length of the string in bytes. This is a constant-time operation.
+    #[allow(clippy::len_without_is_empty)] // is_empty is provided by Fixedstr
+    pub fn len(&self) -> usize {
+        self.as_str().len()
+    } //len
+
+    /// length in number of characters as opposed to bytes: this is
+    /// not necessarily a constant time operation.
+    pub fn charlen(&self) -> usize {
+        self.as_str().chars().count()
+    } //charlen
+
+    /// converts to `&str` type
+    pub fn as_str(&self) -> &str {
+        match &*self.inner {
+            fixed(s) => s.as_str(),
+            owned(s) => s.as_str(),
+        } //match
+    }
+
+    /// alias for [Self::as_str]
+    pub fn to_str(&self) -> &str {
+        self.as_str()
+    } //to_str
+
+    /// returns the underlying bytes of the string
+    pub fn as_bytes(&self) -> &[u8] {
+        self.as_str().as_bytes()
+    } //as_bytes
+
+    /// retrieves a copy of the underlying fixed string, if it is a fixed string.
+    pub fn get_str(&self) -> Option<tstr<N>> {
+        if let fixed(s) = &*self.inner {
+            Some(*s)
+        } else {
+            None
+        }
+    } //get_str
+
+    /// this function returns a possibly cloned string: the owned string
+    /// is moved out without copying if this is the only pointer to it.
+    #[allow(clippy::inherent_to_string_shadow_display)] // no ToString import needed
+    pub fn to_string(self) -> String {
+        match Rc::try_unwrap(self.inner) {
+            Ok(owned(s)) => s,
+            Ok(fixed(s)) => s.to_string(),
+            Err(r) => String::from(CowSharedstr { inner: r }.as_str()),
+        } //match
+    } //to_string
+
+    /// returns the nth char of the string, if it exists
+    pub fn nth(&self, n: usize) -> Option<char> {
+        self.as_str().chars().nth(n)
+    }
+
+    /// returns whether the internal representation is a fixed string (tstr)
+    pub fn is_fixed(&self) -> bool {
+        matches!(&*self.inner, fixed(_))
+    } //is_fixed
+
+    /// returns whether the internal representation is an owned String
+    pub fn is_owned(&self) -> bool {
+        !self.is_fixed()
+    }
+
+    /// whether this is the only pointer to the string, so that it can be
+    /// mutated without copying
+    pub fn is_unique(&self) -> bool {
+        Rc::strong_count(&self.inner) == 1
+    }
+
+    /// detaches a private copy of the string if it is currently shared.
+    /// Called implicitly by all mutating operations.
+    pub fn make_unique(&mut self) {
+        Rc::make_mut(&mut self.inner);
+    }
+
+    /// the underlying representation, copied first if shared
+    fn unique_mut(&mut self) -> &mut Strunion<N> {
+        Rc::make_mut(&mut self.inner)
+    }
+
+    /// changes a character at character position i to c.  This function
+    /// requires that c is in the same character class (ascii or unicode)
+    /// as the char being replaced.  It never shuffles the bytes underneath.
+    /// The function returns true if the change was successful.  The string
+    /// is detached from its clones only if the change is made.
+    pub fn set(&mut self, i: usize, c: char) -> bool {
+        let bi = match self.as_str().char_indices().nth(i) {
+            Some((bi, rc)) if rc.len_utf8() == c.len_utf8() => bi,
+            _ => return false,
+        };
+        match self.unique_mut() {
+            fixed(s) => s.set(i, c),
+            owned(s) => {
+                let cbuf = &mut [0u8; 4];
+                s.replace_range(bi..bi + c.len_utf8(), c.encode_utf8(cbuf));
+                true
+            }
+        } //match
+    } //set
+
+    /// appends the CowSharedstr with the given slice,
+    /// switching to the owned-String representation if necessary.  The function
+    /// returns true if the resulting string uses a `tstr<N>` type, and
+    /// false if the representation is an owned string.
+    pub fn push_str(&mut self, s: &str) -> bool {
+        let u = self.unique_mut();
+        match u {
+            fixed(fs) if fs.len() + s.len() < N => {
+                fs.push(s);
+                true
+            }
+            fixed(fs) => {
+                *u = owned(fs.to_string() + s);
+                false
+            }
+            owned(ns) => {
+                ns.push_str(s);
+                false
+            }
+        } //match
+    } //push_str
+
+    /// appends string with a single character, switching to the String
+    /// representation if necessary.  Returns true if resulting string
+    /// remains fixed.
+    pub fn push(&mut self, c: char) -> bool {
+        let mut buf = [0u8; 4];
+        self.push_str(c.encode_utf8(&mut buf))
+    } //push
+
+    /// alias for push
+    pub fn push_char(&mut self, c: char) -> bool {
+        self.push(c)
+    }
+
+    /// remove and return last character in string, if it exists
+    pub fn pop(&mut self) -> Option<char> {
+        if self.is_empty() {
+            return None;
+        }
+        let u = self.unique_mut();
+        match u {
+            fixed(s) => s.pop_char(),
+            owned(s) if s.len() > N => s.pop(),
+            owned(s) => {
+                // change representation
+                let answer = s.pop();
+                *u = Strunion::from_string(core::mem::take(s));
+                answer
+            }
+        } //match
+    } //pop
+
+    /// alias for [Self::pop]
+    pub fn pop_char(&mut self) -> Option<char> {
+        self.pop()
+    }
+
+    /// this function truncates a string at the indicated byte position,
+    /// returning true if the truncated string is fixed, and false if owned.
+    /// The operation has no effect if n is larger than the length of the
+    /// string.  The operation will **panic** if n is not on a character
+    /// boundary, similar to [String::truncate].
+    pub fn truncate(&mut self, n: usize) -> bool {
+        if n >= self.len() {
+            return self.is_fixed();
+        }
+        let u = self.unique_mut();
+        match u {
+            fixed(fs) => {
+                fs.truncate_bytes(n);
+                true
+            }
+            owned(s) => {
+                s.truncate(n);
+                *u = Strunion::from_string(core::mem::take(s));
+                matches!(u, fixed(_))
+            }
+        } //match
+    } //truncate
+
+    /// resets string to empty.  A shared string is not copied: this handle
+    /// simply starts pointing to a new, empty string.
+    pub fn clear(&mut self) {
+        if self.is_unique() {
+            *self.unique_mut() = Strunion::make("");
+        } else {
+            *self = Self::new();
+        }
+    } //clear
+
+    /// returns string corresponding to slice indices as a copy or clone.
+    /// Returns an empty string if the indices are out of range or not at
+    /// character boundaries.
+    pub fn substr(&self, start: usize, end: usize) -> CowSharedstr<N> {
+        match self.as_str().get(start..end) {
+            Some(s) => CowSharedstr::make(s),
+            None => CowSharedstr::new(),
+        }
+    } //substr
+
+    /// tests if two instances of CowSharedstr point to the same location,
+    /// contrasts with `==`, which always tests for content-equality
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.inner, &other.inner)
+    }
+
+    /// creates a new, non-shared copy of the string
+    pub fn deep_clone(&self) -> Self {
+        CowSharedstr::make(self.as_str())
+    }
+
+    /// returns the number of handles sharing this string (strong `Rc`
+    /// pointers)
+    pub fn ptr_count(&self) -> usize {
+        Rc::strong_count(&self.inner)
+    }
+
+    /// in-place modification of ascii characters to lower-case
+    pub fn make_ascii_lowercase(&mut self) {
+        match self.unique_mut() {
+            fixed(s) => s.make_ascii_lowercase(),
+            owned(s) => s.make_ascii_lowercase(),
+        } //match
+    } //make_ascii_lowercase
+
+    /// in-place modification of ascii characters to upper-case
+    pub fn make_ascii_uppercase(&mut self) {
+        match self.unique_mut() {
+            fixed(s) => s.make_ascii_uppercase(),
+            owned(s) => s.make_ascii_uppercase(),
+        } //match
+    }
+
+    /// Tests for ascii case-insensitive equality with another string.
+    pub fn case_insensitive_eq<TA>(&self, other: TA) -> bool
+    where
+        TA: AsRef<str>,
+    {
+        self.as_str().eq_ignore_ascii_case(other.as_ref())
+    } //case_insensitive_eq
+
+    /// returns a copy/clone of the string with new fixed capacity M.
+    pub fn resize<const M: usize>(&self) -> CowSharedstr<M> {
+        CowSharedstr::make(self.as_str())
+    }
+} //impl CowSharedstr
+
+impl<const N: usize> Default for CowSharedstr<N> {
+    fn default() -> Self {
+        CowSharedstr {
+            inner: Rc::new(Strunion::make("")),
+        }
+    }
+}
+
+#[cfg(feature = "flex-str")]
+use crate::Flexstr;
+#[cfg(feature = "flex-str")]
+impl<const N: usize> CowSharedstr<N> {
+    /// converts and consumes CowSharedstr into a Flexstr, which is done
+    /// without copying if this is the only pointer to the string.  This
+    /// function is only available with the `flex-str` option.
+    pub fn to_flexstr(self) -> Flexstr<N> {
+        match Rc::unwrap_or_clone(self.inner) {
+            fixed(s) => Flexstr::from_tstr(s),
+            owned(s) => Flexstr::from_string(s),
+        }
+    } //to_flexstr
+} //impl CowSharedstr
+
+impl<const N: usize> core::ops::Deref for CowSharedstr<N> {
+    type Target = str;
+    fn deref(&self) -> &Self::Target {
+        self.as_str()
+    }
+}
+
+impl<T: AsRef<str> + ?Sized, const N: usize> core::convert::From<&T> for CowSharedstr<N> {
+    fn from(s: &T) -> Self {
+        Self::make(s.as_ref())
+    }
+}
+impl<T: AsMut<str> + ?Sized, const N: usize> core::convert::From<&mut T> for CowSharedstr<N> {
+    fn from(s: &mut T) -> Self {
+        Self::make(s.as_mut())
+    }
+}
+
+impl<const N: usize> core::convert::From<String> for CowSharedstr<N> {
+    /// *will consume owned string and convert it to a fixed
+    /// representation if its length is less than N*
+    fn from(s: String) -> Self {
+        CowSharedstr::from_string(s)
+    }
+} //from String
+
+impl<const N: usize> core::cmp::PartialOrd for CowSharedstr<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<const N: usize> core::cmp::Ord for CowSharedstr<N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl<const N: usize> core::convert::AsRef<str> for CowSharedstr<N> {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+/// Allows a `CowSharedstr` key of a `HashMap` or `BTreeMap` to be looked up by
+/// `&str`, as hashing and comparison are consistent with those of `str`.
+impl<const N: usize> core::borrow::Borrow<str> for CowSharedstr<N> {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> core::cmp::PartialEq for CowSharedstr<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+} //eq
+
+impl<const N: usize> PartialEq<&str> for CowSharedstr<N> {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    } //eq
+}
+impl<const N: usize> PartialEq<CowSharedstr<N>> for &str {
+    fn eq(&self, other: &CowSharedstr<N>) -> bool {
+        other.as_str() == *self
+    }
+}
+impl<const N: usize> core::hash::Hash for CowSharedstr<N> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state);
+    }
+} //hash
+
+impl<const N: usize> core::fmt::Debug for CowSharedstr<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.pad(self.as_str())
+    }
+} // Debug impl
+
+impl<const N: usize> core::fmt::Display for CowSharedstr<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+impl<const N: usize> core::fmt::Write for CowSharedstr<N> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.push_str(s);
+        Ok(())
+    } //write_str
+} //core::fmt::Write trait
+
+impl<const N: usize, TA: AsRef<str>> Add<TA> for &CowSharedstr<N> {
+    type Output = CowSharedstr<N>;
+    fn add(self, other: TA) -> Self::Output {
+        let mut a2 = self.clone();
+        a2.push_str(other.as_ref());
+        a2
+    }
+} //Add, Rhs = &str
+
+impl<const N: usize> Add<&CowSharedstr<N>> for &str {
+    type Output = CowSharedstr<N>;
+    fn add(self, other: &CowSharedstr<N>) -> CowSharedstr<N> {
+        let mut a2 = CowSharedstr::from(self);
+        a2.push_str(other);
+        a2
+    }
+} //Add &str on left
+
+impl<const N: usize> core::str::FromStr for CowSharedstr<N> {
+    type Err = CapacityError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(CowSharedstr::from(s))
+    }
+}
+
+impl<const N: usize> crate::Fixedstr for CowSharedstr<N> {
+    fn len(&self) -> usize {
+        self.len()
+    }
+    /// the inline capacity: strings longer than this are stored on the heap
+    fn capacity(&self) -> usize {
+        N - 1
+    }
+    fn with_segments(&self, f: &mut dyn FnMut(&str, &str)) {
+        f(self.as_str(), "")
+    }
+    fn push_str<'t>(&mut self, src: &'t str) -> &'t str {
+        CowSharedstr::push_str(self, src);
+        ""
+    }
+    fn push_char(&mut self, c: char) -> bool {
+        self.push(c);
+        true
+    }
+    fn pop_char(&mut self) -> Option<char> {
+        self.pop()
+    }
+    fn truncate_bytes(&mut self, n: usize) {
+        self.truncate(n);
+    }
+    fn clear(&mut self) {
+        self.clear()
+    }
+}
+
+impl<const N: usize> crate::FixedstrMake for CowSharedstr<N> {
+    fn make(s: &str) -> Self {
+        CowSharedstr::make(s)
+    }
+    fn try_make(s: &str) -> Result<Self, CapacityError> {
+        Ok(CowSharedstr::make(s))
+    }
+    fn new() -> Self {
+        CowSharedstr::new()
+    }
+}
+
+impl<const N: usize> crate::ContiguousFixedstr for CowSharedstr<N> {
+    fn as_str(&self) -> &str {
+        self.as_str()
+    }
+}
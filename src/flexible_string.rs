@@ -21,6 +21,7 @@ use crate::shared_structs::Strunion::*;
 use crate::{str12, str128, str16, str192, str24, str256, str32, str4, str48, str64, str8, str96};
 use alloc::string::String;
 use alloc::vec::Vec;
+use crate::CapacityError;
 use core::cmp::{min, Ordering};
 use core::ops::Add;
 
@@ -29,8 +30,8 @@ use core::ops::Add;
 /// the string is less than N bytes, and by an owned String otherwise.
 /// The structure satisfies the following axiom:
 /// >   *For N <= 256, a `Flexstr<N>` is represented internally by an
-///     owned String if and only if the length of the string is greater than
-///     or equal to N*.
+/// > owned String if and only if the length of the string is greater than
+/// > or equal to N*.
 ///
 /// For example, a `Flexstr<16>` will hold a string of up to 15 bytes
 /// in an u8-array of size 16. The first byte of the array holds the length of
@@ -74,26 +75,16 @@ pub struct Flexstr<const N: usize = 32> {
 impl<const N: usize> Flexstr<N> {
     /// Creates a new `Flexstr<N>` with given &str.
     pub fn make(s: &str) -> Self {
-        if s.len() < N && N <= 256 {
-            Flexstr {
-                inner: fixed(tstr::<N>::from(s)),
-            }
-        } else {
-            Flexstr {
-                inner: owned(String::from(s)),
-            }
+        Flexstr {
+            inner: Strunion::make(s),
         }
     } //make
 
     /// Creates a `Flexstr<N>` by consuming a given string.  However, if the
     /// string has length less than N, then a fixed representation will be used.
     pub fn from_string(s: String) -> Self {
-        if s.len() < N && N <= 256 {
-            Flexstr {
-                inner: fixed(tstr::<N>::from(&s[..])),
-            }
-        } else {
-            Flexstr { inner: owned(s) }
+        Flexstr {
+            inner: Strunion::from_string(s),
         }
     }
 
@@ -102,14 +93,15 @@ impl<const N: usize> Flexstr<N> {
         Flexstr { inner: fixed(s) }
     }
 
-    #[cfg(feature = "serde")]
-    /// this function is only added for uniformity in serde implementation
-    pub fn try_make(s: &str) -> Result<Flexstr<N>, &str> {
+    /// Provided for uniformity with the other string types: since a
+    /// `Flexstr` never truncates, this function always succeeds.
+    pub fn try_make(s: &str) -> Result<Flexstr<N>, CapacityError> {
         Ok(Flexstr::make(s))
     }
 
     /// length of the string in bytes. This is a constant-time operation.
     #[inline]
+    #[allow(clippy::len_without_is_empty)] // is_empty is provided by Fixedstr
     pub fn len(&self) -> usize {
         match &self.inner {
             fixed(s) => s.len(),
@@ -186,7 +178,7 @@ impl<const N: usize> Flexstr<N> {
     /// return the owned string, leaving an empty string in its place.
     pub fn take_string(&mut self) -> Option<String> {
         if let owned(s) = &mut self.inner {
-            let mut temp = fixed(tstr::new());
+            let mut temp = Strunion::make("");
             core::mem::swap(&mut self.inner, &mut temp);
             if let owned(t) = temp {
                 Some(t)
@@ -199,6 +191,7 @@ impl<const N: usize> Flexstr<N> {
     } //take_owned
 
     /// this function consumes the Flexstr and returns an owned string
+    #[allow(clippy::inherent_to_string_shadow_display)] // no ToString import needed
     pub fn to_string(self) -> String {
         match self.inner {
             fixed(s) => s.to_string(),
@@ -238,9 +231,13 @@ impl<const N: usize> Flexstr<N> {
 
    /// returns mutable u8-slice of string underneath.  Function requires
    /// call to [String::as_mut_str] and is therefore marked unsafe.
+   ///
+   /// # Safety
+   /// The caller must ensure that the bytes are valid UTF-8 when the
+   /// borrow ends.
     pub unsafe fn as_bytes_mut(&mut self) -> &mut [u8] {
         match &mut self.inner {
-            fixed(f) => f.as_bytes_mut(),
+            fixed(f) => unsafe { f.as_bytes_mut() },
             owned(s) => unsafe {s.as_mut_str().as_bytes_mut() },
         } //match
     }
@@ -248,28 +245,69 @@ impl<const N: usize> Flexstr<N> {
     /// changes a character at character position i to c.  This function
     /// requires that c is in the same character class (ascii or unicode)
     /// as the char being replaced.  It never shuffles the bytes underneath.
+    /// See [Flexstr::replace_nth] for a version without this requirement.
     /// The function returns true if the change was successful.
     pub fn set(&mut self, i: usize, c: char) -> bool {
         match &mut self.inner {
             fixed(s) => s.set(i, c),
             owned(s) => unsafe {
-                let ref mut cbuf = [0u8; 4];
+                let cbuf = &mut [0u8; 4];
                 c.encode_utf8(cbuf);
                 let clen = c.len_utf8();
-                if let Some((bi, rc)) = s.char_indices().nth(i) {
-                    if clen == rc.len_utf8() {
-                        s.as_bytes_mut()[bi..bi + clen].copy_from_slice(&cbuf[..clen]);
-                        //self.chrs[bi + 1..bi + clen + 1].copy_from_slice(&cbuf[..clen]);
-                        //for k in 0..clen {self.chrs[bi+k+1] = cbuf[k];}
-                        return true;
-                    }
+                if let Some((bi, rc)) = s.char_indices().nth(i)
+                    && clen == rc.len_utf8()
+                {
+                    s.as_bytes_mut()[bi..bi + clen].copy_from_slice(&cbuf[..clen]);
+                    //self.chrs[bi + 1..bi + clen + 1].copy_from_slice(&cbuf[..clen]);
+                    //for k in 0..clen {self.chrs[bi+k+1] = cbuf[k];}
+                    return true;
                 }
-                return false;
+                false
             },
         } //match
     } //set
 
+    /// changes the character at character position n to c, returning true
+    /// on success.  Unlike [Flexstr::set], the new character may have a
+    /// different utf8 length than the one it replaces, in which case the
+    /// rest of the string is shifted, switching to the String
+    /// representation if necessary.  Returns false only if n is out of
+    /// range.
+    pub fn replace_nth(&mut self, n: usize, c: char) -> bool {
+        match self.to_str().char_indices().nth(n) {
+            Some((i, _)) => self.replace_at(i, c),
+            None => false,
+        }
+    }
+
+    /// version of [Flexstr::replace_nth] that changes the character
+    /// starting at *byte* index `idx`.  Returns false if `idx` is not the
+    /// start of a character of the string.
+    pub fn replace_at(&mut self, idx: usize, c: char) -> bool {
+        let Some(old) = self.to_str().get(idx..).and_then(|t| t.chars().next()) else {
+            return false;
+        };
+        let range = idx..idx + old.len_utf8();
+        let mut buf = [0u8; 4];
+        let new = c.encode_utf8(&mut buf);
+        match &mut self.inner {
+            fixed(s) => {
+                if s.replace_range(range.clone(), new).is_err() {
+                    let mut fss = s.to_string();
+                    fss.replace_range(range, new);
+                    self.inner = owned(fss);
+                }
+            }
+            owned(s) => s.replace_range(range, new),
+        } //match
+        true
+    } //replace_at
+
     /// version of [Flexstr::set] that assumes that the char is a single byte. Sets the char at the given byte index. Does not check for index bounds. This function is designed to be fast.
+    ///
+    /// # Safety
+    /// `c` must be ascii and `i` must be the byte index of a single-byte
+    /// character inside the string.
     pub unsafe fn set_byte_char(&mut self, i:usize, c:char) {
        match &mut self.inner {
          fixed(s) => s.set_byte_char(i,c),
@@ -392,7 +430,7 @@ impl<const N: usize> Flexstr<N> {
 
     /// remove and return last character in string, if it exists
     pub fn pop(&mut self) -> Option<char> {
-        if self.len() == 0 {
+        if self.is_empty() {
             return None;
         }
         match &mut self.inner {
@@ -401,7 +439,7 @@ impl<const N: usize> Flexstr<N> {
             owned(s) => {
                 // change representation
                 let lastchar = s.pop();
-                self.inner = fixed(tstr::from(&s));
+                self.inner = Strunion::from_string(core::mem::take(s));
                 lastchar
             }
         } //match
@@ -424,16 +462,12 @@ impl<const N: usize> Flexstr<N> {
                 true
             }
             fixed(_) => true,
-            owned(s) if n < N => {
-                assert!(s.is_char_boundary(n));
-                self.inner = fixed(tstr::<N>::from(&s[..n]));
-                true
-            }
             owned(s) => {
                 if n < s.len() {
                     s.truncate(n);
                 }
-                false
+                self.inner = Strunion::from_string(core::mem::take(s));
+                self.is_fixed()
             }
         } //match
     } //truncate
@@ -445,7 +479,7 @@ impl<const N: usize> Flexstr<N> {
                 s.clear();
             }
             owned(s) => {
-                self.inner = fixed(tstr::default());
+                self.inner = Strunion::make("");
             }
         }
     } //clear
@@ -473,11 +507,13 @@ impl<const N: usize> Flexstr<N> {
     pub fn split_off(&mut self) -> String {
         match &mut self.inner {
             fixed(s) => String::default(),
-            owned(s) => {
-                let answer = String::from(&s[N - 1..]);
-                self.inner = fixed(tstr::<N>::from(&s[..N - 1]));
+            owned(s) if N <= 256 => {
+                let k = crate::floor_char_boundary(s.as_bytes(), N - 1);
+                let answer = String::from(&s[k..]);
+                self.inner = Strunion::make(&s[..k]);
                 answer
             }
+            owned(_) => String::default(),
         } //match
     } //split_off
 
@@ -519,11 +555,11 @@ impl<const N: usize> Flexstr<N> {
         for i in 0..self.len() {
             let mut c = sbytes[i];
             if (c > 64 && c < 91) {
-                c = c | 32;
+                c |= 32;
             } // make lowercase
             let mut d = obytes[i];
             if (d > 64 && d < 91) {
-                d = d | 32;
+                d |= 32;
             } // make lowercase
             if c != d {
                 return false;
@@ -532,19 +568,13 @@ impl<const N: usize> Flexstr<N> {
         true
     } //case_insensitive_eq
 
-    /// Decodes a UTF-16 encodeded slice. If a decoding error is encountered
-    /// or capacity exceeded, an `Err(s)` is returned where s is the
-    /// the encoded string up to the point of the error.
-    pub fn from_utf16(v: &[u16]) -> Result<Self, Self> {
+    /// Decodes a UTF-16 encodeded slice, returning an error if the slice
+    /// is not valid utf16.
+    pub fn from_utf16(v: &[u16]) -> Result<Self, CapacityError> {
+        crate::utf16_len(v)?;
         let mut s = Self::new();
-        for c in char::decode_utf16(v.iter().cloned()) {
-            if let Ok(c1) = c {
-                if !s.push_char(c1) {
-                    return Err(s);
-                }
-            } else {
-                return Err(s);
-            }
+        for c in char::decode_utf16(v.iter().cloned()).flatten() {
+            s.push(c);
         }
         Ok(s)
     } //from_utf16
@@ -553,20 +583,11 @@ impl<const N: usize> Flexstr<N> {
 impl<const N: usize> Default for Flexstr<N> {
     fn default() -> Self {
         Flexstr {
-            inner: fixed(tstr::<N>::default()),
+            inner: Strunion::make(""),
         }
     }
 }
 
-/*
-impl<const N:usize> core::hash::Hash for Flexstr<N>
-{
-  fn hash<H:core::hash::Hasher>(&self, state:&mut H) {
-    self.as_str().hash(state)
-  }
-}//hash
-*/
-
 impl<const N: usize> core::ops::Deref for Flexstr<N> {
     type Target = str;
     fn deref(&self) -> &Self::Target {
@@ -604,6 +625,33 @@ impl<const N: usize> core::convert::AsRef<str> for Flexstr<N> {
         self.to_str()
     }
 }
+
+/// Allows a `Flexstr` key of a `HashMap` or `BTreeMap` to be looked up by
+/// `&str`, as hashing and comparison are consistent with those of `str`.
+impl<const N: usize> core::borrow::Borrow<str> for Flexstr<N> {
+    fn borrow(&self) -> &str {
+        self.to_str()
+    }
+}
+
+/// Collects characters or strings (see [crate::StrPiece]), switching to
+/// the owned-String representation if necessary: this never truncates.
+impl<T: crate::StrPiece, const N: usize> core::iter::FromIterator<T> for Flexstr<N> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut s = Flexstr::new();
+        s.extend(iter);
+        s
+    }
+}
+
+impl<T: crate::StrPiece, const N: usize> core::iter::Extend<T> for Flexstr<N> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for piece in iter {
+            piece.with_str(|s| self.push_str(s));
+        }
+    }
+}
+
 impl<const N: usize> core::convert::AsMut<str> for Flexstr<N> {
     fn as_mut(&mut self) -> &mut str {
         match &mut self.inner {
@@ -619,25 +667,14 @@ impl<const N: usize> PartialEq<&str> for Flexstr<N> {
     } //eq
 }
 
-impl<const N: usize> PartialEq<&str> for &Flexstr<N> {
-    fn eq(&self, other: &&str) -> bool {
-        &self.to_str() == other
-    } //eq
-}
-impl<'t, const N: usize> PartialEq<Flexstr<N>> for &'t str {
+impl<const N: usize> PartialEq<Flexstr<N>> for &str {
     fn eq(&self, other: &Flexstr<N>) -> bool {
         &other.to_str() == self
     }
 }
-impl<'t, const N: usize> PartialEq<&Flexstr<N>> for &'t str {
-    fn eq(&self, other: &&Flexstr<N>) -> bool {
-        &other.to_str() == self
-    }
-}
-
 impl<const N: usize> core::fmt::Debug for Flexstr<N> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        f.pad(&self.to_str())
+        f.pad(self.to_str())
     }
 } // Debug impl
 
@@ -659,13 +696,7 @@ impl<const N: usize> core::convert::From<String> for Flexstr<N> {
     /// *will consume owned string and convert it to a fixed
     /// representation if its length is less than N*
     fn from(s: String) -> Self {
-        if s.len() >= N {
-            Flexstr { inner: owned(s) }
-        } else {
-            Flexstr {
-                inner: fixed(tstr::<N>::from(&s[..])),
-            }
-        }
+        Flexstr::from_string(s)
     }
 } //from String
 
@@ -770,12 +801,60 @@ impl<const N: usize> core::cmp::PartialEq for Flexstr<N> {
 } //eq
 
 impl<const N: usize> core::str::FromStr for Flexstr<N> {
-    type Err = &'static str;
+    type Err = CapacityError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(Flexstr::from(s))
     }
 }
 
+impl<const N: usize> crate::Fixedstr for Flexstr<N> {
+    fn len(&self) -> usize {
+        self.len()
+    }
+    /// the inline capacity: strings longer than this are stored on the heap
+    fn capacity(&self) -> usize {
+        N - 1
+    }
+    fn with_segments(&self, f: &mut dyn FnMut(&str, &str)) {
+        f(self.to_str(), "")
+    }
+    fn push_str<'t>(&mut self, src: &'t str) -> &'t str {
+        Flexstr::push_str(self, src);
+        ""
+    }
+    fn push_char(&mut self, c: char) -> bool {
+        self.push(c);
+        true
+    }
+    fn pop_char(&mut self) -> Option<char> {
+        self.pop()
+    }
+    fn truncate_bytes(&mut self, n: usize) {
+        self.truncate(n);
+    }
+    fn clear(&mut self) {
+        self.clear()
+    }
+}
+
+impl<const N: usize> crate::FixedstrMake for Flexstr<N> {
+    fn make(s: &str) -> Self {
+        Flexstr::make(s)
+    }
+    fn try_make(s: &str) -> Result<Self, CapacityError> {
+        Ok(Flexstr::make(s))
+    }
+    fn new() -> Self {
+        Flexstr::new()
+    }
+}
+
+impl<const N: usize> crate::ContiguousFixedstr for Flexstr<N> {
+    fn as_str(&self) -> &str {
+        self.to_str()
+    }
+}
+
 /// convenient type aliases for [Flexstr]
 pub type flexstr8 = Flexstr<8>;
 pub type flexstr16 = Flexstr<16>;
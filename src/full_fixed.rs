@@ -11,104 +11,127 @@
 extern crate std;
 use crate::tiny_internal::*;
 use crate::zero_terminated::*;
+use crate::const_fns::ConstBytes;
+use crate::CapacityError;
+use crate::{FixedstrMake, SplitInto, StrPattern};
+use crate::{OverflowMode, OverflowPolicy, Truncate};
 use core::cmp::{min, Ordering};
-use core::ops::Add;
+use core::marker::PhantomData;
+use core::ops::{Add, Range, RangeBounds};
 use std::eprintln;
 use std::string::String;
 
 /// **This type is only available with the `std` (or `fstr`) feature.**
 /// A `fstr<N>` is a string of up to const N bytes, using a separate variable to store the length.
 /// This type is not as memory-efficient as some other types such as str4-str256.  This is also the only type of the crate that does not support `no_std`.
+///
+/// The optional second parameter selects the [OverflowPolicy] (by default
+/// [Truncate]) that determines what happens when the capacity is exceeded.
 #[derive(Copy, Clone, Eq)]
-pub struct fstr<const N: usize> {
+pub struct fstr<const N: usize, P: OverflowPolicy = Truncate> {
     chrs: [u8; N],
     len: usize, // length will be <=N
+    policy: PhantomData<P>,
 } //fstr
-impl<const N: usize> fstr<N> {
+impl<const N: usize, P: OverflowPolicy> fstr<N, P> {
     /// creates a new `fstr<N>` with given &str.  If the length of s exceeds
-    /// N, the extra characters are ignored and a **warning is sent to stderr**.
-    pub fn make(s: &str) -> fstr<N> {
-        let bytes = s.as_bytes(); // &[u8]
-        let mut blen = bytes.len();
-        if (blen > N) {
+    /// N, the overflow is handled according to the policy `P`.  Under the
+    /// default [Truncate] policy, the extra characters are ignored
+    /// (truncating at a character boundary) and a **warning is sent to
+    /// stderr**.
+    pub fn make(s: &str) -> fstr<N, P> {
+        if P::MODE == OverflowMode::Truncate && s.len() > N {
             eprintln!("!Fixedstr Warning in fstr::make: length of string literal \"{}\" exceeds the capacity of type fstr<{}>; string truncated",s,N);
-            blen = N;
-        }
-        let mut chars = [0u8; N];
-        let mut i = 0;
-        let limit = min(N, blen);
-        chars[..limit].clone_from_slice(&bytes[..limit]);
-        /* //replaced re performance lint
-        for i in 0..blen
-        {
-          if i<N {chars[i] = bytes[i];} else {break;}
-        }
-        */
-        fstr {
-            chrs: chars,
-            len: blen, /* as u16 */
         }
+        Self::create(s)
     } //make
 
     /// Version of make that does not print warning to stderr.  If the
-    /// capacity limit is exceeded, the extra characters are ignored.
-    pub fn create(s: &str) -> fstr<N> {
-        let bytes = s.as_bytes(); // &[u8]
-        let mut blen = bytes.len();
-        if (blen > N) {
-            blen = N;
-        }
-        let mut chars = [0u8; N];
-        let mut i = 0;
-        let limit = min(N, blen);
-        chars[..limit].clone_from_slice(&bytes[..limit]);
-        fstr {
-            chrs: chars,
-            len: blen,
-        }
+    /// capacity limit is exceeded, the overflow is handled according to
+    /// the policy `P` (by default, the extra characters are ignored).
+    pub fn create(s: &str) -> fstr<N, P> {
+        let mut f = Self::new();
+        f.push_policy(s, false);
+        f
     } //create
 
+    /// truncating constructor that takes the place of the `From`
+    /// conversions of versions before 0.6.0, which are now `TryFrom`
+    /// conversions that fail instead of truncating.  Since a type cannot
+    /// implement both `From<&str>` and `TryFrom<&str>`, this function keeps
+    /// calls of the form `fstr::from(s)` working (but not `s.into()`) until it
+    /// is removed.
+    #[deprecated(since = "0.6.0", note = "use `make` to truncate, or `try_make` or `try_from` to detect overflow")]
+    pub fn from<T: AsRef<str>>(s: T) -> Self {
+        Self::make(s.as_ref())
+    }
+
     /// version of make that does not truncate, if s exceeds capacity,
-    /// an Err result is returned containing s
-    pub fn try_make(s: &str) -> Result<fstr<N>, &str> {
-        if s.len() > N {
-            Err(s)
-        } else {
-            Ok(fstr::make(s))
-        }
+    /// a [CapacityError] is returned
+    pub fn try_make(s: &str) -> Result<fstr<N, P>, CapacityError> {
+        Self::const_try_create(s)
+    }
+
+    /// version of [FromIterator] that returns a [CapacityError] instead of
+    /// applying the [OverflowPolicy] if the items do not fit.
+    /// See [crate::StrPiece] for the types of items.
+    pub fn try_from_iter<T: crate::StrPiece>(iter: impl IntoIterator<Item = T>) -> Result<fstr<N, P>, CapacityError> {
+        let mut s = fstr::new();
+        s.try_extend(iter)?;
+        Ok(s)
+    }
+
+    /// version of [Extend] that pushes either all of the items or, if they
+    /// do not fit, none of them, returning a [CapacityError]
+    pub fn try_extend<T: crate::StrPiece>(&mut self, iter: impl IntoIterator<Item = T>) -> Result<(), CapacityError> {
+        crate::try_extend_pieces(self, N, false, iter)
     }
 
 /// const constructor, to be called from const contexts.  However, as
 /// const constructors are restricted from using iterators, it's slightly
 /// better to call the non-const constructors in non-const contexts.
-/// Truncates automatically.
-    pub const fn const_create(s:&str) -> fstr<N> {
-      let mut t = fstr::<N>::new();
-      let mut len = s.len();
-      if len>N { len = N; } // fix max length
-      t.len = len;
+/// Overflow is handled according to the policy `P`, so that under
+/// [crate::Strict] or [crate::Panic] an oversized string fails to compile
+/// when evaluated in a const context.
+    pub const fn const_create(s:&str) -> fstr<N, P> {
+      let mut t = fstr::<N, P>::new();
       let bytes = s.as_bytes();
-      let mut i = 0;
-      while i<len {
-        t.chrs[i] = bytes[i];
-        i += 1;
+      let (_, len, ellipsis) = crate::plan_push(0, bytes, N, P::MODE, false);
+      crate::copy_bytes(&mut t.chrs, 0, bytes, len);
+      t.len = len;
+      if ellipsis {
+        crate::copy_bytes(&mut t.chrs, len, crate::ELLIPSIS.as_bytes(), crate::ELLIPSIS.len());
+        t.len += crate::ELLIPSIS.len();
       }
       t
     }//const_make
 
     /// version of `const_create` that does not truncate.
-    pub const fn const_try_create(s:&str) -> Result<fstr<N>, &str> {
-      if s.len()+1>N { Err(s) }
+    pub const fn const_try_create(s:&str) -> Result<fstr<N, P>, CapacityError> {
+      if s.len()>N {
+        Err(CapacityError::Overflow{required: s.len(), available: N})
+      }
       else { Ok(fstr::const_create(s)) }
     }
+
+    /// alias for [fstr::const_create], named as for the other types
+    pub const fn const_make(s:&str) -> fstr<N, P> {
+      fstr::const_create(s)
+    }
+
+    /// alias for [fstr::const_try_create], named as for the other types
+    pub const fn const_try_make(s:&str) -> Result<fstr<N, P>, CapacityError> {
+      fstr::const_try_create(s)
+    }
     
     /// creates an empty string, equivalent to fstr::default() but can also be
     /// called from a const context
     #[inline]
-    pub const fn new() -> fstr<N> {
+    pub const fn new() -> fstr<N, P> {
         fstr {
           chrs:[0;N],
           len: 0,
+          policy: PhantomData,
         }
     }//new
 
@@ -116,6 +139,7 @@ impl<const N: usize> fstr<N> {
     /// This is a constant-time operation and can be called from a const
     /// context
     #[inline]
+    #[allow(clippy::len_without_is_empty)] // is_empty is provided by Fixedstr
     pub const fn len(&self) -> usize {
         self.len
     }
@@ -126,6 +150,7 @@ impl<const N: usize> fstr<N> {
     }
 
     /// converts fstr to an owned string
+    #[allow(clippy::inherent_to_string_shadow_display)] // no ToString import needed
     pub fn to_string(&self) -> String {
         //self.to_str().to_owned()
         String::from(self.to_str())
@@ -138,8 +163,8 @@ impl<const N: usize> fstr<N> {
     }
 
     /// returns slice of the u8 array underneath
-    pub fn as_bytes(&self) -> &[u8] {
-        &self.chrs[0..self.len]
+    pub const fn as_bytes(&self) -> &[u8] {
+        self.chrs.split_at(self.len).0
     }
 
     /// returns mutable slice of the u8 array underneath (use with care)
@@ -151,8 +176,8 @@ impl<const N: usize> fstr<N> {
     /// converts fstr to &str using [std::str::from_utf8_unchecked].  Since
     /// fstr can only be built from valid utf8 sources, this function
     /// is safe.
-    pub fn to_str(&self) -> &str {
-        unsafe { std::str::from_utf8_unchecked(&self.chrs[0..self.len]) }
+    pub const fn to_str(&self) -> &str {
+        unsafe { std::str::from_utf8_unchecked(self.as_bytes()) }
     }
     /// same functionality as [fstr::to_str], but using [std::str::from_utf8]
     /// and may technically panic.
@@ -171,19 +196,20 @@ impl<const N: usize> fstr<N> {
     /// changes a character at character position i to c.  This function
     /// requires that c is in the same character class (ascii or unicode)
     /// as the char being replaced.  It never shuffles the bytes underneath.
+    /// See [fstr::replace_nth] for a version without this requirement.
     /// The function returns true if the change was successful.
     pub fn set(&mut self, i: usize, c: char) -> bool {
-        let ref mut cbuf = [0u8; 4]; // characters require at most 4 bytes
+        let cbuf = &mut [0u8; 4]; // characters require at most 4 bytes
         c.encode_utf8(cbuf);
         let clen = c.len_utf8();
-        if let Some((bi, rc)) = self.to_str().char_indices().nth(i) {
-            if clen == rc.len_utf8() {
-                self.chrs[bi..bi + clen].clone_from_slice(&cbuf[..clen]);
-                //for k in 0..clen {self.chrs[bi+k] = cbuf[k];}
-                return true;
-            }
+        if let Some((bi, rc)) = self.to_str().char_indices().nth(i)
+            && clen == rc.len_utf8()
+        {
+            self.chrs[bi..bi + clen].clone_from_slice(&cbuf[..clen]);
+            //for k in 0..clen {self.chrs[bi+k] = cbuf[k];}
+            return true;
         }
-        return false;
+        false
     }
 
     /// version of [fstr::set] that assumes that the char is a single byte.
@@ -199,6 +225,9 @@ impl<const N: usize> fstr<N> {
     /// returns the portion of the push string that was NOT pushed due to
     /// capacity, so
     /// if "" is returned then all characters were pushed successfully.
+    /// A multibyte character is never split: the returned remainder always
+    /// starts at a character boundary.  How much is pushed when the
+    /// capacity is exceeded depends on the [OverflowPolicy] `P`.
     pub fn push<'t>(&mut self, s: &'t str) -> &'t str {
         self.push_str(s)
         /*
@@ -233,40 +262,38 @@ impl<const N: usize> fstr<N> {
 
     /// alias for [fstr::push]
     pub fn push_str<'t>(&mut self, src: &'t str) -> &'t str {
-        let srclen = src.len();
-        let slen = self.len();
-        let bytes = &src.as_bytes();
-        let length = core::cmp::min(slen + srclen, N);
-        let remain = if N >= (slen + srclen) {
-            0
-        } else {
-            (srclen + slen) - N
-        };
-        let mut i = 0;
-        while i < srclen && i + slen < N {
-            self.chrs[slen + i] = bytes[i];
-            i += 1;
-        } //while
-        self.len += i;
-        &src[srclen - remain..]
+        self.push_policy(src, true)
+    }
+
+    // push according to the overflow policy; `fallible` is false for
+    // callers that cannot report the part that was not pushed
+    fn push_policy<'t>(&mut self, src: &'t str, fallible: bool) -> &'t str {
+        let (keep, n, ellipsis) = crate::plan_push(self.len, src.as_bytes(), N, P::MODE, fallible);
+        if keep < self.len {
+            self.len = crate::floor_char_boundary(self.as_bytes(), keep);
+        }
+        self.push_bytes(&src.as_bytes()[..n]);
+        if ellipsis {
+            self.push_bytes(crate::ELLIPSIS.as_bytes());
+        }
+        &src[n..]
+    } //push_policy
+
+    fn push_bytes(&mut self, bytes: &[u8]) {
+        self.chrs[self.len..self.len + bytes.len()].copy_from_slice(bytes);
+        self.len += bytes.len();
     }
 
     /// pushes a single character to the end of the string, returning
     /// true on success.
     pub fn push_char(&mut self, c: char) -> bool {
-        let clen = c.len_utf8();
-        if self.len + clen > N {
-            return false;
-        }
         let mut buf = [0u8; 4]; // char buffer
-        let bstr = c.encode_utf8(&mut buf);
-        self.push(bstr);
-        true
+        self.push_str(c.encode_utf8(&mut buf)).is_empty()
     } // push_char
 
     /// remove and return last character in string, if it exists
     pub fn pop_char(&mut self) -> Option<char> {
-        if self.len() == 0 {
+        if self.is_empty() {
             return None;
         }
         let (ci, lastchar) = self.char_indices().last().unwrap();
@@ -363,7 +390,7 @@ impl<const N: usize> fstr<N> {
     /// characters.  This contrasts with [str::to_ascii_uppercase],
     /// which creates an owned String.
     pub fn to_ascii_upper(&self) -> Self {
-        let mut cp = self.clone();
+        let mut cp = *self;
         cp.make_ascii_uppercase();
         cp
     }
@@ -387,14 +414,13 @@ impl<const N: usize> fstr<N> {
             return false;
         }
         let obytes = other.as_ref().as_bytes();
-        for i in 0..self.len() {
+        for (i, mut d) in obytes.iter().copied().enumerate() {
             let mut c = self.chrs[i];
             if (c > 64 && c < 91) {
-                c = c | 32;
+                c |= 32;
             } // make lowercase
-            let mut d = obytes[i];
             if (d > 64 && d < 91) {
-                d = d | 32;
+                d |= 32;
             } // make lowercase
             if c != d {
                 return false;
@@ -403,169 +429,181 @@ impl<const N: usize> fstr<N> {
         true
     } //case_insensitive_eq
 
-    /// Decodes a UTF-16 encodeded slice. If a decoding error is encountered
-    /// or capacity exceeded, an `Err(s)` is returned where s is the
-    /// the encoded string up to the point of the error.
-    pub fn from_utf16(v: &[u16]) -> Result<Self, Self> {
+    /// Decodes a UTF-16 encodeded slice.  Returns an error if the slice is
+    /// not valid utf16 or if the decoded string exceeds the capacity.
+    pub fn from_utf16(v: &[u16]) -> Result<Self, CapacityError> {
+        let required = crate::utf16_len(v)?;
+        if required > N {
+            return Err(CapacityError::Overflow {
+                required,
+                available: N,
+            });
+        }
         let mut s = Self::new();
-        for c in char::decode_utf16(v.iter().cloned()) {
-            if let Ok(c1) = c {
-                if !s.push_char(c1) {
-                    return Err(s);
-                }
-            } else {
-                return Err(s);
-            }
+        for c in char::decode_utf16(v.iter().cloned()).flatten() {
+            s.push_char(c);
         }
         Ok(s)
     } //from_utf16
 } //impl fstr<N>
 
-impl<const N: usize> std::ops::Deref for fstr<N> {
+impl<const N: usize, P: OverflowPolicy> std::ops::Deref for fstr<N, P> {
     type Target = str;
     fn deref(&self) -> &Self::Target {
         self.to_str()
     }
 }
 
-impl<T: AsRef<str> + ?Sized, const N: usize> std::convert::From<&T> for fstr<N> {
-    fn from(s: &T) -> fstr<N> {
-        fstr::make(s.as_ref())
-    }
-}
-impl<T: AsMut<str> + ?Sized, const N: usize> std::convert::From<&mut T> for fstr<N> {
-    fn from(s: &mut T) -> fstr<N> {
-        fstr::make(s.as_mut())
-    }
-}
-
-/*
-//generic, but "conflicts with crate 'core'
-impl<const N: usize, TA:AsRef<str>> std::convert::From<TA> for fstr<N> {
-    fn from(s: TA) -> fstr<N> {
-        fstr::<N>::make(s.as_ref())
-    }
-}
-*/
-
-impl<const N: usize> std::convert::From<String> for fstr<N> {
-    fn from(s: String) -> fstr<N> {
-        fstr::<N>::make(&s[..])
-    }
-}
-
-impl<const N: usize, const M: usize> std::convert::From<zstr<M>> for fstr<N> {
-    fn from(s: zstr<M>) -> fstr<N> {
-        fstr::<N>::make(&s.to_str())
-    }
-}
-
-impl<const N: usize, const M: usize> std::convert::From<tstr<M>> for fstr<N> {
-    fn from(s: tstr<M>) -> fstr<N> {
-        fstr::<N>::make(&s.to_str())
-    }
-}
-
-impl<const N: usize> std::cmp::PartialOrd for fstr<N> {
+impl<const N: usize, P: OverflowPolicy> std::cmp::PartialOrd for fstr<N, P> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
     }
 }
 
-impl<const N: usize> std::cmp::Ord for fstr<N> {
+impl<const N: usize, P: OverflowPolicy> std::cmp::Ord for fstr<N, P> {
     fn cmp(&self, other: &Self) -> Ordering {
         self.chrs[0..self.len].cmp(&other.chrs[0..other.len])
     }
 }
 
-impl<const M: usize> fstr<M> {
+impl<const M: usize, P: OverflowPolicy> fstr<M, P> {
     /// converts an fstr\<M\> to an fstr\<N\>. If the length of the string being
-    /// converted is greater than N, the extra characters are ignored.
+    /// converted is greater than N, the overflow is handled according to
+    /// the policy `P` (by default, the extra characters are ignored).
     /// This operation produces a copy (non-destructive).
     /// Example:
     ///```ignore
-    ///  let s1:fstr<8> = fstr::from("abcdefg");
+    ///  let s1:fstr<8> = fstr::make("abcdefg");
     ///  let s2:fstr<16> = s1.resize();
     ///```
-    pub fn resize<const N: usize>(&self) -> fstr<N> {
+    pub fn resize<const N: usize>(&self) -> fstr<N, P> {
         //if (self.len()>N) {eprintln!("!Fixedstr Warning in fstr::resize: string \"{}\" truncated while resizing to fstr<{}>",self,N);}
-        let length = if (self.len < N) { self.len } else { N };
-        let mut chars = [0u8; N];
-        chars[..length].clone_from_slice(&self.chrs[..length]);
-        //for i in 0..length {chars[i] = self.chrs[i];}
-        fstr {
-            chrs: chars,
-            len: length,
-        }
+        fstr::create(self.to_str())
     } //resize
 
     /// version of resize that does not allow string truncation due to length
-    pub fn reallocate<const N: usize>(&self) -> Option<fstr<N>> {
+    pub fn reallocate<const N: usize>(&self) -> Option<fstr<N, P>> {
         if self.len() <= N {
             Some(self.resize())
         } else {
             None
         }
     }
+
+    /// converts to a `fstr<N>` at least as large as `self`, which unlike
+    /// [Self::resize] can never truncate: it is checked at compile time
+    /// that a `fstr<N>` can hold any `fstr<M>`.
+    /// ```
+    ///  # use fixedstr::*;
+    ///  let s = fstr::<8>::make("abcdefgh");
+    ///  let t: fstr<16> = s.grow(); // s.grow::<4>() would not compile
+    ///  assert_eq!(t, "abcdefgh");
+    /// ```
+    pub fn grow<const N: usize>(&self) -> fstr<N, P> {
+        crate::assert_grows::<Self, fstr<N, P>>();
+        self.resize()
+    }
+
+    /// concatenates `self` and `other` into a new `fstr<N>`.  It is checked
+    /// at compile time that a `fstr<N>` can hold the result, so nothing is
+    /// ever truncated.  `other` can be of any size and of any type
+    /// implementing [FixedCapacity](crate::FixedCapacity).
+    /// ```
+    ///  # use fixedstr::*;
+    ///  let a = fstr::<8>::make("abc");
+    ///  let b = fstr::<4>::make("def");
+    ///  let c = a.concat::<12>(&b); // a.concat::<11>(&b) would not compile
+    ///  assert_eq!(c, "abcdef");
+    /// ```
+    pub fn concat<const N: usize>(&self, other: &impl crate::FixedCapacity) -> fstr<N, P> {
+        crate::assert_concat_fits::<Self, _, fstr<N, P>>(other);
+        let mut cat: fstr<N, P> = self.resize();
+        crate::append_all(&mut cat, other);
+        cat
+    }
 } //impl fstr<M>
 
-impl<const N: usize> std::convert::AsRef<str> for fstr<N> {
+impl<const N: usize, P: OverflowPolicy> std::convert::AsRef<str> for fstr<N, P> {
     fn as_ref(&self) -> &str {
         self.to_str()
     }
 }
-impl<const N: usize> std::convert::AsMut<str> for fstr<N> {
+
+/// Allows a `fstr` key of a `HashMap` or `BTreeMap` to be looked up by
+/// `&str`, as hashing and comparison are consistent with those of `str`.
+impl<const N: usize, P: OverflowPolicy> std::borrow::Borrow<str> for fstr<N, P> {
+    fn borrow(&self) -> &str {
+        self.to_str()
+    }
+}
+
+/// Collects characters or strings (see [crate::StrPiece]), truncating at
+/// the first item that does not fit as the [OverflowPolicy] dictates
+///
+/// # Panics
+/// Panics if an item does not fit under the [crate::Strict] or
+/// [crate::Panic] policy: use [fstr::try_from_iter] to detect overflow.
+impl<T: crate::StrPiece, const N: usize, P: OverflowPolicy> std::iter::FromIterator<T> for fstr<N, P> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut s = fstr::new();
+        s.extend(iter);
+        s
+    }
+}
+
+/// Pushes the items until one does not fit, which is handled according
+/// to the [OverflowPolicy].
+///
+/// # Panics
+/// Panics if an item does not fit under the [crate::Strict] or
+/// [crate::Panic] policy: use [fstr::try_extend] to detect overflow.
+impl<T: crate::StrPiece, const N: usize, P: OverflowPolicy> std::iter::Extend<T> for fstr<N, P> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        crate::extend_pieces(iter, P::MODE, |s| self.push_str(s).is_empty());
+    }
+}
+
+impl<const N: usize, P: OverflowPolicy> std::convert::AsMut<str> for fstr<N, P> {
     fn as_mut(&mut self) -> &mut str {
         unsafe { std::str::from_utf8_unchecked_mut(&mut self.chrs[0..self.len]) }
     }
 }
 
-impl<const N: usize> std::fmt::Display for fstr<N> {
+impl<const N: usize, P: OverflowPolicy> std::fmt::Display for fstr<N, P> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         //write!(f, "{}", self.to_str())
         f.pad(self.to_str())
     }
 }
 
-impl<const N: usize> PartialEq<&str> for fstr<N> {
+impl<const N: usize, P: OverflowPolicy> PartialEq<&str> for fstr<N, P> {
     fn eq(&self, other: &&str) -> bool {
         &self.to_str() == other // see below
     } //eq
 }
 
-impl<const N: usize> PartialEq<&str> for &fstr<N> {
-    fn eq(&self, other: &&str) -> bool {
-        &self.to_str() == other
-    } //eq
-}
-impl<'t, const N: usize> PartialEq<fstr<N>> for &'t str {
-    fn eq(&self, other: &fstr<N>) -> bool {
-        &other.to_str() == self
-    }
-}
-impl<'t, const N: usize> PartialEq<&fstr<N>> for &'t str {
-    fn eq(&self, other: &&fstr<N>) -> bool {
+impl<const N: usize, P: OverflowPolicy> PartialEq<fstr<N, P>> for &str {
+    fn eq(&self, other: &fstr<N, P>) -> bool {
         &other.to_str() == self
     }
 }
-
 /// defaults to empty string
-impl<const N: usize> Default for fstr<N> {
+impl<const N: usize, P: OverflowPolicy> Default for fstr<N, P> {
     fn default() -> Self {
-        fstr::<N>::make("")
+        fstr::<N, P>::make("")
     }
 }
 
-impl<const N: usize> std::fmt::Debug for fstr<N> {
+impl<const N: usize, P: OverflowPolicy> std::fmt::Debug for fstr<N, P> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        f.pad(&self.to_str())
+        f.pad(self.to_str())
     }
 } // Debug impl
 
-impl<const N: usize> fstr<N> {
+impl<const N: usize, P: OverflowPolicy> fstr<N, P> {
     /// returns a copy of the portion of the string, string could be truncated
     /// if indices are out of range. Similar to slice [start..end]
-    pub fn substr(&self, start: usize, end: usize) -> fstr<N> {
+    pub fn substr(&self, start: usize, end: usize) -> fstr<N, P> {
         let mut chars = [0u8; N];
         let mut inds = self.char_indices();
         let len = self.len();
@@ -573,6 +611,7 @@ impl<const N: usize> fstr<N> {
             return fstr {
                 chrs: chars,
                 len: 0,
+                policy: PhantomData,
             };
         }
         let (si, _) = inds.nth(start).unwrap();
@@ -594,11 +633,279 @@ impl<const N: usize> fstr<N> {
         */
         fstr {
             chrs: chars,
-            len: end - start,
+            len: last - si,
+            policy: PhantomData,
         }
     } //substr
 }
 
+impl<const N: usize, P: OverflowPolicy> fstr<N, P> {
+    /// returns a copy of the string with all matches of the [StrPattern]
+    /// `pat` replaced by `to`, and whether the copy was truncated to fit
+    /// the capacity (according to the overflow policy `P`).  Unlike
+    /// [str::replace], this does not allocate.
+    pub fn replace<Pat: StrPattern>(&self, pat: Pat, to: &str) -> (fstr<N, P>, bool) {
+        crate::replace_into(self.as_str(), pat, to, usize::MAX)
+    }
+
+    /// version of [fstr::replace] that only replaces the first `count` matches
+    pub fn replacen<Pat: StrPattern>(&self, pat: Pat, to: &str, count: usize) -> (fstr<N, P>, bool) {
+        crate::replace_into(self.as_str(), pat, to, count)
+    }
+
+    /// version of [fstr::replace] that replaces every occurrence of one
+    /// character by another.  The copy can only be truncated if `to`
+    /// has a longer utf8 encoding than `from`.
+    pub fn replace_char(&self, from: char, to: char) -> (fstr<N, P>, bool) {
+        let mut buf = [0u8; 4];
+        crate::replace_into(self.as_str(), from, to.encode_utf8(&mut buf), usize::MAX)
+    }
+
+    /// removes all matches of the [StrPattern] `pat` in place
+    pub fn remove_matches<Pat: StrPattern>(&mut self, pat: Pat) {
+        *self = crate::replace_into(self.as_str(), pat, "", usize::MAX).0;
+    }
+
+    /// splits the string at the first match of `pat`, returning the parts
+    /// before and after the match, or `None` if there is no match.  The
+    /// parts are never truncated.
+    pub fn split_once<Pat: StrPattern>(&self, pat: Pat) -> Option<(fstr<N, P>, fstr<N, P>)> {
+        let s = self.as_str();
+        pat.find_in(s).map(|m| crate::split_around(s, m))
+    }
+
+    /// splits the string at the last match of `pat`, returning the parts
+    /// before and after the match, or `None` if there is no match.
+    pub fn rsplit_once<Pat: StrPattern>(&self, pat: Pat) -> Option<(fstr<N, P>, fstr<N, P>)> {
+        let s = self.as_str();
+        pat.rfind_in(s).map(|m| crate::split_around(s, m))
+    }
+
+    /// returns a copy of the string without the prefix `pat`, or `None` if
+    /// the string does not start with it
+    pub fn strip_prefix<Pat: StrPattern>(&self, pat: Pat) -> Option<fstr<N, P>> {
+        let s = self.as_str();
+        match pat.find_in(s) {
+            Some(m) if m.start == 0 => Some(fstr::make(&s[m.end..])),
+            _ => None,
+        }
+    }
+
+    /// returns a copy of the string without the suffix `pat`, or `None` if
+    /// the string does not end with it
+    pub fn strip_suffix<Pat: StrPattern>(&self, pat: Pat) -> Option<fstr<N, P>> {
+        let s = self.as_str();
+        match pat.rfind_in(s) {
+            Some(m) if m.end == s.len() => Some(fstr::make(&s[..m.start])),
+            _ => None,
+        }
+    }
+
+    /// iterator over the parts of the string separated by the [StrPattern]
+    /// `pat`, as with [str::split], but which yields strings of type `T`
+    /// instead of slices.  See [SplitInto].
+    pub fn split_into<T: FixedstrMake>(&self, pat: impl StrPattern) -> SplitInto<'_, T, impl StrPattern> {
+        SplitInto::new(self.as_str(), "", pat, usize::MAX)
+    }
+
+    /// version of [fstr::split_into] that yields at most `n` parts, the last
+    /// being the rest of the string, as with [str::splitn]
+    pub fn splitn_into<T: FixedstrMake>(&self, n: usize, pat: impl StrPattern) -> SplitInto<'_, T, impl StrPattern> {
+        SplitInto::new(self.as_str(), "", pat, n)
+    }
+
+    /// iterator over the whitespace-separated words of the string, as with
+    /// [str::split_whitespace], that yields strings of type `T`
+    pub fn split_whitespace_into<T: FixedstrMake>(&self) -> SplitInto<'_, T, fn(char) -> bool> {
+        SplitInto::new(self.as_str(), "", char::is_whitespace as fn(char) -> bool, usize::MAX).skipping_empty()
+    }
+
+    /// iterator over the lines of the string, as with [str::lines], that
+    /// yields strings of type `T`
+    pub fn lines_into<T: FixedstrMake>(&self) -> SplitInto<'_, T, char> {
+        SplitInto::new(self.as_str(), "", '\n', usize::MAX).splitting_lines()
+    }
+} //pattern methods
+
+impl<const N: usize, P: OverflowPolicy> fstr<N, P> {
+    // replaces the bytes in range r with `with`
+    fn splice(&mut self, r: Range<usize>, with: &str) -> Result<(), CapacityError> {
+        self.len = crate::splice_bytes(&mut self.chrs, self.len, r, with.as_bytes())?;
+        Ok(())
+    }
+
+    /// inserts a character at byte index `idx`, shifting the rest of the
+    /// string, as `String::insert` does.  Returns false (leaving the
+    /// string unchanged) if the result would not fit.  **Panics** if `idx`
+    /// is larger than the length or not on a character boundary.
+    pub fn insert(&mut self, idx: usize, c: char) -> bool {
+        let mut buf = [0u8; 4];
+        self.insert_str(idx, c.encode_utf8(&mut buf)).is_ok()
+    }
+
+    /// inserts a string at byte index `idx`, as `String::insert_str` does,
+    /// or returns a [CapacityError] (leaving the string unchanged) if the
+    /// result would not fit.  **Panics** if `idx` is larger than the
+    /// length or not on a character boundary.
+    pub fn insert_str(&mut self, idx: usize, s: &str) -> Result<(), CapacityError> {
+        self.replace_range(idx..idx, s)
+    }
+
+    /// removes and returns the character at byte index `idx`, as
+    /// `String::remove` does.  **Panics** if `idx` is not less than the
+    /// length or not on a character boundary.
+    pub fn remove(&mut self, idx: usize) -> char {
+        let c = self.as_str()[idx..].chars().next().expect("cannot remove a char from the end of a string");
+        self.drain(idx..idx + c.len_utf8());
+        c
+    }
+
+    /// removes the given range of bytes from the string and returns it as
+    /// a new string.  Unlike `String::drain`, the removal is immediate and
+    /// no iterator is involved.  **Panics** if the range is out of bounds
+    /// or not on character boundaries.
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> fstr<N, P> {
+        let s = self.as_str();
+        let r = crate::byte_range(s.len(), range, |i| s.is_char_boundary(i));
+        let drained = fstr::make(&s[r.clone()]);
+        self.splice(r, "").unwrap();
+        drained
+    }
+
+    /// keeps only the characters for which `f` returns true, as
+    /// `String::retain` does
+    pub fn retain<F: FnMut(char) -> bool>(&mut self, mut f: F) {
+        let mut kept = fstr::<N, P>::new();
+        for c in self.as_str().chars().filter(|c| f(*c)) {
+            kept.push_char(c);
+        }
+        *self = kept;
+    }
+
+    /// replaces the given range of bytes with a string, as
+    /// `String::replace_range` does, or returns a [CapacityError] (leaving
+    /// the string unchanged) if the result would not fit.  **Panics** if
+    /// the range is out of bounds or not on character boundaries.
+    pub fn replace_range<R: RangeBounds<usize>>(&mut self, range: R, with: &str) -> Result<(), CapacityError> {
+        let s = self.as_str();
+        let r = crate::byte_range(s.len(), range, |i| s.is_char_boundary(i));
+        self.splice(r, with)
+    }
+
+    /// changes the character at *character position* n to c, returning
+    /// true on success.  Unlike [fstr::set], the new character may have a
+    /// different utf8 length than the one it replaces, in which case the
+    /// rest of the string is shifted.  Returns false (leaving the string
+    /// unchanged) only if n is out of range or the result would not fit.
+    pub fn replace_nth(&mut self, n: usize, c: char) -> bool {
+        match self.as_str().char_indices().nth(n) {
+            Some((i, _)) => self.replace_at(i, c),
+            None => false,
+        }
+    }
+
+    /// version of [fstr::replace_nth] that changes the character starting
+    /// at *byte* index `idx`.  Returns false if `idx` is not the start of a
+    /// character of the string or the result would not fit.
+    pub fn replace_at(&mut self, idx: usize, c: char) -> bool {
+        let mut buf = [0u8; 4];
+        match self.as_str().get(idx..).and_then(|t| t.chars().next()) {
+            Some(old) => self.replace_range(idx..idx + old.len_utf8(), c.encode_utf8(&mut buf)).is_ok(),
+            None => false,
+        }
+    }
+} //editing methods
+
+impl<const N: usize, P: OverflowPolicy> fstr<N, P> {
+    /// version of [fstr::push_str] that can be called from a const context
+    pub const fn const_push_str<'t>(&mut self, src: &'t str) -> &'t str {
+        let (keep, n, ellipsis) = crate::plan_push(self.len, src.as_bytes(), N, P::MODE, true);
+        if keep < self.len {
+            self.len = crate::floor_char_boundary(self.as_bytes(), keep);
+        }
+        crate::copy_bytes(&mut self.chrs, self.len, src.as_bytes(), n);
+        self.len += n;
+        if ellipsis {
+            crate::copy_bytes(&mut self.chrs, self.len, crate::ELLIPSIS.as_bytes(), crate::ELLIPSIS.len());
+            self.len += crate::ELLIPSIS.len();
+        }
+        src.split_at(n).1
+    } //const_push_str
+
+    /// version of `==` with a `&str` that can be called from a const context
+    pub const fn const_eq(&self, other: &str) -> bool {
+        crate::const_fns::eq(self.const_bytes(), ConstBytes::of(other))
+    }
+
+    /// version of [Ord::cmp] with a `&str` that can be called from a
+    /// const context
+    pub const fn const_cmp(&self, other: &str) -> Ordering {
+        crate::const_fns::cmp(self.const_bytes(), ConstBytes::of(other))
+    }
+
+    /// version of [str::starts_with] that can be called from a const context
+    pub const fn const_starts_with(&self, prefix: &str) -> bool {
+        crate::const_fns::starts_with(self.const_bytes(), ConstBytes::of(prefix))
+    }
+
+    /// returns the byte position of the first occurrence of `pat`, like
+    /// [str::find] but callable from a const context
+    pub const fn const_find(&self, pat: &str) -> Option<usize> {
+        crate::const_fns::find(self.const_bytes(), ConstBytes::of(pat))
+    }
+
+    /// version of [fstr::substr] that can be called from a const context:
+    /// returns a copy of the characters at (character) positions
+    /// `start..end`, which are clamped to the length of the string
+    pub const fn const_substr(&self, start: usize, end: usize) -> fstr<N, P> {
+        if end <= start {
+            return fstr::new();
+        }
+        let b = self.const_bytes();
+        self.const_range(crate::const_fns::char_to_byte(b, start), crate::const_fns::char_to_byte(b, end))
+    }
+
+    /// version of [fstr::to_ascii_upper] that can be called from a const context
+    pub const fn const_to_ascii_upper(&self) -> fstr<N, P> {
+        self.const_map_ascii(true)
+    }
+
+    /// version of [fstr::to_ascii_lower] that can be called from a const context
+    pub const fn const_to_ascii_lower(&self) -> fstr<N, P> {
+        self.const_map_ascii(false)
+    }
+
+    /// returns a copy of the string without leading and trailing
+    /// whitespace, like [str::trim] but callable from a const context
+    pub const fn const_trim(&self) -> fstr<N, P> {
+        let (start, end) = crate::const_fns::trim(self.const_bytes());
+        self.const_range(start, end)
+    }
+
+    // the bytes of the string, for the const methods
+    const fn const_bytes(&self) -> ConstBytes<'_> {
+        ConstBytes::new(&self.chrs, 0, self.len)
+    }
+
+    // copy of bytes start..end, which must be at character boundaries
+    const fn const_range(&self, start: usize, end: usize) -> fstr<N, P> {
+        let mut s = fstr::new();
+        crate::copy_bytes(&mut s.chrs, 0, self.chrs.split_at(start).1, end - start);
+        s.len = end - start;
+        s
+    }
+
+    const fn const_map_ascii(&self, upper: bool) -> fstr<N, P> {
+        let mut s = *self;
+        let mut i = 0;
+        while i < self.len {
+            s.chrs[i] = if upper { s.chrs[i].to_ascii_uppercase() } else { s.chrs[i].to_ascii_lowercase() };
+            i += 1;
+        }
+        s
+    }
+} //const methods
+
 ////////////// core::fmt::Write trait
 /// Usage:
 /// ```
@@ -610,45 +917,45 @@ impl<const N: usize> fstr<N> {
 ///   let s2 = str_format!(fstr<24>,"hello {}, {}, {}",1,2,3);
 ///   let s3 = try_format!(fstr::<4>,"hello {}, {}, {}",1,2,3); // returns None
 /// ```
-impl<const N: usize> core::fmt::Write for fstr<N> {
+impl<const N: usize, P: OverflowPolicy> core::fmt::Write for fstr<N, P> {
     fn write_str(&mut self, s: &str) -> std::fmt::Result //Result<(),std::fmt::Error>
     {
         let rest = self.push(s);
-        if rest.len() > 0 {
-            return Err(core::fmt::Error::default());
+        if !rest.is_empty() {
+            return Err(core::fmt::Error);
         }
         Ok(())
     } //write_str
 } //core::fmt::Write trait
 
-impl<const N: usize, TA: AsRef<str>> Add<TA> for fstr<N> {
-    type Output = fstr<N>;
-    fn add(self, other: TA) -> fstr<N> {
+impl<const N: usize, TA: AsRef<str> + ?Sized, P: OverflowPolicy> Add<&TA> for fstr<N, P> {
+    type Output = fstr<N, P>;
+    fn add(self, other: &TA) -> fstr<N, P> {
         let mut a2 = self;
-        a2.push(other.as_ref());
+        a2.push_policy(other.as_ref(), false);
         a2
     }
 } //Add &str
 
-impl<const N: usize> Add<&fstr<N>> for &str {
-    type Output = fstr<N>;
-    fn add(self, other: &fstr<N>) -> fstr<N> {
-        let mut a2 = fstr::from(self);
-        a2.push(other);
+impl<const N: usize, P: OverflowPolicy> Add<&fstr<N, P>> for &str {
+    type Output = fstr<N, P>;
+    fn add(self, other: &fstr<N, P>) -> fstr<N, P> {
+        let mut a2 = fstr::make(self);
+        a2.push_policy(other, false);
         a2
     }
 } //Add &str on left
 
-impl<const N: usize> Add<fstr<N>> for &str {
-    type Output = fstr<N>;
-    fn add(self, other: fstr<N>) -> fstr<N> {
-        let mut a2 = fstr::from(self);
-        a2.push(&other);
+impl<const N: usize, P: OverflowPolicy> Add<fstr<N, P>> for &str {
+    type Output = fstr<N, P>;
+    fn add(self, other: fstr<N, P>) -> fstr<N, P> {
+        let mut a2 = fstr::make(self);
+        a2.push_policy(&other, false);
         a2
     }
 } //Add &str on left
 
-impl<const N: usize> core::hash::Hash for fstr<N> {
+impl<const N: usize, P: OverflowPolicy> core::hash::Hash for fstr<N, P> {
     fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
         self.as_ref().hash(state);
     }
@@ -660,19 +967,64 @@ impl<const N: usize> core::hash::Hash for fstr<N> {
       }
   }
   */
-impl<const N: usize> PartialEq for fstr<N> {
+impl<const N: usize, P: OverflowPolicy> PartialEq for fstr<N, P> {
     fn eq(&self, other: &Self) -> bool {
         self.as_ref() == other.as_ref()
     }
 }
 
-impl<const N: usize> core::str::FromStr for fstr<N> {
-    type Err = &'static str;
+impl<const N: usize, P: OverflowPolicy> core::str::FromStr for fstr<N, P> {
+    type Err = CapacityError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.len() <= N {
-            Ok(fstr::from(s))
-        } else {
-            Err("capacity exceeded")
-        }
+        fstr::try_make(s)
+    }
+}
+
+impl<const N: usize, P: OverflowPolicy> crate::FixedCapacity for fstr<N, P> {
+    const CAPACITY: usize = N;
+}
+
+impl<const N: usize, P: OverflowPolicy> crate::Fixedstr for fstr<N, P> {
+    fn len(&self) -> usize {
+        self.len()
+    }
+    fn capacity(&self) -> usize {
+        self.capacity()
+    }
+    fn with_segments(&self, f: &mut dyn FnMut(&str, &str)) {
+        f(self.to_str(), "")
+    }
+    fn push_str<'t>(&mut self, src: &'t str) -> &'t str {
+        self.push_str(src)
+    }
+    fn push_char(&mut self, c: char) -> bool {
+        self.push_char(c)
+    }
+    fn pop_char(&mut self) -> Option<char> {
+        self.pop_char()
+    }
+    fn truncate_bytes(&mut self, n: usize) {
+        self.truncate_bytes(n)
+    }
+    fn clear(&mut self) {
+        self.clear()
+    }
+}
+
+impl<const N: usize, P: OverflowPolicy> crate::FixedstrMake for fstr<N, P> {
+    fn make(s: &str) -> Self {
+        fstr::make(s)
+    }
+    fn try_make(s: &str) -> Result<Self, CapacityError> {
+        fstr::try_make(s)
+    }
+    fn new() -> Self {
+        fstr::new()
+    }
+}
+
+impl<const N: usize, P: OverflowPolicy> crate::ContiguousFixedstr for fstr<N, P> {
+    fn as_str(&self) -> &str {
+        self.to_str()
     }
 }
@@ -4,96 +4,158 @@
 //!  - The size of some types such as [str8] and [zstr]\<8\>
 //!    are 8 bytes, compared to 16 bytes for `&str` on 64bit systems,
 //!    providing more efficient ways of representing small strings.
+//!    `Option<tstr<N>>` and `Option<zstr<N>>` take no more space than
+//!    `tstr<N>` and `zstr<N>`.
 //!  -  Most types (except the optional [Flexstr] and [Sharedstr]) can be
-//!    copied and stack-allocated.
+//!     copied and stack-allocated.
 //!  -  `#![no_std]` is supported by all but the optional [fstr] type.
 //!     Features that use the alloc crate can also be optionally excluded.
-//!  -  Unicode is supported by all but the optional [cstr] type.
+//!  -  Unicode is supported by all types.
 //!  -  Serde serialization is supported by all but the optional [Sharedstr] type.
-//!  -  Select functions are `const`, including const constructors.
+//!  -  Select functions are `const`, including const constructors.  The
+//!     [fixedstr!] macro creates strings from literals at compile time,
+//!     rejecting literals that do not fit.  Methods such as
+//!     [tstr::const_push_str], `const_eq`, `const_trim` and `const_find`
+//!     allow tables of strings to be built and checked in `const` items.
+//!  -  All types implement the [Fixedstr] trait (and [FixedstrMake]), so
+//!     generic code can be written over any of them.  Strings of different
+//!     types can be compared with `==` and `<`, as can strings and `str`,
+//!     `String`, `Box<str>` and `Cow<str>`.
+//!  -  Conversions with `TryFrom`, between the types of the crate and from
+//!     `&str`, `String`, `Box<str>`, `Cow<str>` and `char`, fail with a
+//!     [CapacityError] instead of truncating, as does `try_make`: use
+//!     `make` or `resize` to truncate, and `grow` (as in [tstr::grow]) to
+//!     enlarge a string without any possibility of truncation.
+//!  -  Strings can be collected from iterators of `char` and `&str` (see
+//!     [StrPiece]), truncating as their overflow policy dictates, or with
+//!     `try_from_iter`, which reports an overflow instead.
+//!  -  The behavior of the fixed-capacity types on overflow (truncate,
+//!     reject, panic or truncate with an ellipsis) can be chosen with an
+//!     [OverflowPolicy] type parameter, as in `zstr<16, Strict>`.
 //!
 //!
 //! **COMPATIBILITY NOTICES**:
 //!
+//! > **With Version 0.6.0, the infallible `From` conversions have been
+//! > removed.**  Conversions from `&str`, `String` and the other types of
+//! > the crate used to silently truncate; they are now `TryFrom` impls that
+//! > fail with a [CapacityError].  A type cannot implement both `From<&str>`
+//! > and `TryFrom<&str>`, so the `From` impls could not be kept alongside.
+//! > Calls such as `str8::from("abc")` still compile, with a deprecation
+//! > warning, through a deprecated `from` function of each type (as in
+//! > [tstr::from]) that will be removed in a later version, but
+//! > `let s: zstr<8> = "abc".into()` and `T: From<&str>` bounds no longer
+//! > compile: use `make` to truncate as before, or `try_make` or `try_from`
+//! > to detect overflow.
+//!
+//! > Also with Version 0.6.0, `as_bytes_mut` of [tstr] and [zstr] is
+//! > `unsafe`, and zstr's `IndexMut<usize>` (of the `experimental` feature)
+//! > has been removed: writing an arbitrary byte could leave invalid utf8,
+//! > or the byte 0xFF that `Option<tstr<N>>` and `Option<zstr<N>>` use as a
+//! > niche to take no more space than the strings themselves.  To modify a
+//! > string in place, use the checked [tstr::set] and [tstr::set_byte_char]
+//! > (and those of zstr) or the `&mut str` of `AsMut<str>`.
+//!
+//! > Also with Version 0.6.0, [Sharedstr] no longer implements `Deref<Target
+//! > = str>`, `AsRef<str>` or `AsMut<str>`, and its `as_str`, `as_str_utf8`,
+//! > `to_str` and `as_bytes` are `unsafe`, since the `&str` they return could
+//! > dangle after a mutation through another pointer.  Read the string
+//! > through the guard returned by [Sharedstr::borrow] instead, as in
+//! > `&*s.borrow()`.  Mutating a `Sharedstr` while such a guard is alive
+//! > panics, as with a `RefCell`.
+//!
 //! > **With Version 0.5.0, the default availability of some
-//!   string types have changed.**  The default configuration is minimalized.
-//!   The `std`, `flex-str` and `shared-str`
-//!   options are no longer enabled by default.  The crate now
-//!   supports **`#![no_std]`** by default.  The `std` option only enables the
-//!   [fstr] type, which prints warnings to stderr. **However,** unless
-//!   you require one of the types [fstr], [Flexstr] or [Sharedstr], your
-//!   build configurations most likely will work as before: the builds will just be
-//!   smaller.  If `default-features=false` is already part of your
-//!   configuration, it should also work as before.
+//! > string types have changed.**  The default configuration is minimalized.
+//! > The `std`, `flex-str` and `shared-str`
+//! > options are no longer enabled by default.  The crate now
+//! > supports **`#![no_std]`** by default.  The `std` option only enables the
+//! > [fstr] type, which prints warnings to stderr. **However,** unless
+//! > you require one of the types [fstr], [Flexstr] or [Sharedstr], your
+//! > build configurations most likely will work as before: the builds will just be
+//! > smaller.  If `default-features=false` is already part of your
+//! > configuration, it should also work as before.
 //!
 //! > Another change that could potentially affect backwards compatibility is that
-//!   zstr's `Index<usize>` and `IndexMut<usize>` traits, which allow
-//!   arbitrary modifications to underlying bytes, is now only available
-//!   with the optional `experimental` feature.  Previously, they were
-//!   available as default features.
+//! > zstr's `Index<usize>` and `IndexMut<usize>` traits, which allow
+//! > arbitrary modifications to underlying bytes, is now only available
+//! > with the optional `experimental` feature.  Previously, they were
+//! > available as default features.
 //!
 //! **Other Important Recent Updates:**
 //!
 //! >  **Version 0.5.1 introduced the new *`no-alloc`* option**.  In addition to support
-//!    for no_std (for all but the fstr type), this option disables compilation of
-//!    any features that use the alloc crate.  This may make some no_std implementations
-//!    easier. The default build is no longer minimal (see below).
+//! > for no_std (for all but the fstr type), this option disables compilation of
+//! > any features that use the alloc crate.  This may make some no_std implementations
+//! > easier. The default build is no longer minimal (see below).
 //!
 //! >  As of Version 0.4.6, all string types except for `fstr` support
-//! **`#![no_std]`**.
+//! > **`#![no_std]`**.
 //!
 //! >  Starting in Version 0.4.2, the underlying representation of the zero-terminated [zstr]
-//! type no longer allows non-zero bytes after the first zero.  In particular,
-//! the [zstr::from_raw] function now enforces this rule.
+//! > type no longer allows non-zero bytes after the first zero.  In particular,
+//! > the [zstr::from_raw] function now enforces this rule.
 //!
 //! >  Starting in Version 0.4.0, warnings about
-//! capacity being exceeded are only sent to stderr when using the fstr type.
-//! For other types, truncation is done silently. Consider using the
-//! `try_make` function or the [core::str::FromStr] trait.
+//! > capacity being exceeded are only sent to stderr when using the fstr type.
+//! > For other types, truncation is done silently. Consider using the
+//! > `try_make` function or the [core::str::FromStr] trait.
+//!
+//! > All fallible constructors and conversions (`try_make`,
+//! > `const_try_make`, `from_utf16`, [core::str::FromStr], etc.) now
+//! > report failures with the [CapacityError] enum instead of returning
+//! > the original `&str`, a `&'static str` message, or an `Option`.
 //!
 //! <hr>
 //!
 //! **CRATE OVERVIEW**
 //!
 //! The two string types that are always provided by this crate are **[zstr]** and **[tstr]**.
-//! However, [tstr] is not public by default and should be referenced
+//! The [tstr] type is commonly referenced
 //! through the type aliases [str4], [str8], [str16], ...  [str256].
 //!
 //! - A **[zstr]\<N\>** is represented by a `[u8;N]` array underneath
 //!   and can hold zero-terminated, utf-8 strings of up to N-1 bytes.
-//! Furthermore, no non-zero bytes can follow the first zero. This
-//! allows the length of a `zstr<N>` string to be found in O(log N) time.
+//!   Furthermore, no non-zero bytes can follow the first zero. This
+//!   allows the length of a `zstr<N>` string to be found in O(log N) time.
 //!
 //! - The types **[str4]** through **[str256]** are aliases for internal types
-//! [tstr]\<4\> through [tstr]\<256\> respectively.  These strings are stored
-//! in `[u8;N]` arrays with the first byte holding the length of the
-//! string.  Each `tstr<N>` can store strings of up to N-1 bytes, with
-//! maximum N=256. Because Rust does not currently provide
-//! a way to specify conditions (or type casts) on const generics at
-//! compile time, the tstr type is not public by
-//! default and can only be used through the aliases.  The `pub-tstr` option
-//! makes the `tstr` type public but is not recommended: any `tstr<N>` with
-//! `N>256` is not valid and will result in erroneous behavior.
+//!   [tstr]\<4\> through [tstr]\<256\> respectively.  These strings are stored
+//!   in `[u8;N]` arrays with the first byte holding the length of the
+//!   string.  Each `tstr<N>` can store strings of up to N-1 bytes, with
+//!   maximum N=256. Because Rust does not currently provide
+//!   a way to specify conditions on const generics in `where` clauses,
+//!   the limit is enforced by compile-time assertions in the constructors:
+//!   a program that creates a `tstr<N>` with N outside of 1..=256 will not
+//!   compile.  Likewise, `zstr<0>` and `cstr<N>` with N outside of 1..=65535
+//!   are rejected at compile time.
+//!
+//! - An **[mstr]\<N\>** is the counterpart of `tstr<N>` for longer strings:
+//!   the first two bytes of its `[u8;N]` hold the length as a `u16`, so
+//!   that it can store strings of up to N-2 bytes, with maximum N=65537.
+//!   It is commonly referenced through the aliases [mstr512], [mstr1k],
+//!   ... [mstr64k], and has the same API as [tstr], including the
+//!   concatenation of two strings of the same alias into one of twice the size.
 //!
 //! In addition, the following string types are available as options:
 //!
 //! - A **[fstr]\<N\>** stores a string of up to N bytes.
-//! It's represented by a `[u8;N]` array and a separate usize variable
-//! holding the length.  This type is **enabled with either the `std` or
-//! `fstr` option** and some functions will print warnings to stderr when
-//! capacity is exceeded. This is the only type that does not support
-//! `no_std`, but serde is supported.
+//!   It's represented by a `[u8;N]` array and a separate usize variable
+//!   holding the length.  This type is **enabled with either the `std` or
+//!   `fstr` option** and some functions will print warnings to stderr when
+//!   capacity is exceeded. This is the only type that does not support
+//!   `no_std`, but serde is supported.
 //! - The type **[cstr]**, which is **made available
-//! with the `circular-str` option**, uses a fixed u8 array
-//! that is arranged as a circular queue (aka ring buffer).  This allows
-//! efficient implementations of pushing/triming characters *in front* of
-//! the string without additional memory allocation.  The downside of these
-//! strings is that the underlying representation can be non-contiguous as it allows
-//! wrap-around.  As a result, there is no efficient way to implement
-//! `Deref<str>`.  Additionally, cstr is the only string type of the crate
-//! that does not support Unicode. **Only single-byte characters** are
-//! currently supported. There is, however, an iterator over all characters
-//! and most common traits are implemented.  Serde and no-std are both supported.
+//!   with the `circular-str` option**, uses a fixed u8 array
+//!   that is arranged as a circular queue (aka ring buffer).  This allows
+//!   efficient implementations of pushing/triming characters *in front* of
+//!   the string without additional memory allocation.  The downside of these
+//!   strings is that the underlying representation can be non-contiguous as it allows
+//!   wrap-around.  As a result, there is no efficient way to implement
+//!   `Deref<str>`.  Instead, the string is available as a pair of
+//!   `&str` slices, and there is an iterator over all characters.  Multibyte
+//!   characters are supported at both ends of the string, including across
+//!   the wrap-around point.  Most common traits are implemented.  Serde and
+//!   no-std are both supported.
 //! - The **[Flexstr]\<N\>** type becomes available with the **`flex-str` option**.
 //!   This type uses an internal enum that is either a tstr\<N\>
 //!   or an owned String (alloc::string::String) in case the length of the string exceeds N-1.
@@ -105,6 +167,18 @@
 //!   `Rc<RefCell<..>>` underneath to allow strings to be shared as well as
 //!   mutated.  This type does not implement `Copy` but `Clone` is done
 //!   in constant time.  no_std is supported but **not serde**.
+//!   The same option also provides **[CowSharedstr]\<N\>**, a copy-on-write
+//!   variant in which mutating one clone never affects the others.
+//! - The **[SyncSharedstr]\<N\>** type becomes available with the
+//!   **`sync-shared-str` option**.  It is a thread-safe version of
+//!   [Sharedstr] that uses `Arc` and a readers-writer lock (`std::sync::RwLock`
+//!   with the `std` option, a spin lock otherwise), so that it is `Send`
+//!   and `Sync`.  no_std is supported but **not serde**.
+//! - The packed types **[r40str12]**, **[r40str24]**, **[b6str10]** and
+//!   **[b6str21]** become available with the **`compressed-str` option**.
+//!   They store short strings over a restricted alphabet (such as upper-case
+//!   letters and digits) in a single `u64` or `u128`.  Serde and no-alloc
+//!   are both supported.
 //!
 //! **SUMMARY OF OPTIONAL FEATURES**
 //!
@@ -114,22 +188,29 @@
 //!   traits.
 //! - ***circular-str***: this feature makes available the **[cstr]** type.
 //! - ***flex-str***: this feature makes available the **[Flexstr]** type.  
-//! - ***shared-str***: this feature makes available the **[Sharedstr]** type.
+//! - ***shared-str***: this feature makes available the **[Sharedstr]** and
+//!   **[CowSharedstr]** types.
+//! - ***sync-shared-str***: this feature makes available the **[SyncSharedstr]** type.
+//! - ***compressed-str***: this feature makes available the packed string
+//!   types [r40str12], [r40str24], [b6str10] and [b6str21].
+//! - ***equivalent***: implements the `Equivalent` trait of the
+//!   [equivalent](https://docs.rs/equivalent) crate, so that maps keyed by
+//!   [cstr], [Sharedstr] or [SyncSharedstr], which cannot implement
+//!   `Borrow<str>`, can be looked up by `&str` in hashbrown and indexmap.
 //! - ***std***: this feature cancels `no_std` by enabling the **[fstr]** type.
 //!   An alias for this feature name is 'fstr'.
-//! - ***pub-tstr***: this feature will make the tstr type public. It is not
-//!   recommended: use instead the type aliases [str4] - [str256], which are
-//!   always available.
+//! - ***pub-tstr***: this feature no longer has any effect: the tstr type
+//!   is now always public.  It is retained for backwards compatibility.
 //! - **no-alloc**: this *anti-feature* disables any features that requires the alloc (or std)
-//!   crate.  It will disable *entirely* the fstr, Flexstr and Sharedstr types: using
+//!   crate.  It will disable *entirely* the fstr, Flexstr, Sharedstr and SyncSharedstr types: using
 //!   `no-alloc` together with `flex-str`, for example, will not enable the Flexstr type.
 //!   It also disables the features in [tstr], [zstr] and [cstr] that require the
 //!   alloc crate, in particular any use of alloc::string::String.  Using this feature
 //!   is *stronger than no_std*.  Note that when compiled with the `all-features` option, this feature will be included, which will exclude other features.
 //! - ***experimental***: the meaning of this feature may change.  Currently
 //!   it implements custom Indexing traits for the zstr type, including
-//!   `IndexMut<usize>`, which allows individual bytes to be changed
-//!   arbitrarily.  Experimental features are not part of the documentation.
+//!   `Index<usize>`, which returns individual bytes.  Experimental
+//!   features are not part of the documentation.
 //!
 //! None of these features is provided by default, so specifying
 //! `default-features=false` has no effect.
@@ -137,7 +218,7 @@
 //! **SAMPLE BUILD CONFIGURATIONS**
 //!
 //! The simplest way to install this create is to **`cargo add fixedstr`** in your
-//! crate or add `fixedstr = "0.5"` to your dependencies in Cargo.toml.
+//! crate or add `fixedstr = "0.6"` to your dependencies in Cargo.toml.
 //! The default build makes available the [zstr] type and the type aliases
 //! [str4] - [str256] for [tstr].  Serde is not available with this build
 //! but no_std is supported, substituting some std features with those from the
@@ -147,18 +228,18 @@
 //! in your crate or add the following in Cargo.toml.
 //! ```ignore
 //!   [dependencies]
-//!   fixedstr = {version="0.5", features=["no-alloc"]}
+//!   fixedstr = {version="0.6", features=["no-alloc"]}
 //! ```
 //!
 //! To further enable serde serialization, add the following instead:
 //! ```ignore
 //!   [dependencies]
-//!   fixedstr = {version="0.5", features=["serde","no-alloc"]}
+//!   fixedstr = {version="0.6", features=["serde","no-alloc"]}
 //! ```
 //! and to exclude `cstr` but include all other features (except `no-alloc`):
 //! ```ignore
 //!   [dependencies]
-//!   fixedstr = {version="0.5", features=["std","flex-str","shared-str","serde","pub-tstr","experimental"]}
+//!   fixedstr = {version="0.6", features=["std","flex-str","shared-str","serde","pub-tstr","experimental"]}
 //! ```
 //! <br>
 //!
@@ -170,7 +251,7 @@
 //!
 //!```
 //! use fixedstr::*;
-//! let a = str8::from("abcdefg"); //creates new string from &str
+//! let a = str8::make("abcdefg"); //creates new string from &str
 //! let a1 = a; // copied, not moved
 //! let a2:&str = a.to_str();
 //! let a3:String = a.to_string();
@@ -179,7 +260,7 @@
 //! assert_eq!(ab,"bcde");  // can compare with &str
 //! assert_eq!(&a[1..4],"bcd"); // implements Index
 //! assert!(a<ab);  // implements Ord (and Hash, Debug, Display, other traits)
-//! let mut u:zstr<8> = zstr::from("aλb"); //unicode support
+//! let mut u:zstr<8> = zstr::make("aλb"); //unicode support
 //! {assert_eq!(u.nth(1).unwrap(),'λ');} // nth returns Option<char>
 //! assert!(u.set(1,'μ'));  // changes a character of the same character class
 //! assert!(!u.set(1,'c')); // .set returns false on failure
@@ -196,8 +277,8 @@
 //! let (upper,lower) = (str8::make("ABC"), str8::make("abc"));
 //! assert_eq!(upper, lower.to_ascii_upper()); // no owned String needed
 //!  
-//! let c1 = str8::from("abcdef"); // string concatenation with + for strN types  
-//! let c2 = str8::from("xyz123");
+//! let c1 = str8::make("abcdef"); // string concatenation with + for strN types  
+//! let c2 = str8::make("xyz123");
 //! let c3 = c1 + c2;       
 //! assert_eq!(c3,"abcdefxyz123");   
 //! assert_eq!(c3.capacity(),15);  // type of c3 is str16
@@ -220,8 +301,9 @@
 //!
 //! #[cfg(feature = "experimental")]
 //! {
-//!   let mut s = <zstr<8>>::from("abcd");
-//!   s[0] = b'A';       // implements IndexMut<usize> (only for zstr)
+//!   let mut s = <zstr<8>>::make("abcd");
+//!   assert_eq!(s[0], b'a');  // implements Index<usize> (only for zstr)
+//!   s.set_byte_char(0, 'A');
 //!   assert_eq!(&s[0..3],"Abc");
 //! }
 //! ```
@@ -249,7 +331,7 @@ pub use full_fixed::*;
 //mod shared_structs;
 
 #[cfg(not(feature = "no-alloc"))]
-#[cfg(any(feature = "shared-str", feature = "flex-str"))]
+#[cfg(any(feature = "shared-str", feature = "flex-str", feature = "sync-shared-str"))]
 mod shared_structs;
 
 #[cfg(feature = "flex-str")]
@@ -265,15 +347,39 @@ mod shared_string;
 #[cfg(feature = "shared-str")]
 #[cfg(not(feature = "no-alloc"))]
 pub use shared_string::*;
+#[cfg(feature = "shared-str")]
+#[cfg(not(feature = "no-alloc"))]
+mod cow_shared_string;
+#[cfg(feature = "shared-str")]
+#[cfg(not(feature = "no-alloc"))]
+pub use cow_shared_string::*;
+
+#[cfg(feature = "sync-shared-str")]
+#[cfg(not(feature = "no-alloc"))]
+mod sync_shared_string;
+#[cfg(feature = "sync-shared-str")]
+#[cfg(not(feature = "no-alloc"))]
+pub use sync_shared_string::*;
+
+mod overflow_policy;
+pub use overflow_policy::*;
+
+mod pattern;
+pub use pattern::*;
+
+mod const_fns;
+
+mod niche;
 
 mod zero_terminated;
 pub use zero_terminated::*;
 
 mod tiny_internal;
-use tiny_internal::*;
-#[cfg(feature = "pub_tstr")]
 pub use tiny_internal::*;
 
+mod medium_string;
+pub use medium_string::*;
+
 #[cfg(feature = "circular-str")]
 mod circular_string;
 #[cfg(feature = "circular-str")]
@@ -282,83 +388,728 @@ pub use circular_string::*;
 #[cfg(not(feature = "no-alloc"))]
 extern crate alloc;
 
-/*
 #[cfg(feature = "compressed-str")]
-#[cfg(not(feature = "no-alloc"))]
 mod compressed;
 #[cfg(feature = "compressed-str")]
-#[cfg(not(feature = "no-alloc"))]
 pub use compressed::*;
-*/
 
 
+/// The error type returned by the fallible constructors and conversions
+/// of all string types in this crate (`try_make`, `from_utf16`,
+/// [core::str::FromStr], etc.).
+/// ```
+///  # use fixedstr::*;
+///  let e = zstr::<8>::try_make("abcdefghij").unwrap_err();
+///  assert_eq!(e, CapacityError::Overflow{required:10, available:7});
+///  let e = "ab\0c".parse::<zstr<8>>();
+///  assert_eq!(e, Err(CapacityError::InteriorNul{position:2}));
+///  let e = str8::from_utf16(&[0xD800]);
+///  assert_eq!(e, Err(CapacityError::InvalidUtf16));
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum CapacityError {
+    /// the string requires `required` bytes but the type only has room
+    /// for `available` bytes
+    Overflow { required: usize, available: usize },
+    /// the input is not valid utf8; it is valid up to the given byte index
+    InvalidUtf8 { valid_up_to: usize },
+    /// the input is not valid utf16
+    InvalidUtf16,
+    /// a non-ascii character was found at the given byte index (for
+    /// constructors such as `cstr::try_make_ascii` that only accept ascii)
+    NonAscii { position: usize },
+    /// a zero byte was found at the given index, which cannot be represented
+    /// by a zero-terminated [zstr]
+    InteriorNul { position: usize },
+    /// a character at the given byte index is outside of the alphabet of a
+    /// packed string type such as `r40str12`
+    Unencodable { position: usize },
+} //CapacityError
+
+impl core::fmt::Display for CapacityError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            CapacityError::Overflow { required, available } => write!(
+                f,
+                "capacity exceeded: {} bytes required but only {} available",
+                required, available
+            ),
+            CapacityError::InvalidUtf8 { valid_up_to } => {
+                write!(f, "invalid utf8 sequence after byte {}", valid_up_to)
+            }
+            CapacityError::InvalidUtf16 => write!(f, "invalid utf16 sequence"),
+            CapacityError::NonAscii { position } => {
+                write!(f, "non-ascii character at byte {}", position)
+            }
+            CapacityError::InteriorNul { position } => {
+                write!(f, "zero byte at position {}", position)
+            }
+            CapacityError::Unencodable { position } => {
+                write!(f, "unencodable character at byte {}", position)
+            }
+        }
+    }
+}
+
+impl core::error::Error for CapacityError {}
+
+impl From<core::str::Utf8Error> for CapacityError {
+    fn from(e: core::str::Utf8Error) -> Self {
+        CapacityError::InvalidUtf8 {
+            valid_up_to: e.valid_up_to(),
+        }
+    }
+}
+
+/// number of bytes needed to store a utf16-encoded slice as utf8
+pub(crate) fn utf16_len(v: &[u16]) -> Result<usize, CapacityError> {
+    char::decode_utf16(v.iter().cloned())
+        .map(|c| c.map(char::len_utf8).map_err(|_| CapacityError::InvalidUtf16))
+        .sum()
+}
+
+/// returns the largest index no greater than `n` that falls on a character
+/// boundary of the utf8-encoded `bytes`, so that truncating at that index
+/// never cuts a multibyte character in half.
+pub(crate) const fn floor_char_boundary(bytes: &[u8], n: usize) -> usize {
+    if n >= bytes.len() {
+        return bytes.len();
+    }
+    let mut n = n;
+    while n > 0 && (bytes[n] & 0xC0) == 0x80 {
+        n -= 1;
+    }
+    n
+} //floor_char_boundary
+
+/// converts a range of byte indices into a string of length `len` to a
+/// [core::ops::Range].  **Panics**, as slicing a `&str` does, if the range
+/// is out of bounds or does not fall on character boundaries.
+pub(crate) fn byte_range<R, B>(len: usize, range: R, is_boundary: B) -> core::ops::Range<usize>
+where
+    R: core::ops::RangeBounds<usize>,
+    B: Fn(usize) -> bool,
+{
+    use core::ops::Bound::*;
+    let start = match range.start_bound() {
+        Included(&i) => i,
+        Excluded(&i) => i + 1,
+        Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Included(&i) => i + 1,
+        Excluded(&i) => i,
+        Unbounded => len,
+    };
+    assert!(
+        start <= end && end <= len && is_boundary(start) && is_boundary(end),
+        "byte range {}..{} is out of bounds or not on char boundaries",
+        start,
+        end
+    );
+    start..end
+} //byte_range
+
+/// replaces the bytes in `range` of the first `len` bytes of `buf` with
+/// `with`, shifting the rest of the string and zeroing any bytes freed
+/// at the end.  Returns the new length, or a [CapacityError] (leaving
+/// `buf` unchanged) if it would exceed the length of `buf`.
+pub(crate) fn splice_bytes(
+    buf: &mut [u8],
+    len: usize,
+    range: core::ops::Range<usize>,
+    with: &[u8],
+) -> Result<usize, CapacityError> {
+    let newlen = len - range.len() + with.len();
+    if newlen > buf.len() {
+        return Err(CapacityError::Overflow {
+            required: newlen,
+            available: buf.len(),
+        });
+    }
+    buf.copy_within(range.end..len, range.start + with.len());
+    buf[range.start..range.start + with.len()].copy_from_slice(with);
+    if newlen < len {
+        buf[newlen..len].fill(0);
+    }
+    Ok(newlen)
+} //splice_bytes
+
 //////// Unifying Trait Approach
-#[cfg(feature = "experimental")]
-pub trait Fixedstr<const N:usize> {
-  type Target;
-  
-  fn make<TA : AsRef<str>>(s:TA) -> Self::Target;
-  
-  fn create<TA : AsRef<str>>(s:TA) -> Self::Target {
-    Self::make(s)
-  } // won't compile
-  fn try_make<TA:AsRef<str>>(s:TA) -> Result<Self::Target,TA> {
-    if s.as_ref().len()<N {Ok(Self::make(s))} else {Err(s)}
-  }
-
-  /*const*/ fn const_make(s:&str) -> Self::Target;
-  /*const*/ fn const_try_make(s:&str) -> Option<Self::Target>;
-
-  fn new() -> Self::Target {
-    Self::make("")
-  }
-
-  /*const*/ fn len(&self) -> usize;
-  fn charlen(&self) -> usize;
-  /*const*/ fn capacity(&self) -> usize { N-1 }
-
-  fn to_str(&self) -> &str;
-  fn as_str(&self) -> &str;
-  
-  #[cfg(not(feature = "no-alloc"))]
-  fn to_string(&self) -> alloc::string::String {
-        alloc::string::String::from(self.to_str())
-  }
-
-  fn set(&mut self, i: usize, c: char) -> bool;
-
-  fn push_str<'t>(&mut self, src: &'t str) -> &'t str;
-  
-  fn push_char(&mut self, c: char) -> bool;
-
-  fn pop_char(&mut self) -> Option<char>;
-  
-  fn nth(&self, n: usize) -> Option<char>;
-  
-  fn nth_bytechar(&self, n: usize) -> char;
-  
-  fn truncate(&mut self, n: usize);
-  
-  fn truncate_bytes(&mut self, n: usize);
-
-  fn truncate_unchecked(&mut self, n: usize);
-  
-  fn clear(&mut self);
-  
-  fn right_ascii_trim(&mut self);
-  
-  fn make_ascii_lowercase(&mut self);
-
-  fn make_ascii_uppercase(&mut self);
-
-  fn case_insensitive_eq<TA:AsRef<str>>(&self, other: TA) -> bool;
-
-  fn is_ascii(&self) -> bool {
-        self.to_str().is_ascii()
-  }
-
-  
-}//trait Fixedstr
+
+/// Common interface implemented by every string type of this crate:
+/// [zstr], `tstr` (and the aliases [str8]..[str256]), `fstr`, `cstr`,
+/// `Flexstr` and `Sharedstr`.  The trait is object-safe, so generic code
+/// can be written either with type parameters or with `&mut dyn Fixedstr`.
+///
+/// Because not every type stores its contents contiguously (a `cstr` is
+/// a ring buffer) or behind a plain reference (a `Sharedstr` is behind
+/// a `RefCell`), the contents are exposed as a pair of segments through
+/// [Fixedstr::with_segments].  The string is always the concatenation of
+/// the two segments.  Types that can lend out a `&str` directly also
+/// implement [ContiguousFixedstr].
+///
+/// Example:
+/// ```
+///  # use fixedstr::*;
+///  fn shout(s: &mut dyn Fixedstr) {
+///     s.push_char('!');
+///  }
+///  let mut a = zstr::<8>::make("abc");
+///  shout(&mut a);
+///  assert!(a.eq_str("abc!"));
+///  assert_eq!(a.capacity(), 7);
+/// ```
+pub trait Fixedstr {
+    /// length of the string in bytes
+    fn len(&self) -> usize;
+
+    /// the number of bytes the string can hold without spilling over to
+    /// the heap (for `Flexstr` and `Sharedstr`) or being truncated
+    /// (for all other types).
+    fn capacity(&self) -> usize;
+
+    /// calls the closure with the two segments that make up the string.
+    /// The second segment is empty unless the underlying representation
+    /// is not contiguous.
+    fn with_segments(&self, f: &mut dyn FnMut(&str, &str));
+
+    /// appends as much of `src` as will fit and returns the portion
+    /// that did not fit.  Types that can spill to the heap always
+    /// return the empty string.
+    fn push_str<'t>(&mut self, src: &'t str) -> &'t str;
+
+    /// appends a character, returning false (and leaving the string
+    /// unchanged) if it does not fit.
+    fn push_char(&mut self, c: char) -> bool;
+
+    /// removes and returns the last character, if any
+    fn pop_char(&mut self) -> Option<char>;
+
+    /// keeps only the first `n` bytes of the string.  No effect if `n` is
+    /// not less than the length.  **Panics** if `n` is not on a character
+    /// boundary.
+    fn truncate_bytes(&mut self, n: usize);
+
+    /// resets the string to empty
+    fn clear(&mut self);
+
+    /// whether the string is empty
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// length of the string in characters
+    fn charlen(&self) -> usize {
+        let mut n = 0;
+        self.with_segments(&mut |a, b| n = a.chars().count() + b.chars().count());
+        n
+    }
+
+    /// returns the nth character of the string, if it exists
+    fn nth(&self, n: usize) -> Option<char> {
+        let mut answer = None;
+        self.with_segments(&mut |a, b| answer = a.chars().chain(b.chars()).nth(n));
+        answer
+    }
+
+    /// whether the string consists only of ascii characters
+    fn is_ascii(&self) -> bool {
+        let mut answer = true;
+        self.with_segments(&mut |a, b| answer = a.is_ascii() && b.is_ascii());
+        answer
+    }
+
+    /// compares the contents of the string with a `&str`
+    fn eq_str(&self, other: &str) -> bool {
+        let mut answer = false;
+        self.with_segments(&mut |a, b| {
+            answer = other.len() == a.len() + b.len()
+                && other.starts_with(a)
+                && other.ends_with(b)
+        });
+        answer
+    }
+
+    /// writes the contents of the string to a [core::fmt::Write]
+    fn write_to(&self, w: &mut dyn core::fmt::Write) -> core::fmt::Result {
+        let mut answer = Ok(());
+        self.with_segments(&mut |a, b| answer = w.write_str(a).and_then(|_| w.write_str(b)));
+        answer
+    }
+} //trait Fixedstr
+
+/// Constructors shared by all string types of the crate. This trait is
+/// separate from [Fixedstr] so that the latter remains object-safe.
+/// ```
+///  # use fixedstr::*;
+///  fn parse_word<T: FixedstrMake>(input: &str) -> T {
+///     T::make(input.split_whitespace().next().unwrap_or(""))
+///  }
+///  let w: zstr<16> = parse_word("hello world");
+///  assert_eq!(w, "hello");
+///  let w: str4 = parse_word("hello world");
+///  assert_eq!(w, "hel");
+/// ```
+pub trait FixedstrMake: Fixedstr + Sized {
+    /// creates a string from a `&str`, truncating if necessary
+    fn make(s: &str) -> Self;
+
+    /// creates a string from a `&str`, without truncation
+    fn try_make(s: &str) -> Result<Self, CapacityError>;
+
+    /// creates a string from utf8-encoded bytes, without truncation
+    fn from_utf8(v: &[u8]) -> Result<Self, CapacityError> {
+        Self::try_make(core::str::from_utf8(v)?)
+    }
+
+    /// creates an empty string
+    fn new() -> Self {
+        Self::make("")
+    }
+} //trait FixedstrMake
+
+/// Implemented by the string types that store their contents
+/// contiguously and can lend them out as a single `&str`.
+pub trait ContiguousFixedstr: Fixedstr {
+    /// returns the string as a `&str`
+    fn as_str(&self) -> &str;
+}
+
+/// Implemented by the string types whose capacity is determined by their
+/// type alone: [tstr], [zstr], `fstr` and `cstr`.  This allows the capacity
+/// needed for a concatenation to be checked at compile time, as done by
+/// [zstr::concat] and [str_concat!].
+pub trait FixedCapacity: Fixedstr {
+    /// the maximum length of the string in bytes
+    const CAPACITY: usize;
+}
+
+/// fails to compile unless a `B` can hold any `A`
+pub(crate) fn assert_grows<A: FixedCapacity, B: FixedCapacity>() {
+    const { assert!(B::CAPACITY >= A::CAPACITY, "grow: the new capacity is smaller than the old") }
+}
+
+/// fails to compile unless a `C` can hold the concatenation of an `A` and a `B`
+pub(crate) fn assert_concat_fits<A, B, C>(_other: &B)
+where
+    A: FixedCapacity,
+    B: FixedCapacity + ?Sized,
+    C: FixedCapacity,
+{
+    const {
+        assert!(
+            C::CAPACITY >= A::CAPACITY + B::CAPACITY,
+            "the result type is too small to hold the concatenation"
+        )
+    }
+}
+
+/// appends the contents of `src` to `dst`, which must have room for it
+pub(crate) fn append_all(dst: &mut dyn Fixedstr, src: &(impl Fixedstr + ?Sized)) {
+    src.with_segments(&mut |a, b| {
+        dst.push_str(a);
+        dst.push_str(b);
+    });
+}
+
+/// Tuples of references to [FixedCapacity] strings, used by [str_concat!].
+#[doc(hidden)]
+pub trait ConcatParts {
+    const CAPACITY: usize;
+    fn append_to(&self, dst: &mut dyn Fixedstr);
+}
+
+macro_rules! impl_concat_parts {
+    ($($t:ident $i:tt),+) => {
+        impl<$($t: FixedCapacity),+> ConcatParts for ($(&$t,)+) {
+            const CAPACITY: usize = 0 $(+ $t::CAPACITY)+;
+            fn append_to(&self, dst: &mut dyn Fixedstr) {
+                $(append_all(dst, self.$i);)+
+            }
+        }
+    };
+}
+impl_concat_parts!(A 0);
+impl_concat_parts!(A 0, B 1);
+impl_concat_parts!(A 0, B 1, C 2);
+impl_concat_parts!(A 0, B 1, C 2, D 3);
+impl_concat_parts!(A 0, B 1, C 2, D 3, E 4);
+impl_concat_parts!(A 0, B 1, C 2, D 3, E 4, F 5);
+impl_concat_parts!(A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+impl_concat_parts!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+
+#[doc(hidden)]
+pub fn concat_parts<T: FixedCapacity + FixedstrMake, S: ConcatParts>(parts: S) -> T {
+    const {
+        assert!(
+            T::CAPACITY >= S::CAPACITY,
+            "str_concat!: the result type is too small to hold the concatenation"
+        )
+    }
+    let mut cat = T::new();
+    parts.append_to(&mut cat);
+    cat
+}
+
+// `X<N> + X<N>` yields an `X<2N>`, which can always hold the result
+macro_rules! impl_doubling_add {
+    ($ty:ident; $($n:literal => $m:literal),+) => {$(
+        impl<P: OverflowPolicy> core::ops::Add for $ty<$n, P> {
+            type Output = $ty<$m, P>;
+            fn add(self, other: Self) -> $ty<$m, P> {
+                let mut cat: $ty<$m, P> = self.resize();
+                append_all(&mut cat, &other);
+                cat
+            }
+        }
+    )+};
+}
+impl_doubling_add!(tstr; 4 => 8, 8 => 16, 12 => 24, 16 => 32, 24 => 48,
+                   32 => 64, 48 => 96, 64 => 128, 96 => 192, 128 => 256);
+impl_doubling_add!(mstr; 256 => 512, 512 => 1024, 1024 => 2048, 2048 => 4096,
+                   4096 => 8192, 8192 => 16384, 16384 => 32768, 32768 => 65536);
+impl_doubling_add!(zstr; 4 => 8, 8 => 16, 12 => 24, 16 => 32, 24 => 48,
+                   32 => 64, 48 => 96, 64 => 128, 96 => 192, 128 => 256, 256 => 512);
+#[cfg(feature = "std")]
+#[cfg(not(feature = "no-alloc"))]
+impl_doubling_add!(fstr; 4 => 8, 8 => 16, 12 => 24, 16 => 32, 24 => 48,
+                   32 => 64, 48 => 96, 64 => 128, 96 => 192, 128 => 256, 256 => 512);
+#[cfg(feature = "circular-str")]
+impl_doubling_add!(cstr; 4 => 8, 8 => 16, 12 => 24, 16 => 32, 24 => 48,
+                   32 => 64, 48 => 96, 64 => 128, 96 => 192, 128 => 256, 256 => 512);
+
+/// compares the contents of two strings, which may be of different types
+pub(crate) fn cmp_segments(a: &dyn Fixedstr, b: &dyn Fixedstr) -> core::cmp::Ordering {
+    let mut answer = core::cmp::Ordering::Equal;
+    a.with_segments(&mut |a1, a2| {
+        b.with_segments(&mut |b1, b2| {
+            answer = a1.bytes().chain(a2.bytes()).cmp(b1.bytes().chain(b2.bytes()))
+        })
+    });
+    answer
+}
+
+/// compares the contents of a string with a `&str`
+pub(crate) fn cmp_str(a: &dyn Fixedstr, b: &str) -> core::cmp::Ordering {
+    let mut answer = core::cmp::Ordering::Equal;
+    a.with_segments(&mut |a1, a2| answer = a1.bytes().chain(a2.bytes()).cmp(b.bytes()));
+    answer
+}
+
+// `PartialEq` and `PartialOrd` between every pair of distinct string types
+// of the crate.  Each type is given twice, with generic parameters named
+// N, P and M, Q respectively.  Strings of the same type but of different
+// capacities are deliberately not comparable: that would break the type
+// inference of expressions such as `s == t.resize()`.
+macro_rules! impl_cmp_matrix {
+    (@pair {$(#[$am:meta])* [$($ap:tt)*] $at:ty, [$($aq:tt)*] $au:ty}
+           {$(#[$bm:meta])* [$($bp:tt)*] $bt:ty, [$($bq:tt)*] $bu:ty}) => {
+        $(#[$am])* $(#[$bm])*
+        impl<$($ap)* $($bq)*> PartialEq<$bu> for $at {
+            fn eq(&self, other: &$bu) -> bool {
+                Fixedstr::len(self) == Fixedstr::len(other) && cmp_segments(self, other).is_eq()
+            }
+        }
+        $(#[$am])* $(#[$bm])*
+        impl<$($ap)* $($bq)*> PartialOrd<$bu> for $at {
+            fn partial_cmp(&self, other: &$bu) -> Option<core::cmp::Ordering> {
+                Some(cmp_segments(self, other))
+            }
+        }
+    };
+    () => {};
+    ($a:tt $($b:tt)*) => {
+        $(
+            impl_cmp_matrix!(@pair $a $b);
+            impl_cmp_matrix!(@pair $b $a);
+        )*
+        impl_cmp_matrix!($($b)*);
+    };
+}
+
+// `PartialEq` and `PartialOrd` between each string type of the crate and
+// `str`, `&str`, `String`, `Box<str>` and `Cow<str>`, in both directions.
+// Equality with `&str` is implemented separately by each type.
+macro_rules! impl_cmp_std {
+    ($({$(#[$m:meta])* [$($p:tt)*] $t:ty, [$($q:tt)*] $u:ty})*) => {$(
+        $(#[$m])*
+        impl<$($p)*> PartialEq<str> for $t {
+            fn eq(&self, other: &str) -> bool {
+                Fixedstr::eq_str(self, other)
+            }
+        }
+        $(#[$m])*
+        impl<$($p)*> PartialEq<$t> for str {
+            fn eq(&self, other: &$t) -> bool {
+                Fixedstr::eq_str(other, self)
+            }
+        }
+        $(#[$m])*
+        impl<$($p)*> PartialOrd<str> for $t {
+            fn partial_cmp(&self, other: &str) -> Option<core::cmp::Ordering> {
+                Some(cmp_str(self, other))
+            }
+        }
+        $(#[$m])*
+        impl<$($p)*> PartialOrd<$t> for str {
+            fn partial_cmp(&self, other: &$t) -> Option<core::cmp::Ordering> {
+                Some(cmp_str(other, self).reverse())
+            }
+        }
+        $(#[$m])*
+        impl<'a, $($p)*> PartialOrd<&'a str> for $t {
+            fn partial_cmp(&self, other: &&'a str) -> Option<core::cmp::Ordering> {
+                Some(cmp_str(self, other))
+            }
+        }
+        $(#[$m])*
+        impl<'a, $($p)*> PartialOrd<$t> for &'a str {
+            fn partial_cmp(&self, other: &$t) -> Option<core::cmp::Ordering> {
+                Some(cmp_str(other, self).reverse())
+            }
+        }
+        impl_cmp_std!(@owned [$(#[$m])*] [$($p)*] $t; [] alloc::string::String);
+        impl_cmp_std!(@owned [$(#[$m])*] [$($p)*] $t; [] alloc::boxed::Box<str>);
+        impl_cmp_std!(@owned [$(#[$m])*] [$($p)*] $t; ['a,] alloc::borrow::Cow<'a, str>);
+    )*};
+    (@owned [$(#[$m:meta])*] [$($p:tt)*] $t:ty; [$($l:tt)*] $o:ty) => {
+        $(#[$m])*
+        #[cfg(not(feature = "no-alloc"))]
+        impl<$($l)* $($p)*> PartialEq<$o> for $t {
+            fn eq(&self, other: &$o) -> bool {
+                Fixedstr::eq_str(self, other)
+            }
+        }
+        $(#[$m])*
+        #[cfg(not(feature = "no-alloc"))]
+        impl<$($l)* $($p)*> PartialEq<$t> for $o {
+            fn eq(&self, other: &$t) -> bool {
+                Fixedstr::eq_str(other, self)
+            }
+        }
+        $(#[$m])*
+        #[cfg(not(feature = "no-alloc"))]
+        impl<$($l)* $($p)*> PartialOrd<$o> for $t {
+            fn partial_cmp(&self, other: &$o) -> Option<core::cmp::Ordering> {
+                Some(cmp_str(self, other))
+            }
+        }
+        $(#[$m])*
+        #[cfg(not(feature = "no-alloc"))]
+        impl<$($l)* $($p)*> PartialOrd<$t> for $o {
+            fn partial_cmp(&self, other: &$t) -> Option<core::cmp::Ordering> {
+                Some(cmp_str(other, self).reverse())
+            }
+        }
+    };
+}
+
+// the string types of the crate, as taken by impl_cmp_matrix, impl_cmp_std
+// and impl_try_from, preceded by any further arguments to `$mac`
+macro_rules! with_all_string_types {
+    ($mac:ident $($arg:tt)*) => {
+        $mac!($($arg)*
+            {[const N: usize, P: OverflowPolicy,] tstr<N, P>, [const M: usize, Q: OverflowPolicy,] tstr<M, Q>}
+            {[const N: usize, P: OverflowPolicy,] mstr<N, P>, [const M: usize, Q: OverflowPolicy,] mstr<M, Q>}
+            {[const N: usize, P: OverflowPolicy,] zstr<N, P>, [const M: usize, Q: OverflowPolicy,] zstr<M, Q>}
+            {#[cfg(feature = "std")] #[cfg(not(feature = "no-alloc"))]
+             [const N: usize, P: OverflowPolicy,] fstr<N, P>, [const M: usize, Q: OverflowPolicy,] fstr<M, Q>}
+            {#[cfg(feature = "circular-str")]
+             [const N: usize, P: OverflowPolicy,] cstr<N, P>, [const M: usize, Q: OverflowPolicy,] cstr<M, Q>}
+            {#[cfg(feature = "flex-str")] #[cfg(not(feature = "no-alloc"))]
+             [const N: usize,] Flexstr<N>, [const M: usize,] Flexstr<M>}
+            {#[cfg(feature = "shared-str")] #[cfg(not(feature = "no-alloc"))]
+             [const N: usize,] Sharedstr<N>, [const M: usize,] Sharedstr<M>}
+            {#[cfg(feature = "shared-str")] #[cfg(not(feature = "no-alloc"))]
+             [const N: usize,] CowSharedstr<N>, [const M: usize,] CowSharedstr<M>}
+            {#[cfg(feature = "sync-shared-str")] #[cfg(not(feature = "no-alloc"))]
+             [const N: usize,] SyncSharedstr<N>, [const M: usize,] SyncSharedstr<M>}
+            {#[cfg(feature = "compressed-str")] [] r40str12, [] r40str12}
+            {#[cfg(feature = "compressed-str")] [] r40str24, [] r40str24}
+            {#[cfg(feature = "compressed-str")] [] b6str10, [] b6str10}
+            {#[cfg(feature = "compressed-str")] [] b6str21, [] b6str21}
+        );
+    };
+}
+with_all_string_types!(impl_cmp_matrix);
+with_all_string_types!(impl_cmp_std);
+
+/// The items from which the string types can be collected with
+/// [FromIterator] and extended with [Extend]: `char`, `&char`, `&str`
+/// and (unless `no-alloc`) `String`.  Items are pushed in turn until one
+/// does not fit entirely, which is handled according to the
+/// [OverflowPolicy] of the string.  The `try_from_iter` and `try_extend`
+/// methods of the fixed-capacity types report an overflow instead.
+/// ```
+///  # use fixedstr::*;
+///  let s: str8 = "abc".chars().rev().collect();
+///  assert_eq!(s, "cba");
+///  let mut z = zstr::<8>::make("ab");
+///  z.extend(["cd", "efgh", "ij"]);
+///  assert_eq!(z, "abcdefg");
+///  let t = str8::try_from_iter(["abcd", "efgh", "ij"]);
+///  assert_eq!(t, Err(CapacityError::Overflow { required: 10, available: 7 }));
+/// ```
+pub trait StrPiece {
+    /// calls `f` with the item as a `&str`
+    fn with_str<R>(&self, f: impl FnOnce(&str) -> R) -> R;
+}
+
+impl StrPiece for char {
+    fn with_str<R>(&self, f: impl FnOnce(&str) -> R) -> R {
+        f(self.encode_utf8(&mut [0u8; 4]))
+    }
+}
+impl StrPiece for &char {
+    fn with_str<R>(&self, f: impl FnOnce(&str) -> R) -> R {
+        (**self).with_str(f)
+    }
+}
+impl StrPiece for &str {
+    fn with_str<R>(&self, f: impl FnOnce(&str) -> R) -> R {
+        f(self)
+    }
+}
+#[cfg(not(feature = "no-alloc"))]
+impl StrPiece for alloc::string::String {
+    fn with_str<R>(&self, f: impl FnOnce(&str) -> R) -> R {
+        f(self)
+    }
+}
+
+/// pushes the items with `push`, which returns whether an item fit, until
+/// one does not.  As [Extend] cannot report the overflow, it panics under
+/// [Strict], like the constructors that cannot fail.
+pub(crate) fn extend_pieces<T: StrPiece>(
+    iter: impl IntoIterator<Item = T>,
+    mode: OverflowMode,
+    mut push: impl FnMut(&str) -> bool,
+) {
+    for piece in iter {
+        if !piece.with_str(&mut push) {
+            if mode == OverflowMode::Strict {
+                panic!("string capacity exceeded under Strict overflow policy");
+            }
+            break;
+        }
+    }
+} //extend_pieces
+
+/// pushes all of the items onto `dst` if they fit in `available` bytes,
+/// and otherwise returns a [CapacityError], leaving `dst` unchanged.
+/// Zero bytes are rejected if `reject_nul` is set (for [zstr]).
+pub(crate) fn try_extend_pieces<T: StrPiece>(
+    dst: &mut dyn Fixedstr,
+    available: usize,
+    reject_nul: bool,
+    iter: impl IntoIterator<Item = T>,
+) -> Result<(), CapacityError> {
+    let start = dst.len();
+    let mut iter = iter.into_iter();
+    while let Some(piece) = iter.next() {
+        let error = piece.with_str(|s| {
+            let nul = s.bytes().position(|b| b == 0).filter(|_| reject_nul);
+            if dst.len() + s.len() > available {
+                Some(CapacityError::Overflow { required: dst.len() + s.len(), available })
+            } else if let Some(i) = nul {
+                Some(CapacityError::InteriorNul { position: dst.len() + i })
+            } else {
+                dst.push_str(s);
+                None
+            }
+        });
+        if let Some(mut e) = error {
+            if let CapacityError::Overflow { required, .. } = &mut e {
+                *required += iter.map(|p| p.with_str(str::len)).sum::<usize>();
+            }
+            dst.truncate_bytes(start);
+            return Err(e);
+        }
+    }
+    Ok(())
+} //try_extend_pieces
+
+/// calls `f` with the two segments of `src` and returns its result
+pub(crate) fn map_segments<R>(src: &(impl Fixedstr + ?Sized), f: impl FnOnce(&str, &str) -> R) -> R {
+    let (mut f, mut answer) = (Some(f), None);
+    src.with_segments(&mut |a, b| answer = f.take().map(|f| f(a, b)));
+    answer.expect("with_segments did not call its closure")
+}
+
+// `TryFrom` conversions into the fixed-capacity types tstr, zstr, fstr and
+// cstr, which fail with a [CapacityError] where `make` would truncate:
+// from a reference to any string type of the crate (including a string of
+// the same type but a different capacity), from any other fixed-capacity
+// type by value, and from `&str`, `char`, `String`, `Box<str>` and
+// `Cow<str>`.  Same-type conversions by value would conflict with the
+// reflexive `From<T> for T`: `resize` and `grow` cover them instead.
+macro_rules! impl_try_from {
+    (@ref {$(#[$am:meta])* [$($ap:tt)*] $at:ty, [$($aq:tt)*] $au:ty}
+          {$(#[$bm:meta])* [$($bp:tt)*] $bt:ty, [$($bq:tt)*] $bu:ty}) => {
+        $(#[$am])* $(#[$bm])*
+        impl<'a, $($ap)* $($bq)*> TryFrom<&'a $bu> for $at {
+            type Error = CapacityError;
+            fn try_from(s: &'a $bu) -> Result<Self, CapacityError> {
+                map_segments(s, |a, b| Self::try_from_iter([a, b]))
+            }
+        }
+    };
+    (@val {$(#[$am:meta])* [$($ap:tt)*] $at:ty, [$($aq:tt)*] $au:ty}
+          {$(#[$bm:meta])* [$($bp:tt)*] $bt:ty, [$($bq:tt)*] $bu:ty}) => {
+        $(#[$am])* $(#[$bm])*
+        impl<$($ap)* $($bq)*> TryFrom<$bu> for $at {
+            type Error = CapacityError;
+            fn try_from(s: $bu) -> Result<Self, CapacityError> {
+                map_segments(&s, |a, b| Self::try_from_iter([a, b]))
+            }
+        }
+    };
+    (@std $(#[$m:meta])* [$($l:tt)*] [$($p:tt)*] $t:ty; $s:ident: $o:ty => $e:expr) => {
+        $(#[$m])*
+        impl<$($l)* $($p)*> TryFrom<$o> for $t {
+            type Error = CapacityError;
+            fn try_from($s: $o) -> Result<Self, CapacityError> {
+                Self::try_make($e)
+            }
+        }
+    };
+    (@refs $dst:tt $($src:tt)*) => {
+        $(impl_try_from!(@ref $dst $src);)*
+    };
+    (@values) => {};
+    (@values $a:tt $($b:tt)*) => {
+        $(
+            impl_try_from!(@val $a $b);
+            impl_try_from!(@val $b $a);
+        )*
+        impl_try_from!(@values $($b)*);
+    };
+    ($({$(#[$m:meta])* [$($p:tt)*] $t:ty, [$($q:tt)*] $u:ty})*) => {
+        $(
+            with_all_string_types!(impl_try_from @refs {$(#[$m])* [$($p)*] $t, [$($q)*] $u});
+            impl_try_from!(@std $(#[$m])* ['a,] [$($p)*] $t; s: &'a str => s);
+            impl_try_from!(@std $(#[$m])* [] [$($p)*] $t; c: char => c.encode_utf8(&mut [0u8; 4]));
+            impl_try_from!(@std $(#[$m])* #[cfg(not(feature = "no-alloc"))]
+                           [] [$($p)*] $t; s: alloc::string::String => &s);
+            impl_try_from!(@std $(#[$m])* #[cfg(not(feature = "no-alloc"))]
+                           [] [$($p)*] $t; s: alloc::boxed::Box<str> => &s);
+            impl_try_from!(@std $(#[$m])* #[cfg(not(feature = "no-alloc"))]
+                           ['a,] [$($p)*] $t; s: alloc::borrow::Cow<'a, str> => &s);
+        )*
+        impl_try_from!(@values $({$(#[$m])* [$($p)*] $t, [$($q)*] $u})*);
+    };
+}
+impl_try_from!(
+    {[const N: usize, P: OverflowPolicy,] tstr<N, P>, [const M: usize, Q: OverflowPolicy,] tstr<M, Q>}
+    {[const N: usize, P: OverflowPolicy,] mstr<N, P>, [const M: usize, Q: OverflowPolicy,] mstr<M, Q>}
+    {[const N: usize, P: OverflowPolicy,] zstr<N, P>, [const M: usize, Q: OverflowPolicy,] zstr<M, Q>}
+    {#[cfg(feature = "std")] #[cfg(not(feature = "no-alloc"))]
+     [const N: usize, P: OverflowPolicy,] fstr<N, P>, [const M: usize, Q: OverflowPolicy,] fstr<M, Q>}
+    {#[cfg(feature = "circular-str")]
+     [const N: usize, P: OverflowPolicy,] cstr<N, P>, [const M: usize, Q: OverflowPolicy,] cstr<M, Q>}
+);
 
 
 
@@ -393,22 +1144,45 @@ mod serde_support {
                     f.write_str("a string")
                 }
                 fn visit_str<E: serde::de::Error>(self, s: &str) -> Result<Self::Value, E> {
-                    $ty::try_make(s).map_err(|_| E::custom("string too long"))
+                    $ty::try_make(s).map_err(E::custom)
+                }
+            }
+        };
+        ($ty: ident, $visitor: ident, policy) => {
+            impl<const N: usize, P: OverflowPolicy> Serialize for $ty<N, P> {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serializer.serialize_str(self.as_str())
+                }
+            }
+            impl<'de, const N: usize, P: OverflowPolicy> Deserialize<'de> for $ty<N, P> {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    deserializer.deserialize_str($visitor(core::marker::PhantomData))
+                }
+            }
+            struct $visitor<const N: usize, P>(core::marker::PhantomData<P>);
+            impl<'de, const N: usize, P: OverflowPolicy> Visitor<'de> for $visitor<N, P> {
+                type Value = $ty<N, P>;
+                fn expecting(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                    f.write_str("a string")
+                }
+                fn visit_str<E: serde::de::Error>(self, s: &str) -> Result<Self::Value, E> {
+                    $ty::try_make(s).map_err(E::custom)
                 }
             }
         };
     }
-    generate_impl!(zstr, ZstrVisitor);
-    generate_impl!(tstr, TstrVisitor);
+    generate_impl!(zstr, ZstrVisitor, policy);
+    generate_impl!(tstr, TstrVisitor, policy);
+    generate_impl!(mstr, MstrVisitor, policy);
     #[cfg(feature = "std")]
     #[cfg(not(feature = "no-alloc"))]
-    generate_impl!(fstr, FstrVisitor);
+    generate_impl!(fstr, FstrVisitor, policy);
     #[cfg(feature = "flex-str")]
     #[cfg(not(feature = "no-alloc"))]
     generate_impl!(Flexstr, FlexstrVisitor);
 
     #[cfg(feature = "circular-str")]
-    impl<const N: usize> Serialize for cstr<N> {
+    impl<const N: usize, P: OverflowPolicy> Serialize for cstr<N, P> {
         fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
             let s = self.to_contiguous(); //self.to_string();
             let (a, _) = s.to_strs();
@@ -417,24 +1191,58 @@ mod serde_support {
     } //serialize
 
     #[cfg(feature = "circular-str")]
-    struct CstrVisitor<const N: usize>;
+    struct CstrVisitor<const N: usize, P>(core::marker::PhantomData<P>);
     #[cfg(feature = "circular-str")]
-    impl<'de, const N: usize> Visitor<'de> for CstrVisitor<N> {
-        type Value = cstr<N>;
+    impl<'de, const N: usize, P: OverflowPolicy> Visitor<'de> for CstrVisitor<N, P> {
+        type Value = cstr<N, P>;
         fn expecting(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
             f.write_str("a string")
         }
         fn visit_str<E: serde::de::Error>(self, s: &str) -> Result<Self::Value, E> {
-            cstr::try_make(s).map_err(|_| E::custom("string too long"))
+            cstr::try_make(s).map_err(E::custom)
         }
     }
 
     #[cfg(feature = "circular-str")]
-    impl<'de, const N: usize> Deserialize<'de> for cstr<N> {
+    impl<'de, const N: usize, P: OverflowPolicy> Deserialize<'de> for cstr<N, P> {
         fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
-            deserializer.deserialize_str(CstrVisitor)
+            deserializer.deserialize_str(CstrVisitor(core::marker::PhantomData))
         }
     }
+
+    #[cfg(feature = "compressed-str")]
+    macro_rules! generate_packed_impl {
+        ($ty: ident, $visitor: ident) => {
+            impl Serialize for $ty {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    self.with_str(|s| serializer.serialize_str(s))
+                }
+            }
+            impl<'de> Deserialize<'de> for $ty {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    deserializer.deserialize_str($visitor)
+                }
+            }
+            struct $visitor;
+            impl<'de> Visitor<'de> for $visitor {
+                type Value = $ty;
+                fn expecting(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                    f.write_str("a string")
+                }
+                fn visit_str<E: serde::de::Error>(self, s: &str) -> Result<Self::Value, E> {
+                    $ty::try_make(s).map_err(E::custom)
+                }
+            }
+        };
+    }
+    #[cfg(feature = "compressed-str")]
+    generate_packed_impl!(r40str12, R40str12Visitor);
+    #[cfg(feature = "compressed-str")]
+    generate_packed_impl!(r40str24, R40str24Visitor);
+    #[cfg(feature = "compressed-str")]
+    generate_packed_impl!(b6str10, B6str10Visitor);
+    #[cfg(feature = "compressed-str")]
+    generate_packed_impl!(b6str21, B6str21Visitor);
 } //serde
 
 /// Types for small strings that use an efficient representation
@@ -445,14 +1253,13 @@ mod serde_support {
 /// Example:
 /// ```
 ///  # use fixedstr::str8;
-///  let mut s = str8::from("aλc");
+///  let mut s = str8::make("aλc");
 ///  assert_eq!(s.capacity(),7);
 ///  assert_eq!(s.push("1234567"), "4567");
 ///  assert_eq!(s,"aλc123");
 ///  assert_eq!(s.charlen(), 6);
 ///  assert_eq!(s.len(), 7);  
 /// ```
-
 pub type str8 = tstr<8>;
 /// A str16 can hold a string of up to 15 bytes. See docs for [fstr] or [zstr].
 /// The size of str16 is 16 bytes, which is the same as for &str on 64bit
@@ -473,16 +1280,17 @@ pub type str128 = tstr<128>;
 ///<br>
 /// In addition, the str4-str128 types implement [core::ops::Add] in a way that
 /// two str8 strings will always concatenate to str16, and similarly for
-/// all other strN types up to str128.
+/// all other strN types up to str128.  The same holds for [zstr], `fstr`
+/// and `cstr` of sizes 4 to 256.  Strings of different sizes can be
+/// concatenated without truncation with [tstr::concat] and [str_concat!].
 ///```
 ///  # use fixedstr::*;
-///  let c1 = str8::from("abcd");
-///  let c2 = str8::from("xyz");
+///  let c1 = str8::make("abcd");
+///  let c2 = str8::make("xyz");
 ///  let c3 = c1 + c2;
 ///  assert_eq!(c3,"abcdxyz");
 ///  assert_eq!(c3.capacity(),15);
 ///```
-
 pub type str256 = tstr<256>;
 
 /// Alias for internal type `tstr<4>`.
@@ -532,6 +1340,63 @@ macro_rules! try_format {
   };
 }
 
+#[macro_export]
+/// creates a string of the given type from a string literal (or any
+/// `&str` constant) in a const context, so that a literal that does not
+/// fit **fails to compile** instead of being truncated as with
+/// `const_make`.  The type can be any of [tstr] (and the [str8].. aliases),
+/// [zstr], `fstr` and `cstr`.  When no type is given, the smallest of the
+/// aliases [str4], [str8], [str12], [str16], [str24] .. [str256] that can
+/// hold the literal is chosen.  The macro can be used to initialize
+/// `const` and `static` items:
+/// ```
+///   # use fixedstr::*;
+///   const GREETING: str8 = fixedstr!(str8, "hello");
+///   static NAME: zstr<16> = fixedstr!(zstr<16>, "λ-calculus");
+///   let s = fixedstr!("abcdefgh");
+///   assert_eq!((GREETING, NAME.len(), s.capacity()), (str8::make("hello"), 11, 11));
+///   let t: str12 = s;
+/// ```
+/// ```compile_fail
+///   # use fixedstr::*;
+///   let s = fixedstr!(str8, "abcdefghij");
+/// ```
+macro_rules! fixedstr {
+    ($ty:ty, $lit:expr) => {{
+        const FIXEDSTR_LITERAL: $ty = match <$ty>::const_try_make($lit) {
+            Ok(s) => s,
+            Err(_) => panic!("fixedstr!: the literal does not fit in the given type"),
+        };
+        FIXEDSTR_LITERAL
+    }};
+    ($lit:expr) => {
+        $crate::fixedstr!($crate::tstr<{ $crate::tstr_fit($lit.len()) }>, $lit)
+    };
+}
+
+#[macro_export]
+/// concatenates up to eight strings of types implementing [FixedCapacity]
+/// (which may differ) into a string of the given type.  The capacity of
+/// the result type must be at least the sum of the capacities of the
+/// arguments, which is checked at compile time, so nothing is ever
+/// truncated.
+/// ```
+///   # use fixedstr::*;
+///   let (a, b, c) = (str8::make("abc"), zstr::<4>::make("-"), str16::make("xyz"));
+///   let s = str_concat!(str32; a, b, c); // 7 + 3 + 15 <= 31
+///   assert_eq!(s, "abc-xyz");
+/// ```
+/// ```compile_fail
+///   # use fixedstr::*;
+///   let (a, b) = (str8::make("abc"), str8::make("xyz"));
+///   let s = str_concat!(str12; a, b);
+/// ```
+macro_rules! str_concat {
+    ($ty:ty; $($s:expr),+ $(,)?) => {
+        $crate::concat_parts::<$ty, _>(($(&$s,)+))
+    };
+}
+
 /*
 //////////// to string trait
 pub trait ToTstr<const N: usize> {
@@ -581,12 +1446,27 @@ macro_rules! convert_to_str {
 /////////////////////////////////////////////////////  Testing ...
 
 #[cfg(test)]
+#[allow(deprecated)] // older tests use the truncating `from` of versions before 0.6.0
 mod tests {
     use super::*;
     #[test]
     fn testmain() {
         nostdtest();
         ztests();
+        traittests();
+        boundarytests();
+        errortests().unwrap();
+        policytests();
+        patterntests();
+        splittests();
+        edittests();
+        concattests();
+        cmptests();
+        hashtests();
+        collecttests();
+        convtests();
+        mstrtests();
+        nichetests();
 
         #[cfg(feature = "std")]
         #[cfg(not(feature = "no-alloc"))]
@@ -603,10 +1483,756 @@ mod tests {
         #[cfg(all(feature = "std", feature = "shared-str"))]
         #[cfg(not(feature = "no-alloc"))]
         strptrtests();
-        #[cfg(feature = "pub-tstr")]
+        #[cfg(feature = "sync-shared-str")]
+        #[cfg(not(feature = "no-alloc"))]
+        syncstrtests();
+        #[cfg(feature = "compressed-str")]
+        compressedtests();
         consttests();
     } //testmain
 
+    fn patterntests() {
+        let s = str16::make("a,b;c,d");
+        assert_eq!(s.replace(',', "--"), (str16::make("a--b;c--d"), false));
+        assert_eq!(s.replacen([',', ';'], "", 2), (str16::make("abc,d"), false));
+        assert_eq!(s.replace("", "."), (str16::make(".a.,.b.;.c.,.d."), false));
+        assert_eq!(str8::make("").replace("", "x"), (str8::make("x"), false));
+        assert_eq!(s.replace(",", "0123456789"), (str16::make("a0123456789b;c0"), true));
+        let (k, v) = s.split_once(&[';'][..]).unwrap();
+        assert!(k == "a,b" && v == "c,d" && s.split_once('x').is_none());
+        assert_eq!(s.rsplit_once(','), Some((str16::make("a,b;c"), str16::make("d"))));
+        assert_eq!(s.strip_prefix("a,"), Some(str16::make("b;c,d")));
+        assert_eq!((s.strip_prefix("b"), s.strip_suffix("c")), (None, None));
+        assert_eq!(s.strip_suffix(','), None);
+        assert_eq!(s.strip_suffix(",d"), Some(str16::make("a,b;c")));
+
+        let mut z = zstr::<8>::make("aaaλ");
+        assert_eq!(z.replace_char('λ', 'l'), (zstr::make("aaal"), false));
+        assert_eq!(z.replace_char('a', 'λ'), (zstr::make("λλλ"), true));
+        z.remove_matches('λ');
+        assert_eq!(z, "aaa");
+        assert_eq!(z.split_once(""), Some((zstr::make(""), zstr::make("aaa"))));
+        let t = tstr::<8, Strict>::make("aaaa");
+        assert_eq!(t.replace('a', "bb"), (tstr::make("bbbbbb"), true));
+        let t = tstr::<8, Saturate>::make("aaaa");
+        assert_eq!(t.replace('a', "bb"), (tstr::make("bbbb…"), true));
+        #[cfg(feature = "std")]
+        #[cfg(not(feature = "no-alloc"))]
+        {
+            let f = fstr::<8>::make("x=1=2");
+            assert_eq!(f.rsplit_once('='), Some((fstr::make("x=1"), fstr::make("2"))));
+            assert_eq!(f.replacen('=', ":=", 1), (fstr::make("x:=1=2"), false));
+            let pat = alloc::string::String::from("=1");
+            assert_eq!(f.replace(&pat, ""), (fstr::make("x=2"), false));
+        }
+    } //patterntests
+
+    fn edittests() {
+        macro_rules! edit_check {
+            ($s:expr) => {{
+                let mut s = $s;
+                assert!(s == "aλc" && s.insert(1, 'x') && s == "axλc");
+                assert_eq!(s.insert_str(0, "12"), Ok(()));
+                assert_eq!((s.remove(4), s.remove(0)), ('λ', '1'));
+                assert_eq!(s, "2axc");
+                assert_eq!(s.drain(1..=2), "ax");
+                assert_eq!(s.replace_range(1.., "λλ"), Ok(()));
+                assert_eq!(s, "2λλ");
+                s.retain(|c| c != 'λ');
+                assert_eq!(s.drain(..), "2");
+                assert!(s.is_empty() && s.insert(0, 'λ') && s.insert(2, 'b') && s.insert(0, 'a'));
+                assert_eq!(s, "aλb");
+                let cap = s.capacity();
+                while s.len() < cap {
+                    assert!(s.insert(1, '-'));
+                }
+                assert_eq!((s.nth(0), s.nth(1), s.nth(cap - 3)), (Some('a'), Some('-'), Some('λ')));
+                assert!(!s.insert(1, '+'));
+                assert_eq!(
+                    s.insert_str(0, "+"),
+                    Err(CapacityError::Overflow { required: cap + 1, available: cap })
+                );
+                assert_eq!(s.replace_range(..2, "λ"), Ok(()));
+                assert_eq!((s.len(), s.nth(0), s.nth(1)), (cap, Some('λ'), Some('-')));
+            }};
+        }
+        edit_check!(str16::make("aλc"));
+        edit_check!(zstr::<16>::make("aλc"));
+        assert_eq!(
+            zstr::<16>::make("ab").insert_str(1, "\0"),
+            Err(CapacityError::InteriorNul { position: 0 })
+        );
+        #[cfg(feature = "std")]
+        #[cfg(not(feature = "no-alloc"))]
+        edit_check!(fstr::<16>::make("aλc"));
+        #[cfg(feature = "circular-str")]
+        for k in 0..8 {
+            // start the string at every position of the ring
+            let mut c = cstr::<15>::make(&"--------"[..k]);
+            c.truncate_left(k);
+            c.push_str("aλc");
+            edit_check!(c);
+            let mut c = cstr::<8>::make(&"--------"[..k]);
+            c.truncate_left(k);
+            c.push_str("abcdefgh");
+            assert_eq!(c.remove(1), 'b');
+            assert_eq!(c.drain(4..6), "fg");
+            assert_eq!(c, "acdeh");
+            assert_eq!(c.remove(0), 'a');
+            assert!(c.insert(3, 'λ') && c.insert(0, 'a') && c.insert(1, 'b'));
+            assert_eq!((c, c.len()), (cstr::make("abcdeλh"), 8));
+        }
+        macro_rules! replace_nth_check {
+            ($s:expr) => {{
+                let mut s = $s;
+                assert!(s.replace_nth(0, 'λ') && s == "λλc");
+                assert!(s.replace_nth(1, 'b') && s == "λbc");
+                assert!(s.replace_at(3, '€') && s == "λb€");
+                assert!(!s.replace_at(1, 'x') && !s.replace_at(6, 'x') && !s.replace_nth(3, 'x'));
+                assert!(!s.replace_nth(1, '€') && s == "λb€");
+                assert!(s.replace_nth(2, 'c') && s == "λbc");
+            }};
+        }
+        replace_nth_check!(str8::make("aλc"));
+        replace_nth_check!(zstr::<8>::make("aλc"));
+        #[cfg(feature = "std")]
+        #[cfg(not(feature = "no-alloc"))]
+        replace_nth_check!(fstr::<7>::make("aλc"));
+        #[cfg(feature = "circular-str")]
+        {
+            let mut c = cstr::<7>::make("-----");
+            c.truncate_left(5);
+            c.push_str("aλc");
+            replace_nth_check!(c);
+        }
+        #[cfg(feature = "std")]
+        #[cfg(not(feature = "no-alloc"))]
+        {
+            extern crate std;
+            let mut s = str8::make("aλc");
+            assert!(std::panic::catch_unwind(move || s.insert(2, 'x')).is_err());
+            assert!(std::panic::catch_unwind(move || s.remove(3)).is_ok());
+            assert!(std::panic::catch_unwind(move || s.drain(..5)).is_err());
+        }
+    } //edittests
+
+    fn cmptests() {
+        use core::cmp::Ordering::{Equal, Greater, Less};
+        // checks the comparisons of a and b in both directions
+        macro_rules! cmp_check {
+            ($a:expr, $b:expr, $ord:expr) => {{
+                let (a, b, ord) = (&$a, &$b, $ord);
+                assert_eq!((a.partial_cmp(b), b.partial_cmp(a)), (Some(ord), Some(ord.reverse())));
+                assert_eq!((a.eq(b), b.eq(a)), (ord.is_eq(), ord.is_eq()));
+            }};
+        }
+        // type inference still works with resize, as strings of the same
+        // type but different capacities are not comparable
+        let (s8, s16) = (str8::make("abc"), str16::make("abc"));
+        assert_eq!(s8, s16.resize());
+        assert_eq!(s16.resize(), s8);
+        assert_eq!(zstr::<8>::make("abc"), zstr::<16>::make("abc").resize());
+
+        let t = str16::make("abλ");
+        let z = zstr::<16>::make("abλ");
+        let lo = zstr::<4>::make("ab");
+        cmp_check!(t, z, Equal);
+        cmp_check!(lo, t, Less);
+        cmp_check!(t, *"abλ", Equal);
+        cmp_check!(z, *"abz", Greater);
+        cmp_check!(t, "ac", Less);
+        cmp_check!(t, "ab", Greater);
+        assert_eq!(&t, &"abλ");
+        #[cfg(not(feature = "no-alloc"))]
+        {
+            use alloc::{borrow::Cow, boxed::Box, string::String};
+            cmp_check!(t, String::from("abλ"), Equal);
+            cmp_check!(z, Box::<str>::from("abc"), Greater);
+            cmp_check!(lo, Cow::Borrowed("abλ"), Less);
+            cmp_check!(t, Cow::<str>::Owned(String::from("abλ")), Equal);
+        }
+        #[cfg(feature = "std")]
+        #[cfg(not(feature = "no-alloc"))]
+        {
+            let f = fstr::<8>::make("abλ");
+            cmp_check!(f, t, Equal);
+            cmp_check!(z, f, Equal);
+            cmp_check!(lo, f, Less);
+        }
+        #[cfg(feature = "circular-str")]
+        {
+            let mut c = cstr::<4>::make("xyz");
+            c.truncate_left(3);
+            c.push_str("abλ"); // wraps around
+            assert!(!c.is_contiguous());
+            cmp_check!(c, t, Equal);
+            cmp_check!(z, c, Equal);
+            cmp_check!(c, lo, Greater);
+            cmp_check!(c, "b", Less);
+            cmp_check!(c, *"abλ", Equal);
+            cmp_check!("a", c, Less);
+        }
+        #[cfg(all(feature = "flex-str", feature = "shared-str"))]
+        #[cfg(not(feature = "no-alloc"))]
+        {
+            let fl = Flexstr::<4>::from("abλ"); // spills to the heap
+            let sh = Sharedstr::<8>::from("abλ");
+            let cw = CowSharedstr::<8>::from("abc");
+            cmp_check!(fl, z, Equal);
+            cmp_check!(fl, sh, Equal);
+            cmp_check!(sh, t, Equal);
+            cmp_check!(cw, sh, Less);
+            cmp_check!(fl, cw, Greater);
+        }
+        #[cfg(feature = "sync-shared-str")]
+        #[cfg(not(feature = "no-alloc"))]
+        cmp_check!(SyncSharedstr::<8>::from("abλ"), z, Equal);
+        #[cfg(feature = "compressed-str")]
+        {
+            let r = r40str12::make("AB-1");
+            let b = b6str10::make("AB1");
+            cmp_check!(r, str8::make("AB-1"), Equal);
+            cmp_check!(zstr::<8>::make("AB1"), b, Equal);
+            cmp_check!(r, b, Less);
+            cmp_check!(r, *"AB-2", Less);
+        }
+    } //cmptests
+
+    fn hashtests() {
+        use core::hash::{Hash, Hasher};
+        // mixes each write as a unit, so that a string hashed in two
+        // pieces does not hash the same as it does in one
+        #[derive(Default)]
+        struct WriteMixer(u64);
+        impl Hasher for WriteMixer {
+            fn write(&mut self, bytes: &[u8]) {
+                let h = bytes
+                    .iter()
+                    .fold(bytes.len() as u64, |h, b| h.wrapping_mul(31) ^ *b as u64);
+                self.0 = self.0.rotate_left(17) ^ h;
+            }
+            fn finish(&self) -> u64 {
+                self.0
+            }
+        }
+        fn hash_of<T: Hash + ?Sized>(x: &T) -> u64 {
+            let mut h = WriteMixer::default();
+            x.hash(&mut h);
+            h.finish()
+        }
+        let h = hash_of("abλ");
+        assert_ne!(h, hash_of("ab"));
+        assert_eq!(hash_of(&str16::make("abλ")), h);
+        assert_eq!(hash_of(&zstr::<8>::make("abλ")), h);
+        #[cfg(feature = "std")]
+        #[cfg(not(feature = "no-alloc"))]
+        assert_eq!(hash_of(&fstr::<8>::make("abλ")), h);
+        #[cfg(feature = "circular-str")]
+        {
+            let mut c = cstr::<4>::make("xyz");
+            c.truncate_left(3);
+            c.push_str("abλ"); // wraps around
+            assert!(!c.is_contiguous());
+            assert_eq!(hash_of(&c), h);
+            assert_eq!(hash_of(&cstr::<8>::make("abλ")), h);
+        }
+        #[cfg(all(feature = "flex-str", feature = "shared-str"))]
+        #[cfg(not(feature = "no-alloc"))]
+        {
+            assert_eq!(hash_of(&Flexstr::<4>::from("abλ")), h);
+            assert_eq!(hash_of(&Sharedstr::<8>::from("abλ")), h);
+            assert_eq!(hash_of(&CowSharedstr::<8>::from("abλ")), h);
+        }
+        #[cfg(feature = "sync-shared-str")]
+        #[cfg(not(feature = "no-alloc"))]
+        assert_eq!(hash_of(&SyncSharedstr::<8>::from("abλ")), h);
+
+        // lookups by &str through Borrow<str>
+        #[cfg(not(feature = "no-alloc"))]
+        {
+            let mut m = alloc::collections::BTreeMap::new();
+            m.insert(str16::make("abc"), 1);
+            m.insert(str16::make("xyz"), 2);
+            assert_eq!((m.get("abc"), m.get("ab")), (Some(&1), None));
+        }
+        #[cfg(feature = "std")]
+        #[cfg(not(feature = "no-alloc"))]
+        {
+            extern crate std;
+            use std::collections::{HashMap, HashSet};
+            let mut m = HashMap::new();
+            m.insert(zstr::<8>::make("abλ"), 1);
+            m.insert(zstr::<8>::make("xyz"), 2);
+            assert_eq!((m.get("abλ"), m.get("xy")), (Some(&1), None));
+            let set: HashSet<fstr<8>> = ["abc", "xyz"].iter().map(|s| fstr::make(s)).collect();
+            assert!(set.contains("xyz"));
+        }
+        #[cfg(feature = "equivalent")]
+        {
+            use equivalent::Equivalent;
+            #[cfg(feature = "circular-str")]
+            {
+                let mut c = cstr::<4>::make("xyz");
+                c.truncate_left(3);
+                c.push_str("abλ");
+                assert!("abλ".equivalent(&c) && !"ab".equivalent(&c));
+            }
+            #[cfg(feature = "shared-str")]
+            #[cfg(not(feature = "no-alloc"))]
+            assert!("abc".equivalent(&Sharedstr::<8>::from("abc")));
+            #[cfg(feature = "sync-shared-str")]
+            #[cfg(not(feature = "no-alloc"))]
+            assert!(!"abc".equivalent(&SyncSharedstr::<8>::from("abd")));
+        }
+    } //hashtests
+
+    fn collecttests() {
+        let s: str8 = "abcλdef".chars().collect();
+        assert_eq!(s, "abcλde");
+        let s: str8 = "abcdefλ".chars().collect(); // stops at the λ that does not fit
+        assert_eq!(s, "abcdef");
+        let z: zstr<8> = ["ab", "cd", "efgh", "i"].iter().copied().collect();
+        assert_eq!(z, "abcdefg");
+        let mut t = str8::make("a");
+        t.extend(['b', 'c'].iter());
+        t.extend(["λ", "d"]);
+        assert_eq!(t, "abcλd");
+        assert_eq!(
+            t.try_extend(["e", "fg"]),
+            Err(CapacityError::Overflow { required: 9, available: 7 })
+        );
+        assert_eq!(t.try_extend("e".chars()), Ok(()));
+        assert_eq!(t, "abcλde");
+        assert_eq!(str4::try_from_iter("abc".chars()), Ok(str4::make("abc")));
+        assert_eq!(
+            str4::try_from_iter(["ab", "cd", "efg"]),
+            Err(CapacityError::Overflow { required: 7, available: 3 })
+        );
+        let mut z = zstr::<8>::make("ab");
+        assert_eq!(z.try_extend(["c", "d\0e"]), Err(CapacityError::InteriorNul { position: 4 }));
+        assert_eq!(z, "ab");
+        #[cfg(not(feature = "no-alloc"))]
+        {
+            use alloc::string::{String, ToString};
+            let words = [String::from("ab"), "cd".to_string()];
+            let z: zstr<8> = words.into_iter().collect();
+            assert_eq!(z, "abcd");
+        }
+        #[cfg(feature = "std")]
+        #[cfg(not(feature = "no-alloc"))]
+        {
+            let f: fstr<4> = "abcdef".chars().collect();
+            assert_eq!(f, "abcd");
+            assert!(fstr::<4>::try_from_iter(["abcd", ""]).is_ok());
+        }
+        #[cfg(feature = "circular-str")]
+        {
+            let mut c = cstr::<4>::make("xyz");
+            c.truncate_left(2);
+            c.extend("abcλ".chars()); // wraps around
+            assert_eq!(c.to_strs(), ("za", "bc"));
+            let mut c = cstr::<6>::make("cd");
+            c.extend_front(["b", "a", "λλ"]);
+            assert_eq!(c, "λabcd");
+            assert_eq!(
+                c.try_extend(['x']),
+                Err(CapacityError::Overflow { required: 7, available: 6 })
+            );
+            assert_eq!(c.try_extend([""]), Ok(()));
+            assert_eq!(cstr::<6>::try_from_iter(["λ", "abcd"]), Ok(cstr::make("λabcd")));
+        }
+        #[cfg(all(feature = "flex-str", feature = "shared-str"))]
+        #[cfg(not(feature = "no-alloc"))]
+        {
+            let mut f: Flexstr<4> = "ab".chars().collect();
+            assert!(f.is_fixed());
+            f.extend(["cd", "ef"]); // spills to the heap
+            assert_eq!(f, "abcdef");
+            let sh: Sharedstr<4> = ["abλ", "cd"].into_iter().collect();
+            let mut sh2 = sh.clone();
+            sh2.extend("ef".chars());
+            assert_eq!(sh, "abλcdef");
+        }
+    } //collecttests
+
+    fn convtests() {
+        fn overflow<T>(required: usize, available: usize) -> Result<T, CapacityError> {
+            Err(CapacityError::Overflow { required, available })
+        }
+        assert_eq!(str8::try_from("abcλ"), Ok(str8::make("abcλ")));
+        assert_eq!(str4::try_from("abλ"), overflow(4, 3));
+        assert_eq!(zstr::<4>::try_from('λ').map(|z| z.len()), Ok(2));
+        assert_eq!(tstr::<2>::try_from('λ'), overflow(2, 1));
+        assert_eq!(zstr::<8>::try_from("a\0b"), Err(CapacityError::InteriorNul { position: 1 }));
+
+        let t = str16::make("abcdefgh");
+        assert_eq!(str8::try_from(&t), overflow(8, 7));
+        assert_eq!(tstr::<9>::try_from(&t), Ok(tstr::make("abcdefgh")));
+        assert_eq!(zstr::<16>::try_from(t), Ok(zstr::make("abcdefgh")));
+        assert_eq!(zstr::<8>::try_from(&t), overflow(8, 7));
+        let z = zstr::<8>::make("abc");
+        assert_eq!(str4::try_from(z), Ok(str4::make("abc")));
+        let grown: zstr<16> = z.grow();
+        assert_eq!((grown, grown.capacity()), (z.resize(), 15));
+        assert_eq!(str8::make("abc").grow::<8>(), "abc");
+        #[cfg(not(feature = "no-alloc"))]
+        {
+            use alloc::{borrow::Cow, boxed::Box, string::String};
+            assert_eq!(str4::try_from(String::from("abc")), Ok(str4::make("abc")));
+            assert_eq!(zstr::<4>::try_from(Box::<str>::from("abcd")), overflow(4, 3));
+            assert_eq!(str8::try_from(Cow::Borrowed("abλ")), Ok(str8::make("abλ")));
+        }
+        #[cfg(feature = "std")]
+        #[cfg(not(feature = "no-alloc"))]
+        {
+            let f = fstr::<8>::make("abcdefgh");
+            assert_eq!(fstr::<4>::try_from(&f), overflow(8, 4));
+            assert_eq!(str16::try_from(f), Ok(t));
+            assert_eq!(fstr::<8>::try_from(t), Ok(f));
+            assert_eq!(f.grow::<12>().capacity(), 12);
+        }
+        #[cfg(feature = "circular-str")]
+        {
+            let mut c = cstr::<4>::make("xyz");
+            c.truncate_left(2);
+            c.push_str("abc"); // wraps around
+            assert_eq!(str8::try_from(c), Ok(str8::make("zabc")));
+            assert_eq!(zstr::<4>::try_from(&c), overflow(4, 3));
+            assert_eq!(cstr::<4>::try_from(z), Ok(cstr::make("abc")));
+            assert_eq!(cstr::<3>::try_from(&c), overflow(4, 3));
+            let g: cstr<8> = c.grow();
+            assert_eq!(g.to_strs(), ("zabc", ""));
+        }
+        #[cfg(all(feature = "flex-str", feature = "shared-str"))]
+        #[cfg(not(feature = "no-alloc"))]
+        {
+            let fl = Flexstr::<4>::from("abcdef");
+            assert_eq!(str8::try_from(&fl), Ok(str8::make("abcdef")));
+            assert_eq!(zstr::<4>::try_from(&Sharedstr::<8>::from("abcd")), overflow(4, 3));
+        }
+        #[cfg(feature = "compressed-str")]
+        assert_eq!(str8::try_from(&r40str12::make("AB-1")), Ok(str8::make("AB-1")));
+    } //convtests
+
+    fn mstrtests() {
+        assert_eq!(core::mem::size_of::<mstr4k>(), 4096);
+        let line: mstr1k = core::iter::repeat_n('λ', 300).collect();
+        assert_eq!((line.len(), line.capacity()), (600, 1022));
+        assert!(line.chars().all(|c| c == 'λ'));
+        let mut m = mstr::<300>::make(&line);
+        assert_eq!((m.len(), m.charlen()), (298, 149));
+        assert_eq!(m.pop_char(), Some('λ'));
+        m.truncate(2);
+        assert_eq!(m, "λλ");
+        m.clear();
+        assert_eq!(m.push_str("abc"), "");
+        assert!(m.insert(1, 'λ') && m.replace_nth(0, 'A'));
+        assert_eq!((m.as_str(), m.len()), ("Aλbc", 5));
+        assert_eq!(m.drain(1..3), "λ");
+        assert_eq!(m.to_ascii_upper(), "ABC");
+        assert_eq!(m.substr(1, 5), "bc");
+        assert!(m.case_insensitive_eq("abc") && m > mstr::make("Ab"));
+
+        let ab = mstr512::make("a").concat::<1540>(&line);
+        assert_eq!(ab.len(), 601);
+        let big = line + line;
+        assert_eq!((big.len(), big.capacity()), (1200, 2046));
+        assert_eq!(
+            mstr::<600>::try_from(&line),
+            Err(CapacityError::Overflow { required: 600, available: 598 })
+        );
+        assert_eq!(str256::try_from(&mstr512::make("abc")), Ok(str256::make("abc")));
+        assert_eq!(mstr512::try_from(str8::make("abc")), Ok(mstr512::make("abc")));
+        assert_eq!(mstr512::try_from(zstr::<8>::make("abc")), Ok(mstr512::make("abc")));
+        assert!(str256::try_from(big).is_err());
+        assert_eq!(mstr1k::make("xyz"), str8::make("xyz"));
+        assert_eq!(mstr64k::new().capacity(), 65534);
+        assert_eq!(mstr::<65537>::new().capacity(), u16::MAX as usize);
+
+        let mut s = mstr::<8>::make("abc");
+        s.set_byte_char(0, 'A');
+        s.set_byte_char(3, 'd');
+        s.set_byte_char(7, 'e');
+        assert_eq!((s.len(), s.as_str()), (3, "Abc"));
+        let mut u = mstr::<8>::make("aλb");
+        (0..4).for_each(|i| u.set_byte_char(i, 'x'));
+        assert_eq!(u, "xλx");
+        #[cfg(feature = "std")]
+        #[cfg(not(feature = "no-alloc"))]
+        {
+            extern crate std;
+            assert!(std::panic::catch_unwind(move || s.set_byte_char(0, 'λ')).is_err());
+            assert_eq!(s, "Abc");
+        }
+    } //mstrtests
+
+    fn nichetests() {
+        use core::mem::size_of;
+        assert_eq!(size_of::<Option<str4>>(), 4);
+        assert_eq!(size_of::<Option<str8>>(), 8);
+        assert_eq!(size_of::<Option<tstr<1>>>(), 1);
+        assert_eq!(size_of::<Option<str256>>(), 256);
+        assert_eq!(size_of::<Option<tstr<8, Strict>>>(), 8);
+        assert_eq!(size_of::<Option<zstr<1>>>(), 1);
+        assert_eq!(size_of::<Option<zstr<8>>>(), 8);
+        assert_eq!(size_of::<Option<zstr<300>>>(), 300);
+        assert_eq!(size_of::<Option<(u8, str8)>>(), 9);
+        assert_eq!(Some(str8::make("abc")), Some(str8::make("abc")));
+        assert_eq!(zstr::<8>::from_raw(b"ab\xFFc"), "ab");
+
+        // a full str256 and one of length 254 share the same length byte
+        let lambdas: mstr512 = core::iter::repeat_n('λ', 127).collect();
+        let long = [core::str::from_utf8(&[b'a'; 255]).unwrap(), &lambdas];
+        let mut s = str256::make(long[0]);
+        assert_eq!((s.len(), s.capacity()), (255, 255));
+        assert_eq!(s.pop_char(), Some('a'));
+        assert_eq!(s.len(), 254);
+        assert!(s.push_char('b') && s.len() == 255 && s.ends_with("ab"));
+        assert!(!s.push_char('c'));
+        s.truncate_bytes(254);
+        assert_eq!(s, long[0][..254]);
+        assert_eq!(s.replace_range(0..1, "λ"), Ok(()));
+        assert_eq!((s.len(), s.nth(0)), (255, Some('λ')));
+        assert_eq!(s.drain(..2), "λ");
+        assert_eq!(s.len(), 253);
+        let mut t = str256::make(long[1]);
+        assert_eq!(t.len(), 254);
+        assert_eq!(t.push_str("xy"), "y");
+        assert_eq!(t.len(), 255);
+        assert_eq!(t.const_to_ascii_upper().len(), 255);
+        assert_eq!(str256::const_make(long[0]), long[0]);
+        assert_eq!(str256::const_make(long[1]).len(), 254);
+        assert_eq!(str256::make(long[0]).substr(1, 255).len(), 254);
+        assert_eq!(str256::make(long[0]).const_trim().len(), 255);
+        assert_eq!(Some(t).map(|t| t.len()), Some(255));
+
+        // set_byte_char past the end must not disturb the length marker
+        let mut s = str256::make(long[0]);
+        s.truncate_bytes(254);
+        s.set_byte_char(254, 'b');
+        assert_eq!((s.len(), s.to_str()), (254, &long[0][..254]));
+        s.set_byte_char(253, 'b');
+        assert!(s.len() == 254 && s.ends_with("ab"));
+        assert_eq!(s.push("cd"), "d");
+        s.set_byte_char(254, 'e');
+        assert!(s.len() == 255 && s.ends_with("abe"));
+        s.set_byte_char(255, 'f');
+        assert!(s.len() == 255 && s.ends_with("abe"));
+        s.truncate(254);
+        s.set_byte_char(254, 'g');
+        assert!(s.len() == 254 && s.ends_with("ab"));
+        assert_eq!(s.push("λ"), "λ");
+        s.truncate(253);
+        assert_eq!(s.push("λ"), "");
+        assert!(s.len() == 255 && s.ends_with("aλ"));
+
+        // no safe mutator can store the niche 0xFF anywhere in the array
+        fn no_niche<T>(t: &T) -> bool {
+            let p = t as *const T as *const u8;
+            (0..size_of::<T>()).all(|i| unsafe { *p.add(i) } != 0xFF)
+        }
+        macro_rules! niche_check {
+            ($t:ty) => {{
+                let mut s = <$t>::make("ÿ߿"); // ends in 0xBF bytes
+                assert!(no_niche(&s));
+                s.push("\u{10FFFF}\u{FFFF}");
+                assert!(s.push_char('a') && no_niche(&s));
+                assert!(s.set(0, '߿') && s.set(3, '\u{BFFF}') && no_niche(&s));
+                s.set_byte_char(s.len() - 1, 'b');
+                s.set_byte_char(s.len(), 'c');
+                assert!(no_niche(&s));
+                s.as_mut().make_ascii_uppercase();
+                assert!(s.replace_nth(0, 'ÿ') && no_niche(&s));
+                assert!(s.insert(0, 'ÿ'));
+                s.retain(|c| c != '\u{BFFF}');
+                assert!(no_niche(&s));
+                assert_eq!(s.remove(0), 'ÿ');
+                s.drain(..2);
+                s.truncate(3);
+                s.pop_char();
+                assert!(no_niche(&s));
+                s.clear();
+                s.extend(["ÿ", "a", "߿"]);
+                assert!(no_niche(&s) && no_niche(&Some(s)));
+                s
+            }};
+        }
+        assert_eq!(niche_check!(str16).len(), 5);
+        assert_eq!(niche_check!(zstr<16>).len(), 5);
+        let mut s = str256::make(long[1]);
+        s.push("ÿ");
+        s.set_byte_char(254, 'z');
+        assert!(no_niche(&s) && s.len() == 254);
+
+        // set_byte_char never overwrites part of a multibyte character
+        let mut t = str8::make("aλb");
+        let mut z = zstr::<8>::make("aλb");
+        for i in 0..5 {
+            t.set_byte_char(i, 'x');
+            z.set_byte_char(i, 'x');
+        }
+        assert_eq!((t.as_str(), z.as_str()), ("xλx", "xλx"));
+        #[cfg(feature = "std")]
+        #[cfg(not(feature = "no-alloc"))]
+        {
+            extern crate std;
+            assert!(std::panic::catch_unwind(move || z.set_byte_char(0, '\0')).is_err());
+            assert_eq!((z.len(), z.as_str()), (4, "xλx"));
+        }
+    } //nichetests
+
+    #[allow(clippy::op_ref)] // exercises the by-reference Add impls
+    fn concattests() {
+        let (a, b) = (zstr::<8>::make("abcdefg"), zstr::<8>::make("hijklmn"));
+        let ab = a + b;
+        assert_eq!((ab, ab.capacity()), (zstr::<16>::make("abcdefghijklmn"), 15));
+        let ab2 = a + &b + "xyz"; // same-size concatenation still truncates
+        assert_eq!(ab2, "abcdefg");
+        let c = a.concat::<12>(&str4::make("xyz"));
+        assert_eq!(c, "abcdefgxyz");
+        let s = str_concat!(zstr<32>; a, str8::make("-"), b);
+        assert_eq!(s, "abcdefg-hijklmn");
+        let t = str_concat!(str8; str4::make("ab"), str4::make("cd"));
+        assert_eq!(t, "abcd");
+        let strict = tstr::<4, Strict>::make("abc") + tstr::<4, Strict>::make("def");
+        assert_eq!(strict, "abcdef");
+        #[cfg(feature = "std")]
+        #[cfg(not(feature = "no-alloc"))]
+        {
+            let f = fstr::<4>::make("abcd") + fstr::<4>::make("efgh");
+            assert_eq!((f.len(), f.capacity()), (8, 8));
+            assert_eq!(f.concat::<16>(&a.concat::<8>(&zstr::<1>::new())), "abcdefghabcdefg");
+        }
+        #[cfg(feature = "circular-str")]
+        {
+            let mut x = cstr::<8>::make("--------");
+            x.truncate_left(6);
+            x.push_str("abcdef"); // wraps around underneath
+            let y = cstr::<8>::make("123");
+            let xy = x + y;
+            assert_eq!((xy.capacity(), xy.to_strs()), (16, ("--abcdef123", "")));
+            assert_eq!(y.concat::<16>(&x), "123--abcdef");
+            assert_eq!(str_concat!(zstr<20>; y, x), "123--abcdef");
+            assert_eq!(&y + &y, "123123");
+        }
+    } //concattests
+
+    fn splittests() {
+        const INPUTS: [&str; 9] = [
+            "", "a,b", ",λ,,c, d,", "ab, cd\r\n\nλ \n", "  x  y\t", "a, b, ", "\n", "a\r",
+            "x\r\r\ny\r",
+        ];
+        for input in INPUTS {
+            let s = str32::make(input);
+            assert!(s.split_into::<str32>(',').eq(input.split(',').map(str32::make)));
+            assert!(s.split_into::<str32>(", ").eq(input.split(", ").map(str32::make)));
+            assert!(s.split_into::<str32>("").eq(input.split("").map(str32::make)));
+            assert!(s.splitn_into::<str32>(2, ',').eq(input.splitn(2, ',').map(str32::make)));
+            assert!(s
+                .split_whitespace_into::<str32>()
+                .eq(input.split_whitespace().map(str32::make)));
+            assert!(s.lines_into::<str32>().eq(input.lines().map(str32::make)));
+            let z = zstr::<32>::make(input);
+            assert!(z
+                .split_into::<zstr<32>>([',', ' '])
+                .eq(input.split([',', ' ']).map(zstr::make)));
+            #[cfg(feature = "circular-str")]
+            for k in (0..input.len()).filter(|&k| input.is_char_boundary(k)) {
+                // the string wraps around the end of its array after byte k
+                let mut c = cstr::<24>::make(&input[k..]);
+                c.push_front(&input[..k]);
+                assert!(c == input && (k == 0 || !c.is_contiguous()));
+                assert!(c.split_into::<str32>(',').eq(input.split(',').map(str32::make)));
+                assert!(c.split_into::<str32>(", ").eq(input.split(", ").map(str32::make)));
+                assert!(c.split_into::<str32>("").eq(input.split("").map(str32::make)));
+                assert!(c.splitn_into::<str32>(3, ',').eq(input.splitn(3, ',').map(str32::make)));
+                assert!(c
+                    .split_whitespace_into::<str8>()
+                    .eq(input.split_whitespace().map(str8::make)));
+                assert!(c.lines_into::<str32>().eq(input.lines().map(str32::make)));
+            }
+        }
+        let s = str16::make("1,22,333");
+        let (parts, n) = s.split_into::<str4>(',').collect_array::<3>().unwrap();
+        assert_eq!((n, parts), (3, [str4::make("1"), str4::make("22"), str4::make("333")]));
+        let (parts, n) = s.split_into::<str8>(",2").collect_array::<3>().unwrap();
+        assert_eq!((n, parts), (2, [str8::make("1"), str8::make("2,333"), str8::new()]));
+        assert_eq!(
+            s.split_into::<str4>(',').collect_array::<2>(),
+            Err(CapacityError::Overflow { required: 3, available: 2 })
+        );
+        assert_eq!(
+            s.split_into::<str4>("2").collect_array::<4>(),
+            Err(CapacityError::Overflow { required: 4, available: 3 })
+        );
+        let mut it = s.splitn_into::<str4>(2, ',');
+        let overflow = CapacityError::Overflow { required: 6, available: 3 };
+        assert_eq!(
+            (it.next(), it.try_next()),
+            (Some(str4::make("1")), Some(Err(overflow)))
+        );
+        assert_eq!(it.next(), None);
+        let numeric = char::is_numeric as fn(char) -> bool;
+        assert_eq!(s.split_into::<str4>(numeric).filter(|p| p.is_empty()).count(), 5);
+        #[cfg(feature = "circular-str")]
+        {
+            let mut c = cstr::<8>::make("5678");
+            c.push_front("1234");
+            assert_eq!(
+                c.split_into::<str8>("45").collect_array::<2>().unwrap().0,
+                [str8::make("123"), str8::make("678")]
+            );
+            let mut c = cstr::<8>::make("ab\r");
+            c.push_front("12345");
+            assert!(c.lines_into::<str8>().eq([str8::make("12345ab")]));
+        }
+    } //splittests
+
+    #[cfg(feature = "compressed-str")]
+    fn compressedtests() {
+        const KEYS: [&str; 6] = ["", "0", "A", "A0", "AB", "Z_Z"];
+        for (i, a) in KEYS.iter().enumerate() {
+            for b in &KEYS[i..] {
+                let (pa, pb) = (r40str12::make(a), r40str24::make(a));
+                assert!(pa == *a && pb == *a && pa.len() == a.len());
+                assert_eq!(pa.cmp(&r40str12::make(b)), a.cmp(b));
+                assert_eq!(pb.cmp(&r40str24::make(b)), a.cmp(b));
+                assert_eq!(b6str10::make(a).cmp(&b6str10::make(b)), a.cmp(b));
+            }
+        }
+        assert!(r40str12::make("-") < r40str12::make(".0"));
+        assert!(b6str10::make("_") < b6str10::make("a"));
+        let full = r40str12::make("ABCDEFGHIJKLMNOP");
+        assert_eq!(full, "ABCDEFGHIJKL");
+        assert_eq!(r40str12::from_bits(full.to_bits()), Some(full));
+        assert_eq!(r40str12::from_bits(u64::MAX), None);
+        assert_eq!(r40str12::from_bits(1), None); // padding before a character
+        assert_eq!(
+            r40str12::try_make("ABCDEFGHIJKLM"),
+            Err(CapacityError::Overflow { required: 13, available: 12 })
+        );
+        assert_eq!(r40str12::try_make("AB+"), Err(CapacityError::Unencodable { position: 2 }));
+        assert_eq!(b6str21::try_make("ab.c"), Err(CapacityError::Unencodable { position: 2 }));
+        assert_eq!(r40str12::make("AB+C"), "AB");
+        let mut p = b6str21::make("snake_case_01");
+        assert_eq!(p.pop_char(), Some('1'));
+        p.truncate(5);
+        assert_eq!((p, p.nth(4), p.nth(5)), (b6str21::make("snake"), Some('e'), None));
+        assert!(!p.push_char('λ') && p.push_char('Z') && p == "snakeZ");
+        let z: zstr<32> = p.into();
+        let t: tstr<32> = p.into();
+        assert_eq!((b6str21::try_from(z), b6str21::try_from(t)), (Ok(p), Ok(p)));
+        assert_eq!(
+            b6str10::try_from(str8::make("aλ")),
+            Err(CapacityError::Unencodable { position: 1 })
+        );
+        let mut buf = zstr::<32>::new();
+        core::fmt::write(&mut buf, format_args!("[{:>8}]", p)).unwrap();
+        assert_eq!(buf, "[  snakeZ]");
+        assert_eq!("X.Y".parse::<r40str24>().unwrap().to_tstr(), "X.Y");
+        assert_eq!(core::mem::size_of::<b6str21>(), 16);
+    } //compressedtests
+
     #[cfg(feature = "std")]
     #[cfg(feature = "shared-str")]
     #[cfg(not(feature = "no-alloc"))]
@@ -623,8 +2249,526 @@ mod tests {
         b.push('3');
         assert!(a == "abc123");
         assert!("abc123" == b);
+        let guard = a.borrow();
+        assert!(&*guard == "abc123" && b.try_borrow().is_ok());
+        let mut b2 = b.clone();
+        let push = std::panic::AssertUnwindSafe(move || b2.push('4'));
+        let rejected = std::panic::catch_unwind(push).is_err();
+        assert!(rejected && &*guard == "abc123");
+        let mut b3 = b.clone();
+        assert!(b3.try_push_str("4").is_err() && b3.try_push('4').is_err());
+        assert!(b3.try_set(0, 'A').is_err() && b3.try_truncate(1).is_err());
+        assert_eq!(&*guard, "abc123");
+        drop(guard);
+        b.push('4');
+        let mut d = Sharedstr::<8>::from("abc");
+        assert_eq!(
+            (d.try_set(0, 'A').ok(), d.try_push_str("λλλ").ok()),
+            (Some(true), Some(false))
+        );
+        assert_eq!((d.try_truncate(2).ok(), d.try_push('c').ok()), (Some(true), Some(true)));
+        assert!(d == "Abc" && d.is_fixed());
+        let c2 = c.clone();
+        c.if_fixed(|s| {
+            assert!(c2.try_borrow().is_err());
+            s.push("!");
+        });
+        assert!(c2 == "abc12!" && b.nth(6) == Some('4'));
+
+        let mut a = CowSharedstr::<8>::from("abc12");
+        let mut b = a.clone();
+        assert!(a.ptr_eq(&b) && a.ptr_count() == 2);
+        assert!(!b.set(0, 'λ') && a.ptr_eq(&b)); // failed set does not copy
+        assert!(b.set(0, 'A') && !a.ptr_eq(&b) && a == "abc12" && b == "Abc12");
+        assert!(b.substr(1, 3) == "bc" && b.substr(2, 9).is_empty() && b.substr(3, 1).is_empty());
+        let mut o = CowSharedstr::<4>::from("aλbcd");
+        assert!(o.substr(1, 2).is_empty() && o.set(1, 'μ') && o == "aμbcd");
+        let mut c = a.clone();
+        assert!(!c.push_str("3456") && c == "abc123456" && a.is_fixed());
+        let mut d = c.clone();
+        d.make_unique();
+        assert!(d.is_unique() && c.is_unique() && d == c && !d.ptr_eq(&c));
+        assert!(c.truncate(3) && d == "abc123456" && &c[..] == "abc");
+        let e = d.clone();
+        d.clear();
+        assert!(d.is_empty() && e.len() == 9 && e.is_unique());
+        assert_eq!(e.to_string(), "abc123456");
+
+        use std::collections::HashMap;
+        let names: std::vec::Vec<Sharedstr<8>> =
+            ["x", "y", "z"].iter().map(|n| Sharedstr::from(*n)).collect();
+        let mut table: HashMap<WeakSharedstr<8>, usize> =
+            names.iter().enumerate().map(|(i, n)| (n.downgrade(), i)).collect();
+        assert!(table[&names[1].downgrade()] == 1 && names[1].weak_count() == 1);
+        let z = names[2].downgrade();
+        drop(names);
+        assert!(z.upgrade().is_none() && z.weak_count() == 0 && z != WeakSharedstr::new());
+        table.retain(|k, _| k.upgrade().is_some());
+        assert!(table.is_empty());
     } //strptrtests
 
+    #[cfg(feature = "sync-shared-str")]
+    #[cfg(not(feature = "no-alloc"))]
+    fn syncstrtests() {
+        extern crate std;
+        use std::thread;
+        use std::vec::Vec;
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<SyncSharedstr<8>>();
+        let mut a = SyncSharedstr::<8>::from("abc12");
+        let b = a.clone();
+        assert!(a.ptr_eq(&b) && a.ptr_count() == 2 && a == b);
+        let c = a.deep_clone();
+        assert!(a == c && !a.ptr_eq(&c));
+        let workers: Vec<_> = (0..4)
+            .map(|i| {
+                let mut s = b.clone();
+                thread::spawn(move || {
+                    s.push_char(char::from(b'a' + i));
+                    s.len()
+                })
+            })
+            .collect();
+        for w in workers {
+            w.join().unwrap();
+        }
+        assert!(a.len() == 9 && a.is_owned() && a.ptr_count() == 2);
+        assert!(a.with_str(|s| s.starts_with("abc12") && s[5..].chars().all(|c| c < 'e')));
+        assert!(a.truncate(5) && a.is_fixed() && b == "abc12");
+        assert!(a.set(0, 'A') && !a.set(0, 'λ') && "Abc12" == b);
+        assert_eq!(a.pop(), Some('2'));
+        a.clear();
+        assert!(a.is_empty() && c == "abc12");
+        // comparisons release one lock before taking the other, so they
+        // cannot deadlock with writers on either string
+        let (x, y) = (SyncSharedstr::<4>::from("abcdef"), SyncSharedstr::<4>::from("abc"));
+        assert_eq!(
+            (x.cmp(&y), y.cmp(&x)),
+            (core::cmp::Ordering::Greater, core::cmp::Ordering::Less)
+        );
+        assert!(x != y && x == x.clone() && x.deep_clone() == x);
+        let workers: Vec<_> = (0..4)
+            .map(|i| {
+                let (mut x, mut y) = (x.clone(), y.clone());
+                thread::spawn(move || {
+                    for _ in 0..500 {
+                        let _ = (x == y, x.cmp(&y), y.cmp(&x));
+                        if i % 2 == 0 {
+                            x.push_str("g");
+                            x.truncate(6);
+                        } else {
+                            y.push_str("d");
+                            y.truncate(3);
+                        }
+                    }
+                })
+            })
+            .collect();
+        for w in workers {
+            w.join().unwrap();
+        }
+        assert!(x == "abcdef" && y == "abc" && x > y);
+        #[cfg(feature = "flex-str")]
+        {
+            let f = a.to_flexstr().unwrap_err();
+            drop(b);
+            assert!(f.to_flexstr().unwrap() == "");
+        }
+    } //syncstrtests
+
+    fn fixedstr_conformance<T: FixedstrMake>() {
+        let long = "0123456789abcdefghijklmnopqrstuvwxyz";
+        let mut s = T::new();
+        let cap = s.capacity();
+        assert!(s.is_empty() && s.eq_str("") && cap >= 3);
+        assert_eq!(s.push_str("ab"), "");
+        assert!(s.push_char('c'));
+        assert!(s.eq_str("abc") && !s.eq_str("ab") && s.is_ascii());
+        assert_eq!((s.len(), s.charlen(), s.nth(1), s.nth(3)), (3, 3, Some('b'), None));
+        assert_eq!(s.pop_char(), Some('c'));
+        s.truncate_bytes(5);
+        s.truncate_bytes(1);
+        assert!(s.eq_str("a"));
+        let rest = s.push_str(long);
+        assert_eq!(s.len() + rest.len(), 1 + long.len());
+        assert!(rest.is_empty() || s.len() == cap);
+        let mut buf = zstr::<64>::new();
+        s.write_to(&mut buf).unwrap();
+        assert!(buf.as_str().starts_with('a'));
+        assert_eq!(&buf.as_str()[1..], &long[..s.len() - 1]);
+        s.clear();
+        assert!(s.is_empty() && s.pop_char().is_none());
+        match T::try_make(long) {
+            Ok(t) => assert!(t.eq_str(long) && (long.len() <= cap || t.len() > cap)),
+            Err(e) => assert!(
+                e == CapacityError::Overflow { required: long.len(), available: cap }
+                    && long.len() > cap
+            ),
+        }
+        assert!(T::make("xy").eq_str("xy"));
+        let d: &mut dyn Fixedstr = &mut s;
+        assert!(d.push_char('z'));
+        assert!(d.eq_str("z"));
+    } //fixedstr_conformance
+
+    fn traittests() {
+        fixedstr_conformance::<zstr<4>>();
+        fixedstr_conformance::<zstr<64>>();
+        fixedstr_conformance::<str4>();
+        fixedstr_conformance::<str64>();
+        fixedstr_conformance::<mstr<5>>();
+        fixedstr_conformance::<mstr1k>();
+        #[cfg(feature = "std")]
+        #[cfg(not(feature = "no-alloc"))]
+        fixedstr_conformance::<fstr<5>>();
+        #[cfg(feature = "circular-str")]
+        fixedstr_conformance::<cstr<3>>();
+        #[cfg(feature = "circular-str")]
+        fixedstr_conformance::<cstr<40>>();
+        #[cfg(feature = "flex-str")]
+        #[cfg(not(feature = "no-alloc"))]
+        fixedstr_conformance::<Flexstr<4>>();
+        #[cfg(feature = "shared-str")]
+        #[cfg(not(feature = "no-alloc"))]
+        fixedstr_conformance::<Sharedstr<4>>();
+        #[cfg(feature = "shared-str")]
+        #[cfg(not(feature = "no-alloc"))]
+        fixedstr_conformance::<CowSharedstr<4>>();
+        #[cfg(feature = "sync-shared-str")]
+        #[cfg(not(feature = "no-alloc"))]
+        fixedstr_conformance::<SyncSharedstr<4>>();
+        #[cfg(feature = "compressed-str")]
+        fixedstr_conformance::<b6str10>();
+        #[cfg(feature = "compressed-str")]
+        fixedstr_conformance::<b6str21>();
+        let mut a = str8::make("abc");
+        let b = &mut a as &mut dyn Fixedstr;
+        assert_eq!(b.push_str("defghijk"), "hijk");
+        assert_eq!(a, "abcdefg");
+    } //traittests
+
+    const MULTIBYTE_INPUTS: [&str; 8] =
+        ["", "a", "λ", "aλ", "aλb€c😀d", "😀😀", "€€€", "abcdefghijklmnop"];
+
+    // every truncating path must stop at the last character boundary that fits
+    fn char_boundary_conformance<T>()
+    where
+        T: FixedstrMake + ContiguousFixedstr + core::fmt::Write,
+    {
+        use core::fmt::Write;
+        let cap = T::new().capacity();
+        for s in MULTIBYTE_INPUTS {
+            let expected = &s[..floor_char_boundary(s.as_bytes(), cap)];
+            assert_eq!(T::make(s).as_str(), expected);
+            for prefix in ["", "a", "λ", "ab"] {
+                let mut t = T::make(prefix);
+                let plen = t.len();
+                let rest = t.push_str(s);
+                assert!(t.len() <= cap);
+                assert_eq!(&t.as_str()[plen..], &s[..s.len() - rest.len()]);
+                assert!(rest.chars().next().is_none_or(|c| t.len() + c.len_utf8() > cap));
+                let mut w = T::make(prefix);
+                let res = write!(w, "{}", s);
+                assert_eq!(w.as_str(), t.as_str());
+                assert_eq!(res.is_ok(), rest.is_empty());
+            }
+        }
+    } //char_boundary_conformance
+
+    fn boundarytests() {
+        macro_rules! conform {
+            ($ty:ident; $($n:literal)*) => {
+                $( char_boundary_conformance::<$ty<$n>>(); )*
+            };
+        }
+        conform!(tstr; 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 256);
+        conform!(zstr; 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 256);
+        conform!(mstr; 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 300);
+        #[cfg(feature = "std")]
+        #[cfg(not(feature = "no-alloc"))]
+        conform!(fstr; 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 256);
+        for s in MULTIBYTE_INPUTS {
+            let (t, z) = (tstr::<16>::make(s), zstr::<16>::make(s));
+            assert_eq!(t.resize::<5>(), tstr::<5>::make(s));
+            assert_eq!(z.resize::<5>(), zstr::<5>::make(s));
+            assert_eq!(tstr::<8>::const_make(s), tstr::<8>::make(s));
+            assert_eq!(zstr::<8>::const_make(s), zstr::<8>::make(s));
+            #[cfg(feature = "std")]
+            #[cfg(not(feature = "no-alloc"))]
+            {
+                let f = fstr::<16>::make(s);
+                assert_eq!(f.resize::<4>(), fstr::<4>::make(s));
+                assert_eq!(fstr::<7>::const_create(s), fstr::<7>::make(s));
+            }
+        }
+        assert_eq!(str_format!(str8, "ab{}", "€€"), "ab€");
+        assert_eq!(str_format!(zstr<8>, "ab{}{}", "€", "λ"), "ab€λ");
+        assert!(try_format!(str8, "ab{}", "€€").is_none());
+        assert_eq!(str4::from("aλλ"), "aλ");
+        #[cfg(feature = "flex-str")]
+        #[cfg(not(feature = "no-alloc"))]
+        {
+            let mut fs = Flexstr::<4>::from("abλλ");
+            assert!(fs.is_owned());
+            assert_eq!(fs.split_off(), "λλ");
+            assert!(fs.is_fixed() && fs == "ab");
+        }
+        #[cfg(feature = "shared-str")]
+        #[cfg(not(feature = "no-alloc"))]
+        {
+            let mut ss = Sharedstr::<4>::from("abλλ");
+            assert_eq!(ss.split_off(), "λλ");
+            assert!(ss.is_fixed() && ss == "ab");
+        }
+        #[cfg(feature = "circular-str")]
+        cstr_unicode_tests();
+    } //boundarytests
+
+    // pushes and pops multibyte chars at both ends of a cstr, at every
+    // position of the wrap-around point
+    #[cfg(feature = "circular-str")]
+    fn cstr_unicode_tests() {
+        extern crate std;
+        assert_eq!(core::mem::size_of::<cstr<16>>(), 16 + 3 + 4 + 1);
+        let mut cb = cstr::<4>::make("abcd");
+        cb.reset();
+        cb.zero();
+        assert!(cb.push_char('λ') && cb.substr(0, 2) == "λ");
+        let chars = ['a', 'λ', '€', '😀'];
+        for offset in 0..8 {
+            for (i, &c) in chars.iter().enumerate() {
+                let mut cb = cstr::<8>::new();
+                for _ in 0..offset {
+                    cb.push_char('.');
+                }
+                cb.truncate_left(offset);
+                let d = chars[(i + 1) % 4];
+                assert!(cb.push_char(c) && cb.push_char_front(d));
+                let (a, b) = cb.to_strs();
+                assert_eq!(a.len() + b.len(), c.len_utf8() + d.len_utf8());
+                assert!(cb.chars().eq([d, c]) && cb.chars().rev().eq([c, d]));
+                assert_eq!(cb.nth(1), Some(c));
+                assert!(cb.set(1, c) && cb.set(0, 'x') == (d == 'a') && cb.set(0, d));
+                let rest = cb.push_str("€€€");
+                assert!(cb.len() <= 8 && (rest.is_empty() || cb.len() + 3 > 8));
+                assert_eq!(cb.len() + rest.len(), 3 * 3 + c.len_utf8() + d.len_utf8());
+                let mut copy = cb;
+                copy.make_contiguous();
+                assert!(copy == cb && copy.is_contiguous());
+                assert_eq!(cb.pop_char_front(), Some(d));
+                assert_eq!(cb.pop_char_front(), Some(c));
+                while cb.pop_char().is_some() {}
+                assert!(cb.len() == 0 && cb.to_strs() == ("", ""));
+            }
+        }
+        let mut cb = cstr::<4>::make("😀");
+        assert_eq!(cb.push_front("λ"), "λ");
+        let mut cb = cstr::<4>::make("ab");
+        cb.truncate_left(1);
+        assert_eq!(cb.push_front("yxλ"), "y"); // λ wraps around index 0
+        assert_eq!(cb.to_strs(), ("xλ", "b"));
+        cb.truncate_right(1);
+        assert!(cb.push_str("😀").len() == 4 && cb.push_str("λ").is_empty());
+        assert_eq!(cb.to_strs(), ("xλ", ""));
+        assert!(std::panic::catch_unwind(move || cb.truncate_left(2)).is_err());
+        let cs = cstr::<16, Saturate>::make("λλλλλλλλλλ");
+        assert_eq!(cs, "λλλλλλ…");
+        let mut cs = cstr::<16, Saturate>::make("λλλλλλλ");
+        assert_eq!(cs.push_front("€€"), "€€");
+        assert_eq!(cs, "…λλλλλλ");
+        assert_eq!(cstr::<8>::make("aλb€c").substr(1, 3), "λ");
+        assert_eq!(cstr::<8>::make("aλb€c").substr(2, 3), "");
+        let mut cb = cstr::<8>::make("abcd");
+        cb.truncate_left(4);
+        cb.push_str(" λ€ "); // € wraps around the end
+        assert_eq!(cb.to_strs(), (" λ€", " "));
+        cb.trim_whitespaces();
+        assert_eq!(cb, "λ€");
+
+        // ring-log eviction
+        let mut log = cstr::<8>::make("λλab");
+        assert_eq!(log.push_str_evicting("€"), 2); // one λ
+        assert_eq!(log.to_strs(), ("λab€", "")); // € wraps around
+        assert_eq!(log.push_char_evicting('c'), 0);
+        assert_eq!(log.to_strs(), ("λab€", "c"));
+        assert_eq!(log.push_front_evicting("😀"), 4);
+        assert_eq!(log, "😀λab");
+        assert_eq!(log.push_char_front_evicting('x'), 1);
+        assert_eq!(log, "x😀λa");
+        assert_eq!(log.push_front_evicting("0123456789"), 2 + 8);
+        assert_eq!(log, "01234567");
+        let mut log = cstr::<12>::make("ab\ncd\n");
+        assert_eq!(log.push_str_evicting_lines("efgh"), 0);
+        assert_eq!(log.push_str_evicting_lines("ij\n"), 3);
+        assert_eq!(log, "cd\nefghij\n");
+        assert_eq!(log.push_str_evicting_lines("0123456789"), 10);
+        assert_eq!(log, "0123456789");
+        assert_eq!(log.push_str_evicting_lines("\n"), 0);
+        assert_eq!(log.push_str_evicting_lines("λλ"), 11);
+        assert_eq!(log, "λλ");
+        // a src that does not fit evicts the whole string, even when the
+        // cut inside src leaves room for some of it
+        let mut log = cstr::<4>::make("bbb");
+        assert_eq!(log.push_str_evicting("😀a"), 3 + 4);
+        assert_eq!(log, "a");
+        let mut log = cstr::<4>::make("b\nb");
+        assert_eq!(log.push_str_evicting_lines("😀a"), 3 + 4);
+        assert_eq!(log, "a");
+        let mut log = cstr::<4>::make("bbb");
+        assert_eq!(log.push_front_evicting("a😀"), 3 + 4);
+        assert_eq!(log, "a");
+        assert_eq!(log.push_front_evicting("λλλ"), 1 + 2);
+        assert_eq!(log, "λλ");
+    } //cstr_unicode_tests
+
+    // checks the overflow policies on a type of capacity 7
+    macro_rules! policy_check {
+        ($t:ident, $n:literal, $const_make:ident) => {{
+            use core::fmt::Write;
+            let panics = |f: fn()| std::panic::catch_unwind(f).is_err();
+            assert_eq!($t::<$n>::make("abcdefghij"), "abcdefg");
+            assert_eq!($t::<$n, TruncateAtCharBoundary>::make("abcdefλ"), "abcdef");
+
+            let mut s = $t::<$n, Strict>::make("abc");
+            assert_eq!(s.push_str("defghij"), "defghij");
+            assert_eq!(s, "abc");
+            assert!(write!(s, "{}", 12345).is_err());
+            assert_eq!(s, "abc");
+            assert_eq!(s.push_str("defg"), "");
+            assert!(!s.push_char('x'));
+            assert_eq!(s, "abcdefg");
+            assert_eq!($t::<$n, Strict>::$const_make("abc"), "abc");
+            assert!(panics(|| {
+                $t::<$n, Strict>::make("abcdefghij");
+            }));
+            assert!(panics(|| {
+                let _ = $t::<$n, Strict>::make("abcde") + "fghij";
+            }));
+            assert!(panics(|| {
+                let _: $t<$n, Strict> = "abcdefghij".chars().collect();
+            }));
+            assert!($t::<$n, Strict>::try_make("abcdefghij").is_err());
+
+            assert!(panics(|| {
+                $t::<$n, Panic>::make("abcdefghij");
+            }));
+            assert!(panics(|| {
+                $t::<$n, Panic>::new().push_str("abcdefghij");
+            }));
+            assert!(panics(|| {
+                let _ = str_format!($t<$n, Panic>, "{}", 12345678);
+            }));
+            assert!($t::<$n, Panic>::try_make("abcdefghij").is_err());
+
+            assert_eq!($t::<$n, Saturate>::make("abcdefghij"), "abcd…");
+            assert_eq!($t::<$n, Saturate>::$const_make("abcdefghij"), "abcd…");
+            assert_eq!($t::<$n, Saturate>::make("abcλλλλ"), "abc…");
+            let mut s = $t::<$n, Saturate>::make("abc");
+            assert_eq!(s.push_str("defghij"), "efghij");
+            assert_eq!(s, "abcd…");
+            assert_eq!(s.push_str("x"), "x");
+            assert_eq!(s, "abcd…");
+            let mut s = $t::<$n, Saturate>::make("abcdef");
+            assert_eq!(s.push_str("gh"), "gh");
+            assert_eq!(s, "abcd…");
+            assert_eq!($t::<$n, Saturate>::make("abc") + "defghij", "abcd…");
+            assert_eq!(str_format!($t<$n, Saturate>, "{}", 1234567890), "1234…");
+            let s: $t<$n, Saturate> = ["abc", "defghij", "x"].into_iter().collect();
+            assert_eq!(s, "abcd…");
+        }};
+    }
+
+    fn policytests() {
+        extern crate std;
+        policy_check!(tstr, 8, const_make);
+        policy_check!(zstr, 8, const_make);
+        policy_check!(mstr, 9, const_make);
+        #[cfg(feature = "std")]
+        #[cfg(not(feature = "no-alloc"))]
+        policy_check!(fstr, 7, const_create);
+
+        let s: tstr<8, Strict> = tstr::make("abc");
+        assert_eq!(s + s, "abcabc"); // str8 + str8 is a str16
+        assert_eq!(s.resize::<4>(), "abc");
+        assert!(std::panic::catch_unwind(|| s.resize::<3>()).is_err());
+        assert_eq!(zstr::<8, Saturate>::make("abcdefghij").resize::<6>(), "ab…");
+        assert_eq!(tstr::<3, Saturate>::make("abcdefghij"), "ab"); // too small for …
+        assert_eq!(core::mem::size_of::<tstr<8, Strict>>(), 8);
+
+        #[cfg(feature = "circular-str")]
+        {
+            let mut c = cstr::<7, Strict>::make("abc");
+            assert_eq!(c.push_str("defghij"), "defghij");
+            assert_eq!(c.push_front("xyzuvw"), "xyzuvw");
+            assert_eq!(c.push_front("wxyz"), "");
+            assert!(!c.push_char('x'));
+            assert!(!c.push_char_front('x'));
+            assert_eq!(c, "wxyzabc");
+            assert!(std::panic::catch_unwind(|| "abcd" + cstr::<7, Strict>::make("efgh")).is_err());
+            let mut c = cstr::<7, Saturate>::make("abcdef");
+            assert_eq!(c.push_str("ghij"), "ghij");
+            assert_eq!(c, "abcd…");
+            let c = cstr::<16>::make("abcdefghijkl");
+            assert_eq!(c.resize::<8>(), "abcdefgh");
+        }
+    } //policytests
+
+    fn errortests() -> Result<(), CapacityError> {
+        use core::fmt::Write;
+        let overflow = |required, available| CapacityError::Overflow { required, available };
+        assert_eq!(str4::try_make("abcd"), Err(overflow(4, 3)));
+        assert_eq!("abcd".parse::<str4>(), Err(overflow(4, 3)));
+        assert_eq!(str4::const_try_make("abcd"), Err(overflow(4, 3)));
+        assert_eq!(zstr::<4>::try_make("abcd"), Err(overflow(4, 3)));
+        assert_eq!(zstr::<8>::try_make("ab\0cd"), Err(CapacityError::InteriorNul { position: 2 }));
+        assert_eq!(
+            zstr::<8>::const_try_make("\0"),
+            Err(CapacityError::InteriorNul { position: 0 })
+        );
+        let utf16: [u16; 3] = [0x61, 0x3bb, 0x62]; // "aλb"
+        assert_eq!(str4::from_utf16(&utf16), Err(overflow(4, 3)));
+        assert_eq!(str8::from_utf16(&utf16)?, "aλb");
+        assert_eq!(zstr::<8>::from_utf16(&utf16)?, "aλb");
+        assert_eq!(
+            zstr::<8>::from_utf16(&[0x61, 0]),
+            Err(CapacityError::InteriorNul { position: 1 })
+        );
+        assert_eq!(str8::from_utf16(&[0x61, 0xdc00]), Err(CapacityError::InvalidUtf16));
+        assert_eq!(
+            <str8 as FixedstrMake>::from_utf8(b"ab\xffc"),
+            Err(CapacityError::InvalidUtf8 { valid_up_to: 2 })
+        );
+        assert_eq!(<zstr<8> as FixedstrMake>::from_utf8(b"abc")?, "abc");
+        let s: str8 = "abc".parse()?;
+        assert_eq!(s, "abc");
+        let mut msg = str64::new();
+        write!(msg, "{}", overflow(4, 3)).unwrap();
+        assert_eq!(msg, "capacity exceeded: 4 bytes required but only 3 available");
+        #[cfg(feature = "std")]
+        #[cfg(not(feature = "no-alloc"))]
+        {
+            assert_eq!(fstr::<4>::try_make("abcde"), Err(overflow(5, 4)));
+            assert_eq!(fstr::<4>::const_try_create("abcd")?, "abcd");
+            assert_eq!(fstr::<2>::from_utf16(&utf16), Err(overflow(4, 2)));
+        }
+        #[cfg(feature = "circular-str")]
+        {
+            assert_eq!(
+                cstr::<4>::try_make_ascii("aλb"),
+                Err(CapacityError::NonAscii { position: 1 })
+            );
+            assert_eq!(cstr::<4>::try_make_ascii("abcde"), Err(overflow(5, 4)));
+            assert_eq!(cstr::<4>::from_pair("abc", "de"), Err(overflow(5, 4)));
+            assert_eq!(cstr::<4>::from_utf16(&utf16)?, "aλb");
+            assert_eq!(cstr::<3>::from_utf16(&utf16), Err(overflow(4, 3)));
+            assert_eq!(cstr::<4>::const_try_make("ab")?, "ab");
+        }
+        #[cfg(feature = "flex-str")]
+        #[cfg(not(feature = "no-alloc"))]
+        assert!(Flexstr::<4>::from_utf16(&utf16)?.is_owned());
+        Ok(())
+    } //errortests
+
     /// test struct
     struct AB(i32, u32);
     impl core::fmt::Display for AB {
@@ -685,7 +2829,7 @@ mod tests {
         let c2 = str8::from("xyz");
         assert!(c2.case_insensitive_eq("XyZ"));
         let c2b = str16::from("xYz");
-        assert!(c2.case_insensitive_eq(&c2b));
+        assert!(c2.case_insensitive_eq(c2b));
         let mut c3 = c1 + c2;
         assert_eq!(c3, "abcdxyz");
         assert_eq!(c3.capacity(), 15); // type of c3 is str16
@@ -697,6 +2841,7 @@ mod tests {
         let c5 = try_format!(str8, "abcdef{}", "ghijklmn");
         assert!(c5.is_none()); // try_format! returns None if capacity exceeded
 
+        #[allow(clippy::zero_prefixed_literal)] // leading zero is deliberate
         let fs = to_fixedstr!(str8, -0132);
         assert_eq!(&fs, "-132");
 
@@ -769,7 +2914,7 @@ mod tests {
                 use std::collections::HashSet;
                 let mut hh = HashSet::new();
                 hh.insert(bb);
-                assert!(hh.get(&bb).is_some());
+                assert!(hh.contains(&bb));
             }
         } //cstr tests
     } //nostdtest
@@ -863,7 +3008,8 @@ mod tests {
         #[cfg(feature = "experimental")]
         {
             let mut s = <zstr<8>>::from("abcd");
-            s[0] = b'A'; // impls IndexMut for zstr (not for fstr nor strN types)
+            assert_eq!(s[1], b'b'); // impls Index<usize> for zstr (not for fstr nor strN types)
+            s.set_byte_char(0, 'A');
             assert_eq!('A', s.nth_ascii(0));
         }
 
@@ -874,7 +3020,7 @@ mod tests {
 
         let mut a: fstr<8> = fstr::from("abcdef");
         let rem = a.push("g");
-        assert!(rem == "" && &a == "abcdefg");
+        assert!(rem.is_empty() && &a == "abcdefg");
 
         use std::collections::BTreeMap;
         let mut map:BTreeMap<str8,i32> = BTreeMap::new();
@@ -903,8 +3049,8 @@ mod tests {
         assert!(ab == "bcde" && a1 == a); // can compare with &str and itself
         assert!(a < ab); // implements Ord trait (and Hash
         let mut u: fstr<8> = fstr::from("aλb"); //unicode support
-        u.nth(1).map(|x| assert_eq!(x, 'λ')); // nth returns Option<char>
-                                              //for x in u.nth(1) {assert_eq!(x,'λ');} // nth returns Option<char>
+        assert_eq!(u.nth(1), Some('λ')); // nth returns Option<char>
+        //for x in u.nth(1) {assert_eq!(x,'λ');} // nth returns Option<char>
         assert!(u.set(1, 'μ')); // changes a character of the same character class
         assert!(!u.set(1, 'c')); // .set returns false on failure
         assert!(u.set(2, 'c'));
@@ -949,6 +3095,11 @@ mod tests {
         let ab = Flexstr::<8>::from("bcdefghijklmnop");
         assert!(a.is_fixed());
         assert!(!ab.is_fixed());
+        let mut fl = Flexstr::<4>::from("ab");
+        assert!(fl.replace_nth(0, 'λ') && fl == "λb" && fl.is_fixed());
+        assert!(fl.replace_at(2, '€') && fl == "λ€" && !fl.is_fixed());
+        assert!(fl.replace_nth(1, 'c') && !fl.replace_nth(2, 'c') && !fl.replace_at(1, 'c'));
+        assert_eq!(fl, "λc");
         let a2: str8 = a.get_str().unwrap();
         assert!(a < ab); // impls Ord, (and Hash, Debug, Eq, other common traits)
         let astr: &str = a.to_str(); // convert to &str (zero copy)
@@ -981,6 +3132,16 @@ mod tests {
         let ab = Flexstr::<32>::from("bcdefghijklmnop");
         println!("size of ab: {}", std::mem::size_of::<Flexstr<32>>());
 
+        // for N>256 the representation is always owned
+        let mut big = Flexstr::<300>::from("abc");
+        assert!(big.is_owned());
+        big.push_str("defg");
+        assert!(!big.truncate(2) && big.is_owned() && big == "ab");
+        assert_eq!(big.pop(), Some('b'));
+        big.clear();
+        assert!(big.is_owned() && big.split_off().is_empty());
+        assert!(Flexstr::<300>::default().is_owned());
+
         let mut vv = Flexstr::<8>::from("abcd");
         vv.push('e');
         //vv.push('λ');
@@ -1103,9 +3264,89 @@ mod tests {
         assert_eq!(zb, zc);
     } //tiny tests
 
-    #[cfg(feature = "pub-tstr")]
     fn consttests() {
        let ls = tstr::<{tstr_limit(258)}>::from("abcd");
        assert_eq!(ls.capacity(),255);
+       let ts = tstr::<1>::make("abc");
+       assert!(ts.is_empty() && ts.capacity() == 0);
+       let zs = zstr::<1>::make("abc");
+       assert!(zs.is_empty() && zs.capacity() == 0);
+
+       static STATIC: str4 = fixedstr!(str4, "abc");
+       const CONST: zstr<8, Strict> = fixedstr!(zstr<8, Strict>, "abcdefg");
+       assert_eq!((STATIC, CONST), (str4::make("abc"), zstr::make("abcdefg")));
+       let (a, b) = (fixedstr!(""), fixedstr!("abc"));
+       let (c, d) = (fixedstr!("abcd"), fixedstr!("λλλλλλ"));
+       let _: (str4, str4, str8, str16) = (a, b, c, d);
+       assert_eq!(fixedstr!("0123456789012345678901234567890123456789").capacity(), 47);
+       #[cfg(feature = "circular-str")]
+       {
+           const C: cstr<4> = fixedstr!(cstr<4>, "abλ");
+           assert_eq!(C, "abλ");
+       }
+       #[cfg(feature = "std")]
+       #[cfg(not(feature = "no-alloc"))]
+       assert_eq!(fixedstr!(fstr<3>, "abc"), "abc");
+
+       // the const methods agree with their non-const counterparts
+       macro_rules! const_fn_check {
+           ($s:expr) => {{
+               let s = $s;
+               let t = s.to_str();
+               for y in ["", "a", " x", "aλ€b", "Aλ€B  ", "b", "€b", "zzzzzzzzzzzz"] {
+                   assert_eq!(s.const_eq(y), t == y);
+                   assert_eq!(s.const_cmp(y), t.cmp(y));
+                   assert_eq!(s.const_starts_with(y), t.starts_with(y));
+                   assert_eq!(s.const_find(y), t.find(y));
+                   let mut p = s;
+                   let mut q = s;
+                   assert_eq!(p.const_push_str(y), q.push_str(y));
+                   assert_eq!(p, q);
+               }
+               assert_eq!(s.const_trim(), t.trim());
+               let (upper, lower) = (s.const_to_ascii_upper(), s.const_to_ascii_lower());
+               assert!(upper.chars().eq(t.chars().map(|c| c.to_ascii_uppercase())));
+               assert!(lower.chars().eq(t.chars().map(|c| c.to_ascii_lowercase())));
+               assert_eq!(s.const_substr(1, 3), s.substr(1, 3));
+               assert_eq!(s.const_substr(2, 100), s.substr(2, 100));
+               assert!(s.const_substr(3, 3).is_empty());
+           }};
+       }
+       for x in ["", "aλ€b", " \u{3000}Aλ€b\t", "abcdefghijk"] {
+           const_fn_check!(str16::make(x));
+           const_fn_check!(zstr::<16>::make(x));
+           const_fn_check!(tstr::<16, Saturate>::make(x));
+           #[cfg(feature = "std")]
+           #[cfg(not(feature = "no-alloc"))]
+           const_fn_check!(fstr::<15>::make(x));
+       }
+       #[cfg(feature = "circular-str")]
+       for k in 0..16 {
+           // rotate the string so that it wraps around at every position
+           let mut c = cstr::<16>::make(&"----------------"[..k]);
+           c.truncate_left(k);
+           c.push_str(" \u{3000}Aλ€b\t");
+           let t = str32::make(c.to_strs().0) + c.to_strs().1;
+           assert!(c.const_eq(&t) && c.const_cmp(&t).is_eq() && c.const_cmp(" ").is_gt());
+           assert_eq!(c.const_find("λ€"), t.find("λ€"));
+           assert!(c.const_starts_with(" \u{3000}A") && !c.const_starts_with("A"));
+           assert_eq!(c.const_trim(), "Aλ€b");
+           assert_eq!(c.const_to_ascii_upper(), " \u{3000}Aλ€B\t");
+           assert_eq!(c.const_to_ascii_lower(), " \u{3000}aλ€b\t");
+           assert_eq!(c.const_substr(5, 7), "λ");
+           assert!(c.const_substr(6, 7).is_empty());
+           let mut d = c;
+           assert_eq!(d.const_push_str("xyz"), c.clone().push_str("xyz"));
+           let mut e = c;
+           e.push_str("xyz");
+           assert_eq!(d, e);
+       }
+       const TABLE: [str8; 3] = [
+           str8::const_make(" One ").const_trim().const_to_ascii_upper(),
+           str8::const_make("two").const_substr(0, 2),
+           str8::const_make("λ€").const_to_ascii_upper(),
+       ];
+       const _: () = assert!(TABLE[0].const_eq("ONE") && TABLE[1].const_cmp("tw").is_eq());
+       assert_eq!(TABLE[2].const_find("€"), Some(2));
     }//consttests
 } //tests mod
@@ -0,0 +1,985 @@
+//! A medium string or `mstr<N>`, with N<=65537, is the counterpart of
+//! [tstr](crate::tstr) for strings that are too long for a single length
+//! byte.  An `mstr<N>` is represented underneath by a `[u8;N]` whose first
+//! two bytes hold the length of the string as a little-endian `u16`, so that
+//! each `mstr<N>` can hold a string of up to N-2 bytes (at most 65535).
+//! Compared to a [zstr](crate::zstr) the length is found in constant time,
+//! and compared to an `fstr` two bytes are used to store it instead of a
+//! `usize`.  As for `tstr`, the bound on N is enforced by compile-time
+//! assertions in the constructors.
+
+#![allow(unused_variables)]
+#![allow(non_snake_case)]
+#![allow(non_camel_case_types)]
+#![allow(unused_parens)]
+#![allow(unused_assignments)]
+#![allow(unused_mut)]
+#![allow(unused_imports)]
+#![allow(dead_code)]
+
+#[cfg(not(feature = "no-alloc"))]
+extern crate alloc;
+
+use crate::const_fns::ConstBytes;
+use crate::CapacityError;
+use crate::{FixedstrMake, SplitInto, StrPattern};
+use crate::{OverflowMode, OverflowPolicy, Truncate};
+use core::cmp::{min, Ordering};
+use core::ops::{Add, Range, RangeBounds};
+use core::marker::PhantomData;
+
+/// Strings of up to N-2 bytes, where N must be between 2 and 65537.  The
+/// types [mstr512] through [mstr64k] alias `mstr<512>` through
+/// `mstr<65536>` respectively.  The first two bytes of the underlying
+/// `[u8;N]` hold the length of the string, which limits N at compile time:
+/// ```compile_fail
+///   # use fixedstr::*;
+///   let s = mstr::<70000>::make("too big");
+/// ```
+///
+/// Like the [crate::tstr] aliases, concatenating two `mstr` aliases of
+/// the same size produces an `mstr` of twice the size, from `mstr<256>`
+/// up to [mstr64k]:
+/// ```
+///   # use fixedstr::*;
+///   let a = mstr1k::make("a log line");
+///   let b = mstr1k::make(", continued");
+///   let c = a + b;  // type of c will be mstr2k
+///   assert_eq!(c, "a log line, continued");
+///   assert_eq!(c.capacity(), 2046);
+///   assert_eq!(core::mem::size_of::<mstr1k>(), 1024);
+/// ```
+///
+/// The optional second parameter selects the [OverflowPolicy] (by default
+/// [Truncate]) that determines what happens when the capacity is exceeded:
+/// ```
+///   # use fixedstr::*;
+///   let s = mstr::<9, Saturate>::make("abcdefghij");
+///   assert_eq!(s, "abcd…");
+/// ```
+#[derive(Copy, Clone, Eq)]
+pub struct mstr<const N: usize = 1024, P: OverflowPolicy = Truncate> {
+    chrs: [u8; N],
+    policy: PhantomData<P>,
+} //mstr
+impl<const N: usize, P: OverflowPolicy> mstr<N, P> {
+    /// creates a new `mstr<N>` with given &str.  If the length of s exceeds
+    /// N-2, the overflow is handled according to the policy `P`: by default
+    /// the extra characters are ignored, truncating at a character
+    /// boundary.  To fail instead of truncating, use [mstr::try_make] or
+    /// `TryFrom`.
+    pub fn make(s: &str) -> mstr<N, P> {
+        let mut t = Self::new();
+        t.push_policy(s, false);
+        t
+    } //make
+
+    /// alias for [Self::make]
+    #[inline]
+    pub fn create(s: &str) -> mstr<N, P> {
+        Self::make(s)
+    } //create
+
+    /// version of make that returns a [CapacityError] if
+    /// truncation is requried, or the string in an `Ok(_)` if no
+    /// truncation is required
+    pub fn try_make(s: &str) -> Result<mstr<N, P>, CapacityError> {
+        Self::const_try_make(s)
+    }
+
+    /// version of [FromIterator] that returns a [CapacityError] instead of
+    /// applying the [OverflowPolicy] if the items do not fit.
+    /// See [crate::StrPiece] for the types of items.
+    pub fn try_from_iter<T: crate::StrPiece>(iter: impl IntoIterator<Item = T>) -> Result<mstr<N, P>, CapacityError> {
+        let mut s = mstr::new();
+        s.try_extend(iter)?;
+        Ok(s)
+    }
+
+    /// version of [Extend] that pushes either all of the items or, if they
+    /// do not fit, none of them, returning a [CapacityError]
+    pub fn try_extend<T: crate::StrPiece>(&mut self, iter: impl IntoIterator<Item = T>) -> Result<(), CapacityError> {
+        crate::try_extend_pieces(self, N - 2, false, iter)
+    }
+
+    /// const constructor, to be called from const contexts.  Overflow is
+    /// handled according to the policy `P`, so that under [crate::Strict]
+    /// or [crate::Panic] an oversized string fails to compile when
+    /// evaluated in a const context.
+    pub const fn const_make(s: &str) -> mstr<N, P> {
+        let mut t = mstr::<N, P>::new();
+        let bytes = s.as_bytes();
+        let (_, len, ellipsis) = crate::plan_push(0, bytes, N - 2, P::MODE, false);
+        crate::copy_bytes(&mut t.chrs, 2, bytes, len);
+        if ellipsis {
+            crate::copy_bytes(&mut t.chrs, len + 2, crate::ELLIPSIS.as_bytes(), crate::ELLIPSIS.len());
+            t.set_len(len + crate::ELLIPSIS.len());
+        } else {
+            t.set_len(len);
+        }
+        t
+    } //const_make
+
+    /// Version of `const_make` that does not truncate.
+    pub const fn const_try_make(s: &str) -> Result<mstr<N, P>, CapacityError> {
+        if s.len() + 2 > N {
+            Err(CapacityError::Overflow { required: s.len(), available: N - 2 })
+        } else {
+            Ok(mstr::const_make(s))
+        }
+    }
+
+    /// creates an empty string; equivalent to mstr::default() but can
+    /// also be called from a const context.
+    #[inline]
+    pub const fn new() -> mstr<N, P> {
+        const { assert!(2 <= N && N <= 65537, "mstr<N> requires 2 <= N <= 65537") };
+        mstr {
+            chrs: [0; N],
+            policy: PhantomData,
+        }
+    }
+
+    /// length of the string in bytes (consistent with [str::len]). This
+    /// is a constant-time operation.
+    #[inline]
+    #[allow(clippy::len_without_is_empty)] // is_empty is provided by Fixedstr
+    pub const fn len(&self) -> usize {
+        u16::from_le_bytes([self.chrs[0], self.chrs[1]]) as usize
+    }
+
+    // stores the length n <= N-2 in the first two bytes
+    #[inline]
+    const fn set_len(&mut self, n: usize) {
+        let [lo, hi] = (n as u16).to_le_bytes();
+        self.chrs[0] = lo;
+        self.chrs[1] = hi;
+    }
+
+    /// returns the number of characters in the string regardless of
+    /// character class.  This is not necessarily a constant-time operation.
+    pub fn charlen(&self) -> usize {
+        self.to_str().chars().count()
+    }
+
+    /// returns maximum capacity in bytes
+    #[inline]
+    pub const fn capacity(&self) -> usize {
+        N - 2
+    }
+
+    /// converts mstr to an alloc::string::string
+    #[cfg(not(feature = "no-alloc"))]
+    #[allow(clippy::inherent_to_string_shadow_display)] // no ToString import needed
+    pub fn to_string(&self) -> alloc::string::String {
+        alloc::string::String::from(self.to_str())
+    }
+
+    /// returns slice of u8 the array underneath the mstr
+    pub const fn as_bytes(&self) -> &[u8] {
+        self.chrs.split_at(2).1.split_at(self.len()).0
+    }
+
+    /// returns mutable slice of the u8 array underneath
+    ///
+    /// # Safety
+    /// The bytes must remain valid utf8.
+    pub unsafe fn as_bytes_mut(&mut self) -> &mut [u8] {
+        let n = self.len() + 2;
+        &mut self.chrs[2..n]
+    }
+
+    /// converts mstr to &str using [core::str::from_utf8_unchecked]
+    pub const fn to_str(&self) -> &str {
+        unsafe { core::str::from_utf8_unchecked(self.as_bytes()) }
+    }
+    /// checked version of [mstr::to_str], but may panic
+    pub fn as_str(&self) -> &str {
+        core::str::from_utf8(self.as_bytes()).unwrap()
+    }
+    /// version of [mstr::as_str] that does not call `unwrap`
+    pub fn as_str_safe(&self) -> Result<&str, core::str::Utf8Error> {
+        core::str::from_utf8(self.as_bytes())
+    }
+
+    /// changes a character at *character position* i to c.  This function
+    /// requires that c is in the same character class (ascii or unicode)
+    /// as the char being replaced.  It never shuffles the bytes underneath.
+    /// See [mstr::replace_nth] for a version without this requirement.
+    /// The function returns true if the change was successful.
+    pub fn set(&mut self, i: usize, c: char) -> bool {
+        let cbuf = &mut [0u8; 4];
+        c.encode_utf8(cbuf);
+        let clen = c.len_utf8();
+        if let Some((bi, rc)) = self.to_str().char_indices().nth(i)
+            && clen == rc.len_utf8()
+        {
+            self.chrs[bi + 2..bi + clen + 2].copy_from_slice(&cbuf[..clen]);
+            return true;
+        }
+        false
+    } //set
+
+    /// version of [mstr::set] that assumes that the char is a single byte.
+    /// Sets the char at the given *byte* index, and does nothing if the
+    /// byte at that index is not an ascii character of the string (which
+    /// includes the case that the index is not less than its length), so
+    /// that a multibyte character is never overwritten.  This function is
+    /// designed to be fast.  **Panics** if c is not an ascii character.
+    pub const fn set_byte_char(&mut self, i: usize, c: char) {
+        assert!(c.is_ascii());
+        if i < self.len() && self.chrs[i + 2].is_ascii() {
+            self.chrs[i + 2] = c as u8;
+        }
+    }
+
+    /// adds chars to end of current string up to maximum size N of `mstr<N>`,
+    /// returns the portion of the push string that was NOT pushed due to
+    /// capacity, so
+    /// if "" is returned then all characters were pushed successfully.
+    /// A multibyte character is never split: the returned remainder always
+    /// starts at a character boundary.  How much is pushed when the
+    /// capacity is exceeded depends on the [OverflowPolicy] `P`.
+    pub fn push<'t>(&mut self, s: &'t str) -> &'t str {
+        self.push_str(s)
+    } //push
+
+    /// alias for [Self::push]
+    pub fn push_str<'t>(&mut self, src: &'t str) -> &'t str {
+        self.push_policy(src, true)
+    } //push_str
+
+    // push according to the overflow policy; `fallible` is false for
+    // callers that cannot report the part that was not pushed
+    fn push_policy<'t>(&mut self, src: &'t str, fallible: bool) -> &'t str {
+        let (keep, n, ellipsis) =
+            crate::plan_push(self.len(), src.as_bytes(), N - 2, P::MODE, fallible);
+        if keep < self.len() {
+            self.set_len(crate::floor_char_boundary(self.as_bytes(), keep));
+        }
+        self.push_bytes(&src.as_bytes()[..n]);
+        if ellipsis {
+            self.push_bytes(crate::ELLIPSIS.as_bytes());
+        }
+        &src[n..]
+    } //push_policy
+
+    fn push_bytes(&mut self, bytes: &[u8]) {
+        let slen = self.len();
+        self.chrs[slen + 2..slen + bytes.len() + 2].copy_from_slice(bytes);
+        self.set_len(slen + bytes.len());
+    }
+
+    /// pushes a single character to the end of the string, returning
+    /// true on success.
+    pub fn push_char(&mut self, c: char) -> bool {
+        let mut buf = [0u8; 4]; // char buffer
+        self.push_str(c.encode_utf8(&mut buf)).is_empty()
+    } // push_char
+
+    /// remove and return last character in string, if it exists
+    pub fn pop_char(&mut self) -> Option<char> {
+        let lastchar = self.to_str().chars().next_back()?;
+        self.set_len(self.len() - lastchar.len_utf8());
+        Some(lastchar)
+    } //pop
+
+    /// returns the nth char of the mstr
+    pub fn nth(&self, n: usize) -> Option<char> {
+        self.to_str().chars().nth(n)
+    }
+
+    /// returns the nth byte of the string as a char.  This
+    /// function should only be called, for example, on ascii strings.  It
+    /// is designed to be quicker than [mstr::nth], and does not check array bounds or
+    /// check n against the length of the string. Nor does it check
+    /// if the value returned is a valid character.
+    pub const fn nth_bytechar(&self, n: usize) -> char {
+        self.chrs[n + 2] as char
+    }
+
+    /// alias for [Self::nth_bytechar]
+    pub const fn nth_ascii(&self, n: usize) -> char {
+        self.chrs[n + 2] as char
+    }
+
+    /// determines if string is an ascii string
+    pub fn is_ascii(&self) -> bool {
+        self.to_str().is_ascii()
+    }
+
+    /// shortens the mstr in-place (mutates).  n indicates the number of
+    /// *characters* to keep in the string. If n is greater than the
+    /// current character-length ([Self::charlen]) of the string, this operation will have no effect.
+    pub fn truncate(&mut self, n: usize) // n is char position, not binary position
+    {
+        if let Some((bi, c)) = self.to_str().char_indices().nth(n) {
+            self.set_len(bi);
+        }
+    }
+
+    /// truncates string up to *byte* position n.  **Panics** if n is
+    /// not on a character boundary, similar to truncate on owned Strings.
+    pub fn truncate_bytes(&mut self, n: usize) {
+        if (n < self.len()) {
+            assert!(self.is_char_boundary(n));
+            self.set_len(n);
+        }
+    }
+
+    /// Trims **in-place** trailing ascii whitespaces.  This function
+    /// regards all bytes as single chars.  The operation panics if
+    /// the resulting string does not end on a character boundary.
+    pub fn right_ascii_trim(&mut self) {
+        let mut n = self.len();
+        while n > 0 && (self.chrs[n + 1] as char).is_ascii_whitespace() {
+            n -= 1;
+        }
+        assert!(self.is_char_boundary(n));
+        self.set_len(n);
+    } //right_trim
+
+    /// resets string to empty string
+    pub fn clear(&mut self) {
+        self.set_len(0);
+    }
+
+    /// in-place modification of ascii characters to lower-case. Panics if
+    /// the string is not ascii.
+    pub fn make_ascii_lowercase(&mut self) {
+        assert!(self.is_ascii());
+        unsafe { self.as_bytes_mut() }.make_ascii_lowercase();
+    } //make_ascii_lowercase
+
+    /// in-place modification of ascii characters to upper-case.  Panics if
+    /// the string is not ascii.
+    pub fn make_ascii_uppercase(&mut self) {
+        assert!(self.is_ascii());
+        unsafe { self.as_bytes_mut() }.make_ascii_uppercase();
+    }
+
+    /// Constructs a clone of this mstr but with only upper-case ascii
+    /// characters.  This contrasts with [str::to_ascii_uppercase],
+    /// which creates an owned String.
+    pub fn to_ascii_upper(&self) -> Self {
+        let mut cp = *self;
+        cp.make_ascii_uppercase();
+        cp
+    }
+
+    /// Constructs a clone of this mstr but with only lower-case ascii
+    /// characters.  This contrasts with [str::to_ascii_lowercase],
+    /// which creates an owned String.
+    pub fn to_ascii_lower(&self) -> Self {
+        let mut cp = *self;
+        cp.make_ascii_lowercase();
+        cp
+    }
+
+    /// Tests for ascii case-insensitive equality with another string.
+    /// This function does not check if either string is ascii.
+    pub fn case_insensitive_eq<TA>(&self, other: TA) -> bool
+    where
+        TA: AsRef<str>,
+    {
+        self.as_bytes().eq_ignore_ascii_case(other.as_ref().as_bytes())
+    } //case_insensitive_eq
+
+    /// Decodes a UTF-16 encodeded slice.  Returns an error if the slice is
+    /// not valid utf16 or if the decoded string exceeds the capacity.
+    pub fn from_utf16(v: &[u16]) -> Result<Self, CapacityError> {
+        let required = crate::utf16_len(v)?;
+        if required > N - 2 {
+            return Err(CapacityError::Overflow {
+                required,
+                available: N - 2,
+            });
+        }
+        let mut s = Self::new();
+        for c in char::decode_utf16(v.iter().cloned()).flatten() {
+            s.push_char(c);
+        }
+        Ok(s)
+    } //from_utf16
+
+    /// returns a copy of the portion of the string, string could be truncated
+    /// if indices are out of range. Similar to slice [start..end]
+    pub fn substr(&self, start: usize, end: usize) -> mstr<N, P> {
+        let mut inds = self.char_indices();
+        let len = self.len();
+        if start >= len || end <= start {
+            return mstr::new();
+        }
+        let (si, _) = inds.nth(start).unwrap();
+        let last = if (end >= len) {
+            len
+        } else {
+            match inds.nth(end - start - 1) {
+                Some((ei, _)) => ei,
+                None => len,
+            } //match
+        }; //let last =...
+        self.const_range(si, last)
+    } //substr
+} //impl mstr<N>
+
+impl<const N: usize, P: OverflowPolicy> core::ops::Deref for mstr<N, P> {
+    type Target = str;
+    fn deref(&self) -> &Self::Target {
+        self.to_str()
+    }
+}
+
+impl<const N: usize, P: OverflowPolicy> core::convert::AsRef<str> for mstr<N, P> {
+    fn as_ref(&self) -> &str {
+        self.to_str()
+    }
+}
+
+/// Allows an `mstr` key of a `HashMap` or `BTreeMap` to be looked up by
+/// `&str`, as hashing and comparison are consistent with those of `str`.
+impl<const N: usize, P: OverflowPolicy> core::borrow::Borrow<str> for mstr<N, P> {
+    fn borrow(&self) -> &str {
+        self.to_str()
+    }
+}
+
+/// Collects characters or strings (see [crate::StrPiece]), truncating at
+/// the first item that does not fit as the [OverflowPolicy] dictates
+///
+/// # Panics
+/// Panics if an item does not fit under the [crate::Strict] or
+/// [crate::Panic] policy: use [mstr::try_from_iter] to detect overflow.
+impl<T: crate::StrPiece, const N: usize, P: OverflowPolicy> core::iter::FromIterator<T> for mstr<N, P> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut s = mstr::new();
+        s.extend(iter);
+        s
+    }
+}
+
+/// Pushes the items until one does not fit, which is handled according
+/// to the [OverflowPolicy].
+///
+/// # Panics
+/// Panics if an item does not fit under the [crate::Strict] or
+/// [crate::Panic] policy: use [mstr::try_extend] to detect overflow.
+impl<T: crate::StrPiece, const N: usize, P: OverflowPolicy> core::iter::Extend<T> for mstr<N, P> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        crate::extend_pieces(iter, P::MODE, |s| self.push_str(s).is_empty());
+    }
+}
+
+impl<const N: usize, P: OverflowPolicy> core::convert::AsMut<str> for mstr<N, P> {
+    fn as_mut(&mut self) -> &mut str {
+        unsafe { core::str::from_utf8_unchecked_mut(self.as_bytes_mut()) }
+    }
+}
+impl<const N: usize, P: OverflowPolicy> core::cmp::PartialOrd for mstr<N, P> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<const N: usize, P: OverflowPolicy> core::cmp::Ord for mstr<N, P> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_bytes().cmp(other.as_bytes())
+    }
+}
+
+impl<const M: usize, P: OverflowPolicy> mstr<M, P> {
+    /// converts an mstr\<M\> to an mstr\<N\>. If the length of the string being
+    /// converted is greater than N-2, the overflow is handled according to
+    /// the policy `P` (by default, the extra characters are ignored).
+    /// This operation produces a copy (non-destructive).
+    pub fn resize<const N: usize>(&self) -> mstr<N, P> {
+        mstr::make(self.to_str())
+    } //resize
+
+    /// version of resize that does not allow string truncation due to length
+    pub fn reallocate<const N: usize>(&self) -> Option<mstr<N, P>> {
+        if self.len() + 2 <= N {
+            Some(self.resize())
+        } else {
+            None
+        }
+    } //reallocate
+
+    /// converts to an `mstr<N>` at least as large as `self`, which unlike
+    /// [Self::resize] can never truncate: it is checked at compile time
+    /// that an `mstr<N>` can hold any `mstr<M>`.
+    /// ```
+    ///  # use fixedstr::*;
+    ///  let s = mstr512::make("abcdefg");
+    ///  let t: mstr1k = s.grow(); // s.grow::<256>() would not compile
+    ///  assert_eq!(t, "abcdefg");
+    /// ```
+    pub fn grow<const N: usize>(&self) -> mstr<N, P> {
+        crate::assert_grows::<Self, mstr<N, P>>();
+        self.resize()
+    }
+
+    /// concatenates `self` and `other` into a new `mstr<N>`.  It is checked
+    /// at compile time that an `mstr<N>` can hold the result, so nothing is
+    /// ever truncated.  `other` can be of any size and of any type
+    /// implementing [FixedCapacity](crate::FixedCapacity).
+    /// ```
+    ///  # use fixedstr::*;
+    ///  let a = mstr512::make("abc");
+    ///  let b = str256::make("def");
+    ///  let c = a.concat::<800>(&b); // a.concat::<700>(&b) would not compile
+    ///  assert_eq!(c, "abcdef");
+    /// ```
+    pub fn concat<const N: usize>(&self, other: &impl crate::FixedCapacity) -> mstr<N, P> {
+        crate::assert_concat_fits::<Self, _, mstr<N, P>>(other);
+        let mut cat: mstr<N, P> = self.resize();
+        crate::append_all(&mut cat, other);
+        cat
+    }
+} //impl mstr<M>
+
+impl<const N: usize, P: OverflowPolicy> core::fmt::Display for mstr<N, P> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.pad(self.to_str())
+    }
+}
+
+impl<const N: usize, P: OverflowPolicy> PartialEq<&str> for mstr<N, P> {
+    fn eq(&self, other: &&str) -> bool {
+        self.to_str() == *other
+    } //eq
+}
+impl<const N: usize, P: OverflowPolicy> PartialEq<mstr<N, P>> for &str {
+    fn eq(&self, other: &mstr<N, P>) -> bool {
+        &other.to_str() == self
+    }
+}
+/// defaults to empty string
+impl<const N: usize, P: OverflowPolicy> Default for mstr<N, P> {
+    fn default() -> Self {
+        mstr::<N, P>::new()
+    }
+}
+
+impl<const N: usize, P: OverflowPolicy> core::fmt::Debug for mstr<N, P> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.pad(self.to_str())
+    }
+} // Debug impl
+
+impl<const N: usize, P: OverflowPolicy> mstr<N, P> {
+    /// returns a copy of the string with all matches of the [StrPattern]
+    /// `pat` replaced by `to`, and whether the copy was truncated to fit
+    /// the capacity (according to the overflow policy `P`).  Unlike
+    /// [str::replace], this does not allocate.
+    pub fn replace<Pat: StrPattern>(&self, pat: Pat, to: &str) -> (mstr<N, P>, bool) {
+        crate::replace_into(self.as_str(), pat, to, usize::MAX)
+    }
+
+    /// version of [mstr::replace] that only replaces the first `count` matches
+    pub fn replacen<Pat: StrPattern>(&self, pat: Pat, to: &str, count: usize) -> (mstr<N, P>, bool) {
+        crate::replace_into(self.as_str(), pat, to, count)
+    }
+
+    /// version of [mstr::replace] that replaces every occurrence of one
+    /// character by another.  The copy can only be truncated if `to`
+    /// has a longer utf8 encoding than `from`.
+    pub fn replace_char(&self, from: char, to: char) -> (mstr<N, P>, bool) {
+        let mut buf = [0u8; 4];
+        crate::replace_into(self.as_str(), from, to.encode_utf8(&mut buf), usize::MAX)
+    }
+
+    /// removes all matches of the [StrPattern] `pat` in place
+    pub fn remove_matches<Pat: StrPattern>(&mut self, pat: Pat) {
+        *self = crate::replace_into(self.as_str(), pat, "", usize::MAX).0;
+    }
+
+    /// splits the string at the first match of `pat`, returning the parts
+    /// before and after the match, or `None` if there is no match.  The
+    /// parts are never truncated.
+    pub fn split_once<Pat: StrPattern>(&self, pat: Pat) -> Option<(mstr<N, P>, mstr<N, P>)> {
+        let s = self.as_str();
+        pat.find_in(s).map(|m| crate::split_around(s, m))
+    }
+
+    /// splits the string at the last match of `pat`, returning the parts
+    /// before and after the match, or `None` if there is no match.
+    pub fn rsplit_once<Pat: StrPattern>(&self, pat: Pat) -> Option<(mstr<N, P>, mstr<N, P>)> {
+        let s = self.as_str();
+        pat.rfind_in(s).map(|m| crate::split_around(s, m))
+    }
+
+    /// returns a copy of the string without the prefix `pat`, or `None` if
+    /// the string does not start with it
+    pub fn strip_prefix<Pat: StrPattern>(&self, pat: Pat) -> Option<mstr<N, P>> {
+        let s = self.as_str();
+        match pat.find_in(s) {
+            Some(m) if m.start == 0 => Some(mstr::make(&s[m.end..])),
+            _ => None,
+        }
+    }
+
+    /// returns a copy of the string without the suffix `pat`, or `None` if
+    /// the string does not end with it
+    pub fn strip_suffix<Pat: StrPattern>(&self, pat: Pat) -> Option<mstr<N, P>> {
+        let s = self.as_str();
+        match pat.rfind_in(s) {
+            Some(m) if m.end == s.len() => Some(mstr::make(&s[..m.start])),
+            _ => None,
+        }
+    }
+
+    /// iterator over the parts of the string separated by the [StrPattern]
+    /// `pat`, as with [str::split], but which yields strings of type `T`
+    /// instead of slices.  See [SplitInto].
+    pub fn split_into<T: FixedstrMake>(&self, pat: impl StrPattern) -> SplitInto<'_, T, impl StrPattern> {
+        SplitInto::new(self.as_str(), "", pat, usize::MAX)
+    }
+
+    /// version of [mstr::split_into] that yields at most `n` parts, the last
+    /// being the rest of the string, as with [str::splitn]
+    pub fn splitn_into<T: FixedstrMake>(&self, n: usize, pat: impl StrPattern) -> SplitInto<'_, T, impl StrPattern> {
+        SplitInto::new(self.as_str(), "", pat, n)
+    }
+
+    /// iterator over the whitespace-separated words of the string, as with
+    /// [str::split_whitespace], that yields strings of type `T`
+    pub fn split_whitespace_into<T: FixedstrMake>(&self) -> SplitInto<'_, T, fn(char) -> bool> {
+        SplitInto::new(self.as_str(), "", char::is_whitespace as fn(char) -> bool, usize::MAX).skipping_empty()
+    }
+
+    /// iterator over the lines of the string, as with [str::lines], that
+    /// yields strings of type `T`
+    pub fn lines_into<T: FixedstrMake>(&self) -> SplitInto<'_, T, char> {
+        SplitInto::new(self.as_str(), "", '\n', usize::MAX).splitting_lines()
+    }
+} //pattern methods
+
+impl<const N: usize, P: OverflowPolicy> mstr<N, P> {
+    // replaces the bytes in range r with `with`
+    fn splice(&mut self, r: Range<usize>, with: &str) -> Result<(), CapacityError> {
+        let len = self.len();
+        let n = crate::splice_bytes(&mut self.chrs[2..], len, r, with.as_bytes())?;
+        self.set_len(n);
+        Ok(())
+    }
+
+    /// inserts a character at byte index `idx`, shifting the rest of the
+    /// string, as `String::insert` does.  Returns false (leaving the
+    /// string unchanged) if the result would not fit.  **Panics** if `idx`
+    /// is larger than the length or not on a character boundary.
+    pub fn insert(&mut self, idx: usize, c: char) -> bool {
+        let mut buf = [0u8; 4];
+        self.insert_str(idx, c.encode_utf8(&mut buf)).is_ok()
+    }
+
+    /// inserts a string at byte index `idx`, as `String::insert_str` does,
+    /// or returns a [CapacityError] (leaving the string unchanged) if the
+    /// result would not fit.  **Panics** if `idx` is larger than the
+    /// length or not on a character boundary.
+    pub fn insert_str(&mut self, idx: usize, s: &str) -> Result<(), CapacityError> {
+        self.replace_range(idx..idx, s)
+    }
+
+    /// removes and returns the character at byte index `idx`, as
+    /// `String::remove` does.  **Panics** if `idx` is not less than the
+    /// length or not on a character boundary.
+    pub fn remove(&mut self, idx: usize) -> char {
+        let c = self.as_str()[idx..].chars().next().expect("cannot remove a char from the end of a string");
+        self.drain(idx..idx + c.len_utf8());
+        c
+    }
+
+    /// removes the given range of bytes from the string and returns it as
+    /// a new string.  Unlike `String::drain`, the removal is immediate and
+    /// no iterator is involved.  **Panics** if the range is out of bounds
+    /// or not on character boundaries.
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> mstr<N, P> {
+        let s = self.as_str();
+        let r = crate::byte_range(s.len(), range, |i| s.is_char_boundary(i));
+        let drained = mstr::make(&s[r.clone()]);
+        self.splice(r, "").unwrap();
+        drained
+    }
+
+    /// keeps only the characters for which `f` returns true, as
+    /// `String::retain` does
+    pub fn retain<F: FnMut(char) -> bool>(&mut self, mut f: F) {
+        let mut kept = mstr::<N, P>::new();
+        for c in self.as_str().chars().filter(|c| f(*c)) {
+            kept.push_char(c);
+        }
+        *self = kept;
+    }
+
+    /// replaces the given range of bytes with a string, as
+    /// `String::replace_range` does, or returns a [CapacityError] (leaving
+    /// the string unchanged) if the result would not fit.  **Panics** if
+    /// the range is out of bounds or not on character boundaries.
+    pub fn replace_range<R: RangeBounds<usize>>(&mut self, range: R, with: &str) -> Result<(), CapacityError> {
+        let s = self.as_str();
+        let r = crate::byte_range(s.len(), range, |i| s.is_char_boundary(i));
+        self.splice(r, with)
+    }
+
+    /// changes the character at *character position* n to c, returning
+    /// true on success.  Unlike [mstr::set], the new character may have a
+    /// different utf8 length than the one it replaces, in which case the
+    /// rest of the string is shifted.  Returns false (leaving the string
+    /// unchanged) only if n is out of range or the result would not fit.
+    pub fn replace_nth(&mut self, n: usize, c: char) -> bool {
+        match self.as_str().char_indices().nth(n) {
+            Some((i, _)) => self.replace_at(i, c),
+            None => false,
+        }
+    }
+
+    /// version of [mstr::replace_nth] that changes the character starting
+    /// at *byte* index `idx`.  Returns false if `idx` is not the start of a
+    /// character of the string or the result would not fit.
+    pub fn replace_at(&mut self, idx: usize, c: char) -> bool {
+        let mut buf = [0u8; 4];
+        match self.as_str().get(idx..).and_then(|t| t.chars().next()) {
+            Some(old) => self.replace_range(idx..idx + old.len_utf8(), c.encode_utf8(&mut buf)).is_ok(),
+            None => false,
+        }
+    }
+} //editing methods
+
+impl<const N: usize, P: OverflowPolicy> mstr<N, P> {
+    /// version of [mstr::push_str] that can be called from a const context,
+    /// as for the `const_` methods of [crate::tstr]:
+    /// ```
+    ///  # use fixedstr::*;
+    ///  const HEADER: mstr512 = {
+    ///     let mut h = mstr512::const_make("level=");
+    ///     h.const_push_str(mstr512::const_make(" WARN ").const_trim().const_to_ascii_lower().to_str());
+    ///     h
+    ///  };
+    ///  const _: () = assert!(HEADER.const_eq("level=warn"));
+    ///  assert_eq!(HEADER.const_find("warn"), Some(6));
+    ///  assert!(HEADER.const_cmp("level=x").is_lt() && HEADER.const_starts_with("level"));
+    ///  assert_eq!(HEADER.const_substr(6, 8), "wa");
+    /// ```
+    pub const fn const_push_str<'t>(&mut self, src: &'t str) -> &'t str {
+        let slen = self.len();
+        let (keep, n, ellipsis) = crate::plan_push(slen, src.as_bytes(), N - 2, P::MODE, true);
+        let mut end = slen;
+        if keep < slen {
+            end = crate::floor_char_boundary(self.as_bytes(), keep);
+        }
+        crate::copy_bytes(&mut self.chrs, end + 2, src.as_bytes(), n);
+        end += n;
+        if ellipsis {
+            crate::copy_bytes(&mut self.chrs, end + 2, crate::ELLIPSIS.as_bytes(), crate::ELLIPSIS.len());
+            end += crate::ELLIPSIS.len();
+        }
+        self.set_len(end);
+        src.split_at(n).1
+    } //const_push_str
+
+    /// version of `==` with a `&str` that can be called from a const context
+    pub const fn const_eq(&self, other: &str) -> bool {
+        crate::const_fns::eq(self.const_bytes(), ConstBytes::of(other))
+    }
+
+    /// version of [Ord::cmp] with a `&str` that can be called from a
+    /// const context
+    pub const fn const_cmp(&self, other: &str) -> Ordering {
+        crate::const_fns::cmp(self.const_bytes(), ConstBytes::of(other))
+    }
+
+    /// version of [str::starts_with] that can be called from a const context
+    pub const fn const_starts_with(&self, prefix: &str) -> bool {
+        crate::const_fns::starts_with(self.const_bytes(), ConstBytes::of(prefix))
+    }
+
+    /// returns the byte position of the first occurrence of `pat`, like
+    /// [str::find] but callable from a const context
+    pub const fn const_find(&self, pat: &str) -> Option<usize> {
+        crate::const_fns::find(self.const_bytes(), ConstBytes::of(pat))
+    }
+
+    /// version of [mstr::substr] that can be called from a const context:
+    /// returns a copy of the characters at (character) positions
+    /// `start..end`, which are clamped to the length of the string
+    pub const fn const_substr(&self, start: usize, end: usize) -> mstr<N, P> {
+        if end <= start {
+            return mstr::new();
+        }
+        let b = self.const_bytes();
+        self.const_range(crate::const_fns::char_to_byte(b, start), crate::const_fns::char_to_byte(b, end))
+    }
+
+    /// version of [mstr::to_ascii_upper] that can be called from a const context
+    pub const fn const_to_ascii_upper(&self) -> mstr<N, P> {
+        self.const_map_ascii(true)
+    }
+
+    /// version of [mstr::to_ascii_lower] that can be called from a const context
+    pub const fn const_to_ascii_lower(&self) -> mstr<N, P> {
+        self.const_map_ascii(false)
+    }
+
+    /// returns a copy of the string without leading and trailing
+    /// whitespace, like [str::trim] but callable from a const context
+    pub const fn const_trim(&self) -> mstr<N, P> {
+        let (start, end) = crate::const_fns::trim(self.const_bytes());
+        self.const_range(start, end)
+    }
+
+    // the bytes of the string, for the const methods
+    const fn const_bytes(&self) -> ConstBytes<'_> {
+        ConstBytes::new(&self.chrs, 2, self.len())
+    }
+
+    // copy of bytes start..end, which must be at character boundaries
+    const fn const_range(&self, start: usize, end: usize) -> mstr<N, P> {
+        let mut s = mstr::new();
+        crate::copy_bytes(&mut s.chrs, 2, self.chrs.split_at(start + 2).1, end - start);
+        s.set_len(end - start);
+        s
+    }
+
+    const fn const_map_ascii(&self, upper: bool) -> mstr<N, P> {
+        let mut s = *self;
+        let mut i = 2;
+        while i < self.len() + 2 {
+            s.chrs[i] = if upper { s.chrs[i].to_ascii_uppercase() } else { s.chrs[i].to_ascii_lowercase() };
+            i += 1;
+        }
+        s
+    }
+} //const methods
+
+impl<const N: usize, TA: AsRef<str> + ?Sized, P: OverflowPolicy> Add<&TA> for mstr<N, P> {
+    type Output = mstr<N, P>;
+    fn add(self, other: &TA) -> mstr<N, P> {
+        let mut a2 = self;
+        a2.push_policy(other.as_ref(), false);
+        a2
+    }
+} //Add &str
+
+impl<const N: usize, P: OverflowPolicy> Add<&mstr<N, P>> for &str {
+    type Output = mstr<N, P>;
+    fn add(self, other: &mstr<N, P>) -> mstr<N, P> {
+        let mut a2 = mstr::make(self);
+        a2.push_policy(other, false);
+        a2
+    }
+} //Add &str on left
+
+impl<const N: usize, P: OverflowPolicy> Add<mstr<N, P>> for &str {
+    type Output = mstr<N, P>;
+    fn add(self, other: mstr<N, P>) -> mstr<N, P> {
+        let mut a2 = mstr::make(self);
+        a2.push_policy(&other, false);
+        a2
+    }
+} //Add &str on left
+
+/// Usage:
+/// ```
+///   # use fixedstr::*;
+///   use core::fmt::Write;
+///   let mut s = mstr512::new();
+///   let result = write!(&mut s, "request {} took {}ms", 17, 250);
+///   assert_eq!(s, "request 17 took 250ms");
+/// ```
+impl<const N: usize, P: OverflowPolicy> core::fmt::Write for mstr<N, P> {
+    /// pushes `s` according to the overflow policy (by default, as much
+    /// as fits up to a character boundary), returning an error if anything
+    /// had to be left out.
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        if !self.push(s).is_empty() {
+            return Err(core::fmt::Error);
+        }
+        Ok(())
+    } //write_str
+} //core::fmt::Write trait
+
+impl<const N: usize, P: OverflowPolicy> core::hash::Hash for mstr<N, P> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.as_ref().hash(state);
+    }
+} //hash
+
+impl<const N: usize, P: OverflowPolicy> core::cmp::PartialEq for mstr<N, P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_ref() == other.as_ref()
+    }
+}
+
+impl<const N: usize, P: OverflowPolicy> core::str::FromStr for mstr<N, P> {
+    type Err = CapacityError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        mstr::try_make(s)
+    }
+}
+
+impl<const N: usize, P: OverflowPolicy> crate::FixedCapacity for mstr<N, P> {
+    const CAPACITY: usize = N - 2;
+}
+
+impl<const N: usize, P: OverflowPolicy> crate::Fixedstr for mstr<N, P> {
+    fn len(&self) -> usize {
+        self.len()
+    }
+    fn capacity(&self) -> usize {
+        self.capacity()
+    }
+    fn with_segments(&self, f: &mut dyn FnMut(&str, &str)) {
+        f(self.to_str(), "")
+    }
+    fn push_str<'t>(&mut self, src: &'t str) -> &'t str {
+        self.push_str(src)
+    }
+    fn push_char(&mut self, c: char) -> bool {
+        self.push_char(c)
+    }
+    fn pop_char(&mut self) -> Option<char> {
+        self.pop_char()
+    }
+    fn truncate_bytes(&mut self, n: usize) {
+        self.truncate_bytes(n)
+    }
+    fn clear(&mut self) {
+        self.clear()
+    }
+}
+
+impl<const N: usize, P: OverflowPolicy> crate::FixedstrMake for mstr<N, P> {
+    fn make(s: &str) -> Self {
+        mstr::make(s)
+    }
+    fn try_make(s: &str) -> Result<Self, CapacityError> {
+        mstr::try_make(s)
+    }
+    fn new() -> Self {
+        mstr::new()
+    }
+}
+
+impl<const N: usize, P: OverflowPolicy> crate::ContiguousFixedstr for mstr<N, P> {
+    fn as_str(&self) -> &str {
+        self.to_str()
+    }
+}
+
+/// An mstr512 can hold a string of up to 510 bytes.  See [mstr].
+pub type mstr512 = mstr<512>;
+/// An mstr1k can hold a string of up to 1022 bytes.  See [mstr].
+pub type mstr1k = mstr<1024>;
+/// An mstr2k can hold a string of up to 2046 bytes.  See [mstr].
+pub type mstr2k = mstr<2048>;
+/// An mstr4k can hold a string of up to 4094 bytes.  See [mstr].
+pub type mstr4k = mstr<4096>;
+/// An mstr8k can hold a string of up to 8190 bytes.  See [mstr].
+pub type mstr8k = mstr<8192>;
+/// An mstr16k can hold a string of up to 16382 bytes.  See [mstr].
+pub type mstr16k = mstr<16384>;
+/// An mstr32k can hold a string of up to 32766 bytes.  See [mstr].
+pub type mstr32k = mstr<32768>;
+/// An mstr64k can hold a string of up to 65534 bytes.  See [mstr].
+pub type mstr64k = mstr<65536>;
@@ -0,0 +1,142 @@
+//! Overflow policies, which determine what the fixed-capacity string
+//! types [crate::zstr], [crate::tstr], `fstr` and `cstr` do when an
+//! operation would exceed their capacity.  The policy is chosen by the
+//! second type parameter of each type, which defaults to [Truncate]:
+//! ```
+//!   # use fixedstr::*;
+//!   type Name = zstr<8, Strict>;
+//!   let mut n = Name::make("abc");
+//!   assert_eq!(n.push_str("defghij"), "defghij"); // nothing pushed
+//!   assert_eq!(n, "abc");
+//!   type Label = zstr<8, Saturate>;
+//!   assert_eq!(Label::make("abcdefghij"), "abcd…");
+//! ```
+//! The policy applies to the constructors that cannot report failure
+//! (`make`, `resize`, `Add`, `FromIterator`) as well as to `push`,
+//! `push_str`, `push_char`, [Extend] and [core::fmt::Write] (and therefore
+//! [crate::str_format!]).  The `try_make` family of constructors,
+//! `TryFrom` and [core::str::FromStr] are not affected: they always return
+//! a [crate::CapacityError] on overflow.
+//!
+//! Because the policy is also observed by the const constructors, an
+//! oversized literal is rejected at compile time under [Strict] or [Panic]:
+//! ```compile_fail
+//!   # use fixedstr::*;
+//!   const CODE: zstr<4, Strict> = zstr::const_make("too long");
+//!   let c = CODE;
+//! ```
+
+use core::fmt::Debug;
+use core::hash::Hash;
+
+/// The behaviors that an [OverflowPolicy] can select.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum OverflowMode {
+    /// keep as much as fits, cutting at a character boundary
+    Truncate,
+    /// keep nothing of the overflowing input: operations that can report
+    /// failure (`push_str`, `push_char`, `write_str`) leave the string
+    /// unchanged, and those that cannot (`make`, `Add`, `resize`,
+    /// `FromIterator`, `Extend`) panic
+    Strict,
+    /// panic on any overflow
+    Panic,
+    /// truncate, and mark the truncation by ending the string with an
+    /// ellipsis (`…`).  Capacities smaller than the ellipsis simply
+    /// truncate.
+    Saturate,
+}
+
+/// Implemented by the marker types [Truncate], [Strict], [Panic] and
+/// [Saturate] that select an [OverflowMode] as a type parameter.
+pub trait OverflowPolicy: Copy + Default + Eq + Hash + Debug + 'static {
+    /// the behavior selected by the policy
+    const MODE: OverflowMode;
+}
+
+/// Overflow policy that silently truncates at the last character
+/// boundary that fits.  This is the default policy of all types.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Truncate;
+
+/// Alias for [Truncate]: truncation never cuts a multibyte character
+/// in half, so the two policies are the same.
+pub type TruncateAtCharBoundary = Truncate;
+
+/// Overflow policy that rejects overflowing input in its entirety.  See
+/// [OverflowMode::Strict].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Strict;
+
+/// Overflow policy that panics whenever the capacity is exceeded.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Panic;
+
+/// Overflow policy that truncates and ends the string with an ellipsis
+/// (`…`) to show that it was truncated.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Saturate;
+
+impl OverflowPolicy for Truncate {
+    const MODE: OverflowMode = OverflowMode::Truncate;
+}
+impl OverflowPolicy for Strict {
+    const MODE: OverflowMode = OverflowMode::Strict;
+}
+impl OverflowPolicy for Panic {
+    const MODE: OverflowMode = OverflowMode::Panic;
+}
+impl OverflowPolicy for Saturate {
+    const MODE: OverflowMode = OverflowMode::Saturate;
+}
+
+/// the marker appended by the [Saturate] policy
+pub(crate) const ELLIPSIS: &str = "\u{2026}";
+
+/// Decides how to push `src` onto a string of length `len` and capacity
+/// `cap` under the given mode.  Returns the number of bytes of the
+/// existing string to keep (the caller must back off to a character
+/// boundary), the number of bytes of `src` to append, and whether to
+/// append an [ELLIPSIS] afterwards.  `fallible` is false for callers that
+/// cannot report an incomplete push, in which case [Strict] panics.
+pub(crate) const fn plan_push(
+    len: usize,
+    src: &[u8],
+    cap: usize,
+    mode: OverflowMode,
+    fallible: bool,
+) -> (usize, usize, bool) {
+    if len + src.len() <= cap {
+        return (len, src.len(), false);
+    }
+    match mode {
+        OverflowMode::Truncate => (len, crate::floor_char_boundary(src, cap - len), false),
+        OverflowMode::Strict => {
+            if !fallible {
+                panic!("string capacity exceeded under Strict overflow policy");
+            }
+            (len, 0, false)
+        }
+        OverflowMode::Panic => panic!("string capacity exceeded under Panic overflow policy"),
+        OverflowMode::Saturate => {
+            let room = cap.saturating_sub(ELLIPSIS.len());
+            if cap < ELLIPSIS.len() {
+                (len, crate::floor_char_boundary(src, cap - len), false)
+            } else if len > room {
+                (room, 0, true)
+            } else {
+                (len, crate::floor_char_boundary(src, room - len), true)
+            }
+        }
+    }
+} //plan_push
+
+/// copies the first `n` bytes of `src` into `dst` starting at index `at`,
+/// in a const context
+pub(crate) const fn copy_bytes(dst: &mut [u8], at: usize, src: &[u8], n: usize) {
+    let mut i = 0;
+    while i < n {
+        dst[at + i] = src[i];
+        i += 1;
+    }
+}
@@ -0,0 +1,341 @@
+//! Patterns for the search, replace and split methods of [crate::tstr],
+//! [crate::zstr], `fstr` and `cstr`, and the [SplitInto] iterator.
+
+use crate::{CapacityError, FixedstrMake};
+use core::marker::PhantomData;
+use core::ops::Range;
+
+/// A pattern that can be searched for in a `&str`: a `&str`, a `char`, or
+/// a slice or array of `char`s that matches any of its characters.  This
+/// is a stand-in for `core::str::pattern::Pattern`, which is not stable.
+///
+/// Patterns are accepted by the `replace`, `replacen`, `remove_matches`,
+/// `split_once`, `rsplit_once`, `strip_prefix` and `strip_suffix` methods
+/// of [crate::tstr], [crate::zstr] and `fstr`.  These methods take the place
+/// of the `str` methods of the same names but return fixed strings instead
+/// of `&str` slices or heap-allocated `String`s, so they are also available
+/// under `no-alloc`:
+/// ```
+///   # use fixedstr::*;
+///   let s = str16::make("key = value");
+///   let (k, v) = s.split_once(" = ").unwrap();
+///   assert_eq!((k, v), (str16::make("key"), str16::make("value")));
+///   assert_eq!(s.replace(' ', ""), (str16::make("key=value"), false));
+///   let (r, truncated) = s.replace("e", "EEEE");
+///   assert!(truncated && r == "kEEEEy = valuEE");
+/// ```
+pub trait StrPattern {
+    /// byte range of the first match in `hay`, if any
+    fn find_in(&self, hay: &str) -> Option<Range<usize>>;
+
+    /// byte range of the last match in `hay`, if any
+    fn rfind_in(&self, hay: &str) -> Option<Range<usize>>;
+
+    /// byte range of the first match that starts in `a` and ends in `b`,
+    /// when the two are read as one string.  Patterns that only match
+    /// single characters never straddle the two.
+    fn find_straddling(&self, a: &str, b: &str) -> Option<Range<usize>> {
+        let _ = (a, b);
+        None
+    }
+}
+
+impl StrPattern for &str {
+    fn find_in(&self, hay: &str) -> Option<Range<usize>> {
+        hay.find(*self).map(|i| i..i + self.len())
+    }
+    fn rfind_in(&self, hay: &str) -> Option<Range<usize>> {
+        hay.rfind(*self).map(|i| i..i + self.len())
+    }
+    fn find_straddling(&self, a: &str, b: &str) -> Option<Range<usize>> {
+        let p = *self;
+        (a.len().saturating_sub(p.len().saturating_sub(1))..a.len())
+            .find(|&i| {
+                a.is_char_boundary(i)
+                    && p.starts_with(&a[i..])
+                    && b.starts_with(&p[a.len() - i..])
+            })
+            .map(|i| i..i + p.len())
+    }
+}
+
+impl StrPattern for char {
+    fn find_in(&self, hay: &str) -> Option<Range<usize>> {
+        hay.find(*self).map(|i| i..i + self.len_utf8())
+    }
+    fn rfind_in(&self, hay: &str) -> Option<Range<usize>> {
+        hay.rfind(*self).map(|i| i..i + self.len_utf8())
+    }
+}
+
+// range of the character starting at byte i
+fn char_at(hay: &str, i: usize) -> Range<usize> {
+    i..i + hay[i..].chars().next().map_or(0, char::len_utf8)
+}
+
+impl StrPattern for &[char] {
+    fn find_in(&self, hay: &str) -> Option<Range<usize>> {
+        hay.find(*self).map(|i| char_at(hay, i))
+    }
+    fn rfind_in(&self, hay: &str) -> Option<Range<usize>> {
+        hay.rfind(*self).map(|i| char_at(hay, i))
+    }
+}
+
+impl<const K: usize> StrPattern for [char; K] {
+    fn find_in(&self, hay: &str) -> Option<Range<usize>> {
+        hay.find(self).map(|i| char_at(hay, i))
+    }
+    fn rfind_in(&self, hay: &str) -> Option<Range<usize>> {
+        hay.rfind(self).map(|i| char_at(hay, i))
+    }
+}
+
+/// A predicate such as `char::is_numeric` matches any character for which
+/// it returns true.
+impl StrPattern for fn(char) -> bool {
+    fn find_in(&self, hay: &str) -> Option<Range<usize>> {
+        hay.find(*self).map(|i| char_at(hay, i))
+    }
+    fn rfind_in(&self, hay: &str) -> Option<Range<usize>> {
+        hay.rfind(*self).map(|i| char_at(hay, i))
+    }
+}
+
+#[cfg(not(feature = "no-alloc"))]
+impl StrPattern for &alloc::string::String {
+    fn find_in(&self, hay: &str) -> Option<Range<usize>> {
+        self.as_str().find_in(hay)
+    }
+    fn rfind_in(&self, hay: &str) -> Option<Range<usize>> {
+        self.as_str().rfind_in(hay)
+    }
+    fn find_straddling(&self, a: &str, b: &str) -> Option<Range<usize>> {
+        self.as_str().find_straddling(a, b)
+    }
+}
+
+#[cfg(not(feature = "no-alloc"))]
+extern crate alloc;
+
+/// Builds a `T` from `s` with the first `limit` matches of `pat` replaced
+/// by `to`, following [str::replacen] (an empty pattern matches between
+/// every pair of characters).  Returns the new string and whether it was
+/// truncated to fit its capacity; nothing is pushed after the first push
+/// that does not fit.
+pub(crate) fn replace_into<T: FixedstrMake, Pat: StrPattern>(
+    s: &str,
+    pat: Pat,
+    to: &str,
+    limit: usize,
+) -> (T, bool) {
+    let mut out = T::new();
+    let mut rest = s;
+    let mut count = 0;
+    let mut fits = true;
+    while fits && count < limit {
+        let Some(m) = pat.find_in(rest) else { break };
+        fits = out.push_str(&rest[..m.start]).is_empty() && out.push_str(to).is_empty();
+        count += 1;
+        if !m.is_empty() {
+            rest = &rest[m.end..];
+        } else if let Some(c) = rest[m.start..].chars().next() {
+            // step over one character after an empty match
+            fits = fits && out.push_char(c);
+            rest = &rest[m.start + c.len_utf8()..];
+        } else {
+            rest = "";
+            break;
+        }
+    }
+    if fits {
+        fits = out.push_str(rest).is_empty();
+    }
+    (out, !fits)
+} //replace_into
+
+/// splits `s` around the match `m`
+pub(crate) fn split_around<T: FixedstrMake>(s: &str, m: Range<usize>) -> (T, T) {
+    (T::make(&s[..m.start]), T::make(&s[m.end..]))
+}
+
+/// first match of `pat` in the concatenation of `a` and `b`, starting at
+/// byte `from`
+fn find_in_pair<Pat: StrPattern>(pat: &Pat, a: &str, b: &str, from: usize) -> Option<Range<usize>> {
+    let shift = |m: Range<usize>, by: usize| m.start + by..m.end + by;
+    if from > a.len() {
+        let from = from - a.len();
+        return pat.find_in(&b[from..]).map(|m| shift(m, a.len() + from));
+    }
+    pat.find_in(&a[from..])
+        .map(|m| shift(m, from))
+        .or_else(|| pat.find_straddling(&a[from..], b).map(|m| shift(m, from)))
+        .or_else(|| pat.find_in(b).map(|m| shift(m, a.len())))
+} //find_in_pair
+
+/// Iterator over the parts of a string separated by a [StrPattern], which
+/// yields each part as a fixed string of type `T` instead of a `&str`.  It
+/// is returned by the `split_into`, `splitn_into`, `split_whitespace_into`
+/// and `lines_into` methods of [crate::tstr], [crate::zstr], `fstr` and
+/// `cstr`, which follow the `str` methods of the same names (without the
+/// `_into`).  A part that exceeds the capacity of `T` is handled as by
+/// [FixedstrMake::make]; use [SplitInto::try_next] or
+/// [SplitInto::collect_array] to detect this instead.
+/// ```
+///   # use fixedstr::*;
+///   let s = str32::make("add r1, r2,  0x10");
+///   let mut words = s.split_whitespace_into::<str4>();
+///   assert_eq!(words.next(), Some(str4::make("add")));
+///   assert_eq!(words.try_next(), Some(Ok(str4::make("r1,"))));
+///   assert_eq!(s.split_into::<str8>(',').count(), 3);
+///   let (args, count) = s.split_into::<zstr<8>>(", ").collect_array::<4>().unwrap();
+///   assert_eq!((count, args[2].as_str()), (3, " 0x10"));
+///   assert!(s.splitn_into::<str8>(2, ' ').collect_array::<4>().is_err());
+/// ```
+pub struct SplitInto<'a, T, Pat> {
+    first: &'a str,
+    second: &'a str,
+    pat: Pat,
+    remaining: usize,
+    skip_empty: bool,
+    lines: bool,
+    after_empty: bool,
+    done: bool,
+    item: PhantomData<fn() -> T>,
+} //SplitInto
+
+impl<'a, T: FixedstrMake, Pat: StrPattern> SplitInto<'a, T, Pat> {
+    /// iterator over the parts of `first` followed by `second`, of which at
+    /// most `limit` are returned, the last one being the unsplit remainder
+    pub(crate) fn new(first: &'a str, second: &'a str, pat: Pat, limit: usize) -> Self {
+        let (first, second) = if first.is_empty() { (second, "") } else { (first, second) };
+        SplitInto {
+            first,
+            second,
+            pat,
+            remaining: limit,
+            skip_empty: false,
+            lines: false,
+            after_empty: false,
+            done: false,
+            item: PhantomData,
+        }
+    }
+
+    // split_whitespace: empty parts are skipped
+    pub(crate) fn skipping_empty(mut self) -> Self {
+        self.skip_empty = true;
+        self
+    }
+
+    // lines: no final empty part, and a '\r' before each '\n' is removed
+    pub(crate) fn splitting_lines(mut self) -> Self {
+        self.lines = true;
+        self
+    }
+
+    // removes the first n bytes of the remaining string
+    fn advance(&mut self, n: usize) {
+        if n < self.first.len() {
+            self.first = &self.first[n..];
+        } else {
+            self.first = &self.second[n - self.first.len()..];
+            self.second = "";
+        }
+    }
+
+    // the next part, which may be split in two by the wrap point of a cstr
+    fn next_segments(&mut self) -> Option<(&'a str, &'a str)> {
+        loop {
+            if self.done || self.remaining == 0 || (self.lines && self.first.is_empty()) {
+                return None;
+            }
+            self.remaining -= 1;
+            let (a, b) = (self.first, self.second);
+            // after an empty match, the next match must start at least one
+            // character later, as with str::split("")
+            let from = match a.chars().next() {
+                Some(c) if self.after_empty => c.len_utf8(),
+                None if self.after_empty => 1,
+                _ => 0,
+            };
+            let m = if self.remaining == 0 || from > a.len() + b.len() {
+                None
+            } else {
+                find_in_pair(&self.pat, a, b, from)
+            };
+            let (mut x, mut y) = match &m {
+                Some(m) if m.start <= a.len() => (&a[..m.start], ""),
+                Some(m) => (a, &b[..m.start - a.len()]),
+                None => (a, b),
+            };
+            let matched = m.is_some();
+            match m {
+                Some(m) => {
+                    self.after_empty = m.is_empty();
+                    self.advance(m.end);
+                }
+                None => self.done = true,
+            }
+            // only a '\r' that precedes a '\n' ends a line
+            if self.lines && matched {
+                match y.strip_suffix('\r') {
+                    Some(t) => y = t,
+                    None if y.is_empty() => x = x.strip_suffix('\r').unwrap_or(x),
+                    None => {}
+                }
+            }
+            if !(self.skip_empty && x.is_empty() && y.is_empty()) {
+                return Some((x, y));
+            }
+        }
+    } //next_segments
+
+    /// version of [Iterator::next] that returns a [CapacityError] instead
+    /// of truncating a part that does not fit in `T`
+    pub fn try_next(&mut self) -> Option<Result<T, CapacityError>> {
+        self.next_segments().map(|(a, b)| {
+            let mut t = T::try_make(a)?;
+            if !b.is_empty() && !t.push_str(b).is_empty() {
+                return Err(CapacityError::Overflow {
+                    required: a.len() + b.len(),
+                    available: t.capacity(),
+                });
+            }
+            Ok(t)
+        })
+    } //try_next
+
+    /// collects the parts into an array of `K` strings, returning the
+    /// array and the number of parts; the remaining entries are empty.
+    /// Returns a [CapacityError] if a part does not fit in `T`, or if there
+    /// are more than `K` parts, in which case `required` is the number
+    /// of parts.
+    pub fn collect_array<const K: usize>(mut self) -> Result<([T; K], usize), CapacityError> {
+        let mut parts: [T; K] = core::array::from_fn(|_| T::new());
+        let mut count = 0;
+        while let Some(part) = self.try_next() {
+            let part = part?;
+            if count == K {
+                return Err(CapacityError::Overflow {
+                    required: count + 1 + self.count(),
+                    available: K,
+                });
+            }
+            parts[count] = part;
+            count += 1;
+        }
+        Ok((parts, count))
+    } //collect_array
+} //impl SplitInto
+
+impl<T: FixedstrMake, Pat: StrPattern> Iterator for SplitInto<'_, T, Pat> {
+    type Item = T;
+    fn next(&mut self) -> Option<T> {
+        self.next_segments().map(|(a, b)| {
+            let mut t = T::make(a);
+            t.push_str(b);
+            t
+        })
+    }
+}
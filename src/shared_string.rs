@@ -17,15 +17,16 @@ use crate::zstr;
 use crate::fstr;
 
 use crate::{str12, str128, str16, str192, str24, str256, str32, str4, str48, str64, str8, str96};
+use crate::CapacityError;
 use core::cmp::{min, Ordering};
 use core::ops::Add;
 
 ////////////////////////////////////////////////////////////////////////
 ///////////// RC experiments
 extern crate alloc;
-use alloc::rc::Rc;
+use alloc::rc::{Rc, Weak};
 use alloc::string::String;
-use core::cell::RefCell;
+use core::cell::{BorrowError, BorrowMutError, Ref, RefCell};
 
 /// **This type is only available with the 'shared-str' option.**
 /// A `Sharedstr` uses [Rc] and [RefCell] underneath to allow pointers to a [crate::Flexstr]
@@ -34,10 +35,16 @@ use core::cell::RefCell;
 /// `tstr<N>` or by an owned string if its length is greater than N-1, for N up
 /// to 256.
 ///
-/// **WARNING:**  this type uses certain unsafe features and allows mutations of
-/// a shared string from multiple pointers, while allowing continued use of the
-/// pointers after mutation. This violates a basic principle of the Rust borrow
-/// checker.  Use with caution.
+/// # Borrowing
+/// A shared string can be mutated through any of its pointers.  To read it
+/// as a `&str`, call [Sharedstr::borrow], which returns a guard that
+/// dereferences to `str`.  As with a [RefCell], any attempt to mutate the
+/// string (through any pointer) while such a guard is alive will **panic**:
+/// this applies to every function that takes `&mut self`.
+/// [Sharedstr::try_borrow] is the non-panicking version for reads, and
+/// [Sharedstr::try_push_str], [Sharedstr::try_push], [Sharedstr::try_set]
+/// and [Sharedstr::try_truncate] are non-panicking versions of the most
+/// common mutations.
 /// Example:
 /// ```
 ///  # use fixedstr::*;
@@ -46,9 +53,14 @@ use core::cell::RefCell;
 ///  b.push('3');
 ///  assert!( a == "abc123" );
 ///  assert!( a==b && a.ptr_eq(&b) );
+///  let guard = a.borrow();
+///  assert!(guard.starts_with("abc") && b.try_borrow().is_ok());
+///  drop(guard);
+///  b.push('4');   // would panic if guard were still alive
 /// ```
 /// Note that `==` always compares the contents while `ptr_eq` compares for
-/// pointer-equality.
+/// pointer-equality.  The unchecked [Sharedstr::as_str] of earlier versions is
+/// still available as an `unsafe` function.
 /// This type **does not support serde**, as expected of shared
 /// pointers.  Convert to another type of string for serialization.
 #[derive(Eq, Clone)]
@@ -58,28 +70,16 @@ pub struct Sharedstr<const N: usize = 32> {
 impl<const N: usize> Sharedstr<N> {
     /// Creates a new `Sharedstr<N>` with given &str.
     pub fn make(s: &str) -> Self {
-        if s.len() < N && N <= 256 {
-            Sharedstr {
-                inner: Rc::new(RefCell::new(fixed(tstr::<N>::from(s)))),
-            }
-        } else {
-            Sharedstr {
-                inner: Rc::new(RefCell::new(owned(String::from(s)))),
-            }
+        Sharedstr {
+            inner: Rc::new(RefCell::new(Strunion::make(s))),
         }
     } //make
 
     /// Creates a `Sharedstr<N>` by consuming a given string.  However, if the
     /// string has length less than N, then a fixed representation will be used.
     pub fn from_string(s: String) -> Self {
-        if s.len() < N && N <= 256 {
-            Sharedstr {
-                inner: Rc::new(RefCell::new(fixed(tstr::<N>::from(&s[..])))),
-            }
-        } else {
-            Sharedstr {
-                inner: Rc::new(RefCell::new(owned(s))),
-            }
+        Sharedstr {
+            inner: Rc::new(RefCell::new(Strunion::from_string(s))),
         }
     }
 
@@ -90,15 +90,14 @@ impl<const N: usize> Sharedstr<N> {
         }
     }
 
-    /*
-      #[cfg(feature="serde")]
-      /// this function is only added for uniformity in serde implementation
-      pub fn try_make(s: &str) -> Result<Sharedstr<N>, &str> {
-           Ok(Sharedstr::make(s))
-        }
-    */
+    /// Provided for uniformity with the other string types: since a
+    /// `Sharedstr` never truncates, this function always succeeds.
+    pub fn try_make(s: &str) -> Result<Sharedstr<N>, CapacityError> {
+        Ok(Sharedstr::make(s))
+    }
 
     /// length of the string in bytes. This is a constant-time operation.
+    #[allow(clippy::len_without_is_empty)] // is_empty is provided by Fixedstr
     pub fn len(&self) -> usize {
         match &*self.inner.borrow() {
             fixed(s) => s.len(),
@@ -106,8 +105,35 @@ impl<const N: usize> Sharedstr<N> {
         } //match
     } //len
 
-    /// converts to `&str` type (may technically panic).
-    pub fn as_str(&self) -> &str {
+    /// returns a guard that dereferences to the contents of the string.
+    /// The string cannot be mutated, through this or any other pointer,
+    /// while the guard is alive: such mutations will panic.  **Panics** if
+    /// the string is currently being mutated.
+    pub fn borrow(&self) -> Ref<'_, str> {
+        Ref::map(self.inner.borrow(), |u| match u {
+            fixed(s) => s.as_str(),
+            owned(s) => s.as_str(),
+        })
+    } //borrow
+
+    /// non-panicking version of [Sharedstr::borrow]
+    pub fn try_borrow(&self) -> Result<Ref<'_, str>, BorrowError> {
+        self.inner.try_borrow().map(|r| {
+            Ref::map(r, |u| match u {
+                fixed(s) => s.as_str(),
+                owned(s) => s.as_str(),
+            })
+        })
+    } //try_borrow
+
+    /// converts to `&str` type without keeping the string borrowed.
+    /// Prefer [Sharedstr::borrow].
+    /// # Safety
+    /// The returned `&str` is not protected from mutations: the caller
+    /// must ensure that the string is not mutated, through this or any
+    /// other pointer, while the `&str` is alive, as a mutation may
+    /// reallocate the string and leave the `&str` dangling.
+    pub unsafe fn as_str(&self) -> &str {
         unsafe {
             match self.inner.as_ptr().as_ref().unwrap() {
                 fixed(s) => s.as_str(),
@@ -116,8 +142,10 @@ impl<const N: usize> Sharedstr<N> {
         } //unsafe
     }
 
-    /// version of [Flexstr::as_str] that does not call `unwrap`
-    pub fn as_str_utf8(&self) -> Result<&str,core::str::Utf8Error>
+    /// version of [Sharedstr::as_str] that does not call `unwrap`
+    /// # Safety
+    /// same requirement as [Sharedstr::as_str]
+    pub unsafe fn as_str_utf8(&self) -> Result<&str,core::str::Utf8Error>
     {
         unsafe {
             match self.inner.as_ptr().as_ref().unwrap() {
@@ -142,8 +170,10 @@ impl<const N: usize> Sharedstr<N> {
     } //charlen
 
     /// alias for [Self::as_str]
-    pub fn to_str(&self) -> &str {
-        self.as_str()
+    /// # Safety
+    /// same requirement as [Sharedstr::as_str]
+    pub unsafe fn to_str(&self) -> &str {
+        unsafe { self.as_str() }
     } //to_str
 
     /// retrieves a copy of the underlying fixed string, if it is a fixed string.
@@ -165,9 +195,10 @@ impl<const N: usize> Sharedstr<N> {
 
     /// if the underlying representation of the string is an owned string,
     /// return the owned string, leaving an empty string in its place.
+    /// **Panics** if the string is borrowed (see [Sharedstr#borrowing]).
     pub fn take_string(&mut self) -> Option<String> {
         if let (ss @ owned(_)) = &mut *self.inner.borrow_mut() {
-            let mut temp = fixed(tstr::new());
+            let mut temp = Strunion::make("");
 
             core::mem::swap(ss, &mut temp);
             if let owned(t) = temp {
@@ -181,6 +212,7 @@ impl<const N: usize> Sharedstr<N> {
     } //take_owned
 
     /// this function returns a possibly cloned string
+    #[allow(clippy::inherent_to_string_shadow_display)] // no ToString import needed
     pub fn to_string(self) -> String {
         match &*self.inner.borrow() {
             fixed(s) => s.to_string(),
@@ -190,7 +222,7 @@ impl<const N: usize> Sharedstr<N> {
 
     /// returns the nth char of the string, if it exists
     pub fn nth(&self, n: usize) -> Option<char> {
-        self.to_str().chars().nth(n)
+        self.borrow().chars().nth(n)
     }
 
     /// returns the nth byte of the string as a char.  This function
@@ -211,35 +243,43 @@ impl<const N: usize> Sharedstr<N> {
     /// returns a u8-slice that represents the underlying string. The first
     /// byte of the slice is **not** the length of the string regarless of
     /// the internal representation.
-    pub fn as_bytes(&self) -> &[u8] {
-        self.as_str().as_bytes()
+    /// # Safety
+    /// same requirement as [Sharedstr::as_str]
+    pub unsafe fn as_bytes(&self) -> &[u8] {
+        unsafe { self.as_str().as_bytes() }
     } //as_bytes
 
     /// changes a character at character position i to c.  This function
     /// requires that c is in the same character class (ascii or unicode)
     /// as the char being replaced.  It never shuffles the bytes underneath.
     /// The function returns true if the change was successful.
+    /// **Panics** if the string is borrowed (see [Sharedstr#borrowing]).
     pub fn set(&mut self, i: usize, c: char) -> bool {
         match &mut *self.inner.borrow_mut() {
             fixed(s) => s.set(i, c),
             owned(s) => unsafe {
-                let ref mut cbuf = [0u8; 4];
+                let cbuf = &mut [0u8; 4];
                 c.encode_utf8(cbuf);
                 let clen = c.len_utf8();
-                if let Some((bi, rc)) = s.char_indices().nth(i) {
-                    if clen == rc.len_utf8() {
-                        s.as_bytes_mut()[bi..bi + clen].copy_from_slice(&cbuf[..clen]);
-                        //self.chrs[bi + 1..bi + clen + 1].copy_from_slice(&cbuf[..clen]);
-                        //for k in 0..clen {self.chrs[bi+k+1] = cbuf[k];}
-                        return true;
-                    }
+                if let Some((bi, rc)) = s.char_indices().nth(i)
+                    && clen == rc.len_utf8()
+                {
+                    s.as_bytes_mut()[bi..bi + clen].copy_from_slice(&cbuf[..clen]);
+                    //self.chrs[bi + 1..bi + clen + 1].copy_from_slice(&cbuf[..clen]);
+                    //for k in 0..clen {self.chrs[bi+k+1] = cbuf[k];}
+                    return true;
                 }
-                return false;
+                false
             },
         } //match
     } //set
 
     /// version of [Sharedstr::set] that assumes that the char is a single byte. Sets the char at the given byte index. Does not check for index bounds. This function is designed to be fast.
+    /// **Panics** if the string is borrowed (see [Sharedstr#borrowing]).
+    ///
+    /// # Safety
+    /// `c` must be ascii and `i` must be the byte index of a single-byte
+    /// character inside the string.
     pub unsafe fn set_byte_char(&mut self, i:usize, c:char) {
        match &mut *self.inner.borrow_mut() {
          fixed(s) => s.set_byte_char(i,c),
@@ -263,6 +303,7 @@ impl<const N: usize> Sharedstr<N> {
 
     /// applies the destructive closure only if the internal representation
     /// is a fixed string
+    /// **Panics** if the string is borrowed (see [Sharedstr#borrowing]).
     pub fn if_fixed<F>(&mut self, f: F)
     where
         F: FnOnce(&mut tstr<N>),
@@ -274,6 +315,7 @@ impl<const N: usize> Sharedstr<N> {
 
     /// applies the destructive closure only if the internal representation
     /// is a fixed string
+    /// **Panics** if the string is borrowed (see [Sharedstr#borrowing]).
     pub fn if_owned<F>(&mut self, f: F)
     where
         F: FnOnce(&mut str),
@@ -297,6 +339,7 @@ impl<const N: usize> Sharedstr<N> {
     } //map
 
     /// version of [Sharedstr::map_or] accepting FnMut closures
+    /// **Panics** if the string is borrowed (see [Sharedstr#borrowing]).
     pub fn map_or_mut<F, G, U>(&mut self, f: &mut F, g: &mut G) -> U
     where
         F: FnMut(&mut tstr<N>) -> U,
@@ -312,6 +355,7 @@ impl<const N: usize> Sharedstr<N> {
     /// switching to the owned-String representation if necessary.  The function
     /// returns true if the resulting string uses a `tstr<N>` type, and
     /// false if the representation is an owned string.
+    /// **Panics** if the string is borrowed (see [Sharedstr#borrowing]).
     pub fn push_str(&mut self, s: &str) -> bool {
         let mut replacer = None;
         let answer;
@@ -340,6 +384,7 @@ impl<const N: usize> Sharedstr<N> {
     /// appends string with a single character, switching to the String
     /// representation if necessary.  Returns true if resulting string
     /// remains fixed.
+    /// **Panics** if the string is borrowed (see [Sharedstr#borrowing]).
     pub fn push(&mut self, c: char) -> bool {
         let clen = c.len_utf8();
         let answer;
@@ -370,11 +415,13 @@ impl<const N: usize> Sharedstr<N> {
     } //push
 
     /// alias for push
+    /// **Panics** if the string is borrowed (see [Sharedstr#borrowing]).
     pub fn push_char(&mut self, c: char) -> bool {
         self.push(c)
     }
 
     /// remove and return last character in string, if it exists
+    /// **Panics** if the string is borrowed (see [Sharedstr#borrowing]).
     pub fn pop(&mut self) -> Option<char> {
         if self.len() == 0 {
             return None;
@@ -391,7 +438,7 @@ impl<const N: usize> Sharedstr<N> {
             owned(s) => {
                 // change representation
                 answer = s.pop();
-                replacer = Some(fixed(tstr::from(&s)));
+                replacer = Some(Strunion::from_string(core::mem::take(s)));
             }
         } //match
         if let Some(r) = replacer {
@@ -401,6 +448,7 @@ impl<const N: usize> Sharedstr<N> {
     } //pop
 
     /// alias for [Self::pop]
+    /// **Panics** if the string is borrowed (see [Sharedstr#borrowing]).
     pub fn pop_char(&mut self) -> Option<char> {
         self.pop()
     }
@@ -409,7 +457,8 @@ impl<const N: usize> Sharedstr<N> {
     /// returning true if the truncated string is fixed, and false if owned.
     /// The operation has no effect if n is larger than the length of the
     /// string.  The operation will **panic** if n is not on a character
-    /// boundary, similar to [String::truncate].
+    /// boundary, similar to [String::truncate], or if the string is
+    /// borrowed (see [Sharedstr#borrowing]).
     pub fn truncate(&mut self, n: usize) -> bool {
         let mut replacer = None;
         let answer;
@@ -421,16 +470,13 @@ impl<const N: usize> Sharedstr<N> {
             fixed(_) => {
                 answer = true;
             }
-            owned(s) if n < N => {
-                assert!(s.is_char_boundary(n));
-                replacer = Some(fixed(tstr::<N>::from(&s[..n])));
-                answer = true;
-            }
             owned(s) => {
                 if n < s.len() {
                     s.truncate(n);
                 }
-                answer = false;
+                let r = Strunion::from_string(core::mem::take(s));
+                answer = matches!(r, fixed(_));
+                replacer = Some(r);
             }
         } //match
         if let Some(r) = replacer {
@@ -440,6 +486,7 @@ impl<const N: usize> Sharedstr<N> {
     } //truncate
 
     /// resets string to empty
+    /// **Panics** if the string is borrowed (see [Sharedstr#borrowing]).
     pub fn clear(&mut self) {
         let mut replacer = None;
         match &mut *self.inner.borrow_mut() {
@@ -447,7 +494,7 @@ impl<const N: usize> Sharedstr<N> {
                 s.clear();
             }
             owned(s) => {
-                replacer = Some(fixed(tstr::default()));
+                replacer = Some(Strunion::make(""));
             }
         }
         if let Some(r) = replacer {
@@ -455,6 +502,41 @@ impl<const N: usize> Sharedstr<N> {
         }
     } //clear
 
+    // fails if the string is borrowed, so that the mutation that follows
+    // cannot panic: the returned guard is dropped immediately
+    fn check_unborrowed(&self) -> Result<(), BorrowMutError> {
+        self.inner.try_borrow_mut().map(|_| ())
+    }
+
+    /// non-panicking version of [Sharedstr::push_str], which returns an
+    /// error, leaving the string unchanged, if it is currently borrowed
+    pub fn try_push_str(&mut self, s: &str) -> Result<bool, BorrowMutError> {
+        self.check_unborrowed()?;
+        Ok(self.push_str(s))
+    }
+
+    /// non-panicking version of [Sharedstr::push], which returns an
+    /// error, leaving the string unchanged, if it is currently borrowed
+    pub fn try_push(&mut self, c: char) -> Result<bool, BorrowMutError> {
+        self.check_unborrowed()?;
+        Ok(self.push(c))
+    }
+
+    /// non-panicking version of [Sharedstr::set], which returns an
+    /// error, leaving the string unchanged, if it is currently borrowed
+    pub fn try_set(&mut self, i: usize, c: char) -> Result<bool, BorrowMutError> {
+        self.check_unborrowed()?;
+        Ok(self.set(i, c))
+    }
+
+    /// version of [Sharedstr::truncate] which returns an error, leaving the
+    /// string unchanged, if it is currently borrowed.  It still panics if
+    /// n is not on a character boundary.
+    pub fn try_truncate(&mut self, n: usize) -> Result<bool, BorrowMutError> {
+        self.check_unborrowed()?;
+        Ok(self.truncate(n))
+    }
+
     /// returns string corresponding to slice indices as a copy or clone.
     pub fn substr(&self, start: usize, end: usize) -> Sharedstr<N> {
         match &*self.inner.borrow() {
@@ -475,6 +557,7 @@ impl<const N: usize> Sharedstr<N> {
     ///   let extras = fs.split_off();
     ///   assert!( &fs=="abc" && &extras=="defg" && fs.is_fixed());
     /// ```
+    /// **Panics** if the string is borrowed (see [Sharedstr#borrowing]).
     pub fn split_off(&mut self) -> String {
         let answer;
         let mut replacer = None;
@@ -482,9 +565,13 @@ impl<const N: usize> Sharedstr<N> {
             fixed(s) => {
                 answer = String::default();
             }
-            owned(s) => {
-                answer = String::from(&s[N - 1..]);
-                replacer = Some(fixed(tstr::<N>::from(&s[..N - 1])));
+            owned(s) if N <= 256 => {
+                let k = crate::floor_char_boundary(s.as_bytes(), N - 1);
+                answer = String::from(&s[k..]);
+                replacer = Some(Strunion::make(&s[..k]));
+            }
+            owned(_) => {
+                answer = String::default();
             }
         } //match
         if let Some(r) = replacer {
@@ -506,7 +593,7 @@ impl<const N: usize> Sharedstr<N> {
 
     /// creates a new, non-shared copy of the string
     pub fn deep_clone(&self) -> Self {
-        Sharedstr::from(self.as_str())
+        Sharedstr::make(&self.borrow())
     }
 
     /// returns the number of shared references to this string (strong `Rc`
@@ -515,7 +602,21 @@ impl<const N: usize> Sharedstr<N> {
         Rc::strong_count(&self.inner)
     }
 
+    /// creates a [WeakSharedstr] pointing to this string, which does not
+    /// keep the string alive.
+    pub fn downgrade(&self) -> WeakSharedstr<N> {
+        WeakSharedstr {
+            inner: Rc::downgrade(&self.inner),
+        }
+    }
+
+    /// returns the number of [WeakSharedstr] pointers to this string
+    pub fn weak_count(&self) -> usize {
+        Rc::weak_count(&self.inner)
+    }
+
     /// in-place modification of ascii characters to lower-case
+    /// **Panics** if the string is borrowed (see [Sharedstr#borrowing]).
     pub fn make_ascii_lowercase(&mut self) {
         match &mut *self.inner.borrow_mut() {
             fixed(s) => {
@@ -528,6 +629,7 @@ impl<const N: usize> Sharedstr<N> {
     } //make_ascii_lowercase
 
     /// in-place modification of ascii characters to upper-case
+    /// **Panics** if the string is borrowed (see [Sharedstr#borrowing]).
     pub fn make_ascii_uppercase(&mut self) {
         match &mut *self.inner.borrow_mut() {
             fixed(s) => {
@@ -545,19 +647,20 @@ impl<const N: usize> Sharedstr<N> {
     where
         TA: AsRef<str>,
     {
-        if self.len() != other.as_ref().len() {
+        let this = self.borrow();
+        if this.len() != other.as_ref().len() {
             return false;
         }
         let obytes = other.as_ref().as_bytes();
-        let sbytes = self.as_bytes();
-        for i in 0..self.len() {
+        let sbytes = this.as_bytes();
+        for i in 0..sbytes.len() {
             let mut c = sbytes[i];
             if (c > 64 && c < 91) {
-                c = c | 32;
+                c |= 32;
             } // make lowercase
             let mut d = obytes[i];
             if (d > 64 && d < 91) {
-                d = d | 32;
+                d |= 32;
             } // make lowercase
             if c != d {
                 return false;
@@ -566,19 +669,13 @@ impl<const N: usize> Sharedstr<N> {
         true
     } //case_insensitive_eq
 
-    /// Decodes a UTF-16 encodeded slice. If a decoding error is encountered
-    /// or capacity exceeded, an `Err(s)` is returned where s is the
-    /// the encoded string up to the point of the error.
-    pub fn from_utf16(v: &[u16]) -> Result<Self, Self> {
+    /// Decodes a UTF-16 encodeded slice, returning an error if the slice
+    /// is not valid utf16.
+    pub fn from_utf16(v: &[u16]) -> Result<Self, CapacityError> {
+        crate::utf16_len(v)?;
         let mut s = Self::new();
-        for c in char::decode_utf16(v.iter().cloned()) {
-            if let Ok(c1) = c {
-                if !s.push_char(c1) {
-                    return Err(s);
-                }
-            } else {
-                return Err(s);
-            }
+        for c in char::decode_utf16(v.iter().cloned()).flatten() {
+            s.push(c);
         }
         Ok(s)
     } //from_utf16
@@ -587,7 +684,7 @@ impl<const N: usize> Sharedstr<N> {
 impl<const N: usize> Default for Sharedstr<N> {
     fn default() -> Self {
         Sharedstr {
-            inner: Rc::new(RefCell::new(fixed(tstr::<N>::default()))),
+            inner: Rc::new(RefCell::new(Strunion::make(""))),
         }
     }
 }
@@ -616,21 +713,6 @@ impl<const N: usize> Sharedstr<N> {
     } //to_flexstr
 } //impl Sharestr
 
-impl<const N: usize> core::ops::Deref for Sharedstr<N> {
-    type Target = str;
-    fn deref(&self) -> &Self::Target {
-        self.as_str()
-    }
-}
-
-/*
-impl<const N: usize> core::hash::Hash for Sharedstr<N> {
-    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
-        (&*self.inner.borrow()).hash(state)
-    }
-} //hash
-*/
-
 impl<T: AsRef<str> + ?Sized, const N: usize> core::convert::From<&T> for Sharedstr<N> {
     fn from(s: &T) -> Self {
         Self::make(s.as_ref())
@@ -650,61 +732,36 @@ impl<const N: usize> core::cmp::PartialOrd for Sharedstr<N> {
 
 impl<const N: usize> core::cmp::Ord for Sharedstr<N> {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.as_str().cmp(other.as_str())
+        self.borrow().cmp(&other.borrow())
     }
 }
 
-impl<const N: usize> core::convert::AsRef<str> for Sharedstr<N> {
-    fn as_ref(&self) -> &str {
-        self.as_str()
-    }
-}
-impl<const N: usize> core::convert::AsMut<str> for Sharedstr<N> {
-    fn as_mut(&mut self) -> &mut str {
-        unsafe {
-            match self.inner.as_ptr().as_mut().unwrap() {
-                fixed(f) => f.as_mut(),
-                owned(s) => s.as_mut(),
-            } //match
-        } //unsafe
-    } //as_mut
-}
-
 impl<const N: usize> PartialEq<&str> for Sharedstr<N> {
     fn eq(&self, other: &&str) -> bool {
-        &self.to_str() == other // see below
+        &*self.borrow() == *other
     } //eq
 }
 
-impl<const N: usize> PartialEq<&str> for &Sharedstr<N> {
-    fn eq(&self, other: &&str) -> bool {
-        &self.to_str() == other
-    } //eq
-}
-impl<'t, const N: usize> PartialEq<Sharedstr<N>> for &'t str {
+impl<const N: usize> PartialEq<Sharedstr<N>> for &str {
     fn eq(&self, other: &Sharedstr<N>) -> bool {
-        &other.to_str() == self
-    }
-}
-impl<'t, const N: usize> PartialEq<&Sharedstr<N>> for &'t str {
-    fn eq(&self, other: &&Sharedstr<N>) -> bool {
-        &other.to_str() == self
+        &*other.borrow() == *self
     }
 }
-
 impl<const N: usize> core::fmt::Debug for Sharedstr<N> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        f.pad(&self.to_str())
+        f.pad(&self.borrow())
     }
 } // Debug impl
 
 impl<const N: usize> core::fmt::Display for Sharedstr<N> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         //write!(f, "{}", self.to_str())
-        f.pad(self.to_str())
+        f.pad(&self.borrow())
     }
 }
 
+/// Appends to the string as [Sharedstr::push_str] does, and so **panics**
+/// if the string is currently borrowed via [Sharedstr::borrow].
 impl<const N: usize> core::fmt::Write for Sharedstr<N> {
     fn write_str(&mut self, s: &str) -> core::fmt::Result {
         self.push_str(s);
@@ -716,18 +773,30 @@ impl<const N: usize> core::convert::From<String> for Sharedstr<N> {
     /// *will consume owned string and convert it to a fixed
     /// representation if its length is less than N*
     fn from(s: String) -> Self {
-        if s.len() >= N {
-            Sharedstr {
-                inner: Rc::new(RefCell::new(owned(s))),
-            }
-        } else {
-            Sharedstr {
-                inner: Rc::new(RefCell::new(fixed(tstr::<N>::from(&s[..])))),
-            }
-        }
+        Sharedstr::from_string(s)
     }
 } //from String
 
+/// Collects characters or strings (see [crate::StrPiece]), switching to
+/// the owned-String representation if necessary: this never truncates.
+impl<T: crate::StrPiece, const N: usize> core::iter::FromIterator<T> for Sharedstr<N> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut s = Sharedstr::new();
+        s.extend(iter);
+        s
+    }
+}
+
+/// Pushes the items as [Sharedstr::push_str] does, and so **panics** if
+/// the string is currently borrowed via [Sharedstr::borrow].
+impl<T: crate::StrPiece, const N: usize> core::iter::Extend<T> for Sharedstr<N> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for piece in iter {
+            piece.with_str(|s| self.push_str(s));
+        }
+    }
+}
+
 impl<const M: usize> Sharedstr<M> {
     /// returns a copy/clone of the string with new fixed capacity N.
     /// Example:
@@ -741,7 +810,7 @@ impl<const M: usize> Sharedstr<M> {
     ///  assert!(a.is_owned());
     /// ```
     pub fn resize<const N: usize>(&self) -> Sharedstr<N> {
-        Sharedstr::from(self)
+        Sharedstr::make(&self.borrow())
     }
 }
 
@@ -778,7 +847,7 @@ impl<const N: usize> Add<&Sharedstr<N>> for &str {
     type Output = Sharedstr<N>;
     fn add(self, other: &Sharedstr<N>) -> Sharedstr<N> {
         let mut a2 = Sharedstr::from(self);
-        a2.push_str(other);
+        a2.push_str(&other.borrow());
         a2
     }
 } //Add &str on left
@@ -787,30 +856,155 @@ impl<const N: usize> Add<Sharedstr<N>> for &str {
     type Output = Sharedstr<N>;
     fn add(self, other: Sharedstr<N>) -> Sharedstr<N> {
         let mut a2 = Sharedstr::from(self);
-        a2.push_str(&other);
+        a2.push_str(&other.borrow());
         a2
     }
 } //Add &str on left
 
 impl<const N: usize> core::hash::Hash for Sharedstr<N> {
     fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
-        self.as_ref().hash(state);
+        self.borrow().hash(state);
     }
 } //hash
 
+/// The contents of a `Sharedstr` are behind a lock, so it cannot implement
+/// `Borrow<str>`: with the `equivalent` option, maps keyed by `Sharedstr`
+/// such as those of hashbrown and indexmap can be looked up by `&str`.
+#[cfg(feature = "equivalent")]
+impl<const N: usize> equivalent::Equivalent<Sharedstr<N>> for str {
+    fn equivalent(&self, key: &Sharedstr<N>) -> bool {
+        key == self
+    }
+}
+
 impl<const N: usize> core::cmp::PartialEq for Sharedstr<N> {
     fn eq(&self, other: &Self) -> bool {
-        self.as_ref() == other.as_ref()
+        *self.borrow() == *other.borrow()
     }
 } //eq
 
 impl<const N: usize> core::str::FromStr for Sharedstr<N> {
-    type Err = &'static str;
+    type Err = CapacityError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(Sharedstr::from(s))
     }
 }
 
+impl<const N: usize> crate::Fixedstr for Sharedstr<N> {
+    fn len(&self) -> usize {
+        self.len()
+    }
+    /// the inline capacity: strings longer than this are stored on the heap
+    fn capacity(&self) -> usize {
+        N - 1
+    }
+    fn with_segments(&self, f: &mut dyn FnMut(&str, &str)) {
+        match &*self.inner.borrow() {
+            fixed(s) => f(s.to_str(), ""),
+            owned(s) => f(s, ""),
+        }
+    }
+    fn push_str<'t>(&mut self, src: &'t str) -> &'t str {
+        Sharedstr::push_str(self, src);
+        ""
+    }
+    fn push_char(&mut self, c: char) -> bool {
+        self.push(c);
+        true
+    }
+    fn pop_char(&mut self) -> Option<char> {
+        self.pop()
+    }
+    fn truncate_bytes(&mut self, n: usize) {
+        self.truncate(n);
+    }
+    fn clear(&mut self) {
+        self.clear()
+    }
+}
+
+impl<const N: usize> crate::FixedstrMake for Sharedstr<N> {
+    fn make(s: &str) -> Self {
+        Sharedstr::make(s)
+    }
+    fn try_make(s: &str) -> Result<Self, CapacityError> {
+        Ok(Sharedstr::make(s))
+    }
+    fn new() -> Self {
+        Sharedstr::new()
+    }
+}
+
+/// A non-owning pointer to a [Sharedstr], created by [Sharedstr::downgrade].
+/// A `WeakSharedstr` does not keep the string alive: [WeakSharedstr::upgrade]
+/// returns `None` once all `Sharedstr` pointers to the string have been
+/// dropped.  Unlike `Sharedstr`, equality and hashing of `WeakSharedstr`
+/// are by pointer, so that it can be used as the key of a lookup table
+/// that is cleaned by discarding the keys that no longer upgrade.
+/// ```
+///  # use fixedstr::*;
+///  let a = Sharedstr::<8>::from("abc");
+///  let w = a.downgrade();
+///  assert!(w == a.downgrade() && w != Sharedstr::<8>::from("abc").downgrade());
+///  assert!(w.upgrade().unwrap().ptr_eq(&a) && a.weak_count() == 1);
+///  drop(a);
+///  assert!(w.upgrade().is_none() && w.strong_count() == 0);
+/// ```
+#[derive(Clone, Default)]
+pub struct WeakSharedstr<const N: usize = 32> {
+    inner: Weak<RefCell<Strunion<N>>>,
+}
+impl<const N: usize> WeakSharedstr<N> {
+    /// creates a `WeakSharedstr` that points to nothing: it never upgrades.
+    pub fn new() -> Self {
+        WeakSharedstr { inner: Weak::new() }
+    }
+
+    /// attempts to obtain a [Sharedstr] pointing to the string, returning
+    /// `None` if the string no longer exists.
+    pub fn upgrade(&self) -> Option<Sharedstr<N>> {
+        self.inner.upgrade().map(|inner| Sharedstr { inner })
+    }
+
+    /// returns the number of [Sharedstr] pointers to the string
+    pub fn strong_count(&self) -> usize {
+        self.inner.strong_count()
+    }
+
+    /// returns the number of `WeakSharedstr` pointers to the string, or 0
+    /// if the string no longer exists
+    pub fn weak_count(&self) -> usize {
+        self.inner.weak_count()
+    }
+
+    /// tests if two `WeakSharedstr` point to the same location; same as `==`
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Weak::ptr_eq(&self.inner, &other.inner)
+    }
+} //impl WeakSharedstr
+
+impl<const N: usize> core::cmp::PartialEq for WeakSharedstr<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr_eq(other)
+    }
+}
+impl<const N: usize> Eq for WeakSharedstr<N> {}
+
+impl<const N: usize> core::hash::Hash for WeakSharedstr<N> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        core::ptr::hash(self.inner.as_ptr(), state);
+    }
+} //hash
+
+impl<const N: usize> core::fmt::Debug for WeakSharedstr<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.upgrade() {
+            Some(s) => write!(f, "WeakSharedstr({:?})", s),
+            None => f.write_str("WeakSharedstr(<dropped>)"),
+        }
+    }
+} // Debug impl
+
 /// convenient type aliases for [Sharedstr]
 pub type sharedstr8 = Sharedstr<8>;
 pub type sharedstr16 = Sharedstr<16>;
@@ -25,3 +25,24 @@ impl<const N: usize> Clone for Strunion<N> {
         } //match
     }
 } //impl Clone
+
+impl<const N: usize> Strunion<N> {
+    /// uses the fixed representation if the string fits in a `tstr<N>`,
+    /// which is never the case for N>256.
+    pub(crate) fn make(s: &str) -> Self {
+        if N <= 256 && s.len() < N {
+            fixed(tstr::make_unchecked(s))
+        } else {
+            owned(String::from(s))
+        }
+    }
+
+    /// version of [Strunion::make] that consumes an owned string
+    pub(crate) fn from_string(s: String) -> Self {
+        if N <= 256 && s.len() < N {
+            fixed(tstr::make_unchecked(&s))
+        } else {
+            owned(s)
+        }
+    }
+} //impl Strunion
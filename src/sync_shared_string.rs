@@ -0,0 +1,623 @@
+#![allow(unused_variables)]
+#![allow(non_snake_case)]
+#![allow(non_camel_case_types)]
+#![allow(unused_parens)]
+#![allow(unused_assignments)]
+#![allow(unused_mut)]
+#![allow(unused_imports)]
+#![allow(dead_code)]
+use crate::shared_structs::Strunion;
+use crate::shared_structs::Strunion::*;
+use crate::tstr;
+use crate::CapacityError;
+use core::cmp::Ordering;
+use core::ops::Add;
+
+extern crate alloc;
+use alloc::string::String;
+use alloc::sync::Arc;
+
+////////////////////////////////////////////////////////////////////////
+///////////// the lock: std's RwLock when available, else a spin lock
+
+#[cfg(feature = "std")]
+mod lock {
+    extern crate std;
+    use std::sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+    /// thin wrapper around [RwLock] that ignores poisoning: a panic while
+    /// a string is locked cannot leave the string in an invalid state.
+    pub(crate) struct Lock<T>(RwLock<T>);
+    impl<T> Lock<T> {
+        pub(crate) fn new(x: T) -> Self {
+            Lock(RwLock::new(x))
+        }
+        pub(crate) fn read(&self) -> RwLockReadGuard<'_, T> {
+            self.0.read().unwrap_or_else(PoisonError::into_inner)
+        }
+        pub(crate) fn write(&self) -> RwLockWriteGuard<'_, T> {
+            self.0.write().unwrap_or_else(PoisonError::into_inner)
+        }
+        pub(crate) fn into_inner(self) -> T {
+            self.0.into_inner().unwrap_or_else(PoisonError::into_inner)
+        }
+    }
+} //mod lock (std)
+
+#[cfg(not(feature = "std"))]
+mod lock {
+    use core::cell::UnsafeCell;
+    use core::ops::{Deref, DerefMut};
+    use core::sync::atomic::{AtomicUsize, Ordering::*};
+
+    const WRITER: usize = usize::MAX;
+
+    /// minimal readers-writer spin lock used when `std` is not available.
+    /// The state is the number of readers, or WRITER while write-locked.
+    pub(crate) struct Lock<T> {
+        state: AtomicUsize,
+        cell: UnsafeCell<T>,
+    }
+    unsafe impl<T: Send> Send for Lock<T> {}
+    unsafe impl<T: Send + Sync> Sync for Lock<T> {}
+
+    impl<T> Lock<T> {
+        pub(crate) fn new(x: T) -> Self {
+            Lock {
+                state: AtomicUsize::new(0),
+                cell: UnsafeCell::new(x),
+            }
+        }
+        pub(crate) fn read(&self) -> ReadGuard<'_, T> {
+            loop {
+                let s = self.state.load(Relaxed);
+                if s < WRITER - 1
+                    && self
+                        .state
+                        .compare_exchange_weak(s, s + 1, Acquire, Relaxed)
+                        .is_ok()
+                {
+                    return ReadGuard(self);
+                }
+                core::hint::spin_loop();
+            }
+        }
+        pub(crate) fn write(&self) -> WriteGuard<'_, T> {
+            while self
+                .state
+                .compare_exchange_weak(0, WRITER, Acquire, Relaxed)
+                .is_err()
+            {
+                core::hint::spin_loop();
+            }
+            WriteGuard(self)
+        }
+        pub(crate) fn into_inner(self) -> T {
+            self.cell.into_inner()
+        }
+    }
+
+    pub(crate) struct ReadGuard<'t, T>(&'t Lock<T>);
+    impl<T> Deref for ReadGuard<'_, T> {
+        type Target = T;
+        fn deref(&self) -> &T {
+            unsafe { &*self.0.cell.get() }
+        }
+    }
+    impl<T> Drop for ReadGuard<'_, T> {
+        fn drop(&mut self) {
+            self.0.state.fetch_sub(1, Release);
+        }
+    }
+
+    pub(crate) struct WriteGuard<'t, T>(&'t Lock<T>);
+    impl<T> Deref for WriteGuard<'_, T> {
+        type Target = T;
+        fn deref(&self) -> &T {
+            unsafe { &*self.0.cell.get() }
+        }
+    }
+    impl<T> DerefMut for WriteGuard<'_, T> {
+        fn deref_mut(&mut self) -> &mut T {
+            unsafe { &mut *self.0.cell.get() }
+        }
+    }
+    impl<T> Drop for WriteGuard<'_, T> {
+        fn drop(&mut self) {
+            self.0.state.store(0, Release);
+        }
+    }
+} //mod lock (spin)
+
+use lock::Lock;
+
+/// **This type is only available with the 'sync-shared-str' option.**
+/// A `SyncSharedstr` is the thread-safe counterpart of [crate::Sharedstr]:
+/// it uses [Arc] and a readers-writer lock underneath, so that it is both
+/// `Send` and `Sync` and clones can be handed to other threads.  The lock
+/// is `std::sync::RwLock` when the `std` feature is enabled, and a
+/// spin lock otherwise.  Cloning is always done in constant time.
+/// Like `Sharedstr<N>`, a `SyncSharedstr<N>` is represented either by a
+/// `tstr<N>` or by an owned string if its length is greater than N-1, for
+/// N up to 256.
+///
+/// Because the contents are behind a lock, this type cannot lend out a
+/// `&str`: use [SyncSharedstr::with_str] to inspect the string in place,
+/// or [SyncSharedstr::to_string] to copy it out.
+/// Example:
+/// ```
+///  # use fixedstr::*;
+///  let mut a = SyncSharedstr::<8>::from("abc12");
+///  let b = a.clone();
+///  std::thread::spawn(move || assert!(b == "abc12" && b.ptr_count() == 2))
+///     .join().unwrap();
+///  a.push('3');
+///  assert_eq!(a.with_str(|s| s.len()), 6);
+///  assert!(a == "abc123" && a.ptr_count() == 1);
+/// ```
+/// As with `Sharedstr`, `==` compares the contents while `ptr_eq` compares
+/// for pointer-equality.  This type **does not support serde**.
+#[derive(Clone)]
+pub struct SyncSharedstr<const N: usize = 32> {
+    inner: Arc<Lock<Strunion<N>>>,
+}
+impl<const N: usize> SyncSharedstr<N> {
+    fn wrap(s: Strunion<N>) -> Self {
+        SyncSharedstr {
+            inner: Arc::new(Lock::new(s)),
+        }
+    }
+
+    /// Creates a new `SyncSharedstr<N>` with given &str.
+    pub fn make(s: &str) -> Self {
+        Self::wrap(Strunion::make(s))
+    } //make
+
+    /// Creates a `SyncSharedstr<N>` by consuming a given string.  However,
+    /// if the string has length less than N, then a fixed representation
+    /// will be used.
+    pub fn from_string(s: String) -> Self {
+        Self::wrap(Strunion::from_string(s))
+    }
+
+    /// creates a `SyncSharedstr<N>` from a given `tstr<N>`
+    pub fn from_tstr(s: tstr<N>) -> Self {
+        Self::wrap(fixed(s))
+    }
+
+    /// Provided for uniformity with the other string types: since a
+    /// `SyncSharedstr` never truncates, this function always succeeds.
+    pub fn try_make(s: &str) -> Result<SyncSharedstr<N>, CapacityError> {
+        Ok(SyncSharedstr::make(s))
+    }
+
+    /// creates an empty string, equivalent to [SyncSharedstr::default]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// calls the closure with the contents of the string while holding a
+    /// read lock, and returns its result.  The closure must not use the
+    /// same string again, through this or any clone: modifying it would
+    /// deadlock, and so can reading it, including by a nested `with_str`
+    /// or by any other method such as `len` or `==`, since a second read
+    /// lock waits behind a writer of another thread that is waiting for
+    /// the first (as `std::sync::RwLock` may do).  Copy the contents out
+    /// first, as with [SyncSharedstr::to_string], if they are needed again.
+    /// ```
+    ///  # use fixedstr::*;
+    ///  let s = SyncSharedstr::<8>::from("abcd");
+    ///  assert!(s.with_str(|x| x.starts_with("ab")));
+    /// ```
+    pub fn with_str<F, U>(&self, f: F) -> U
+    where
+        F: FnOnce(&str) -> U,
+    {
+        match &*self.inner.read() {
+            fixed(s) => f(s.to_str()),
+            owned(s) => f(s),
+        }
+    } //with_str
+
+    /// length of the string in bytes. This is a constant-time operation.
+    #[allow(clippy::len_without_is_empty)] // is_empty is provided by Fixedstr
+    pub fn len(&self) -> usize {
+        self.with_str(str::len)
+    } //len
+
+    /// length in number of characters as opposed to bytes: this is
+    /// not necessarily a constant time operation.
+    pub fn charlen(&self) -> usize {
+        self.with_str(|s| s.chars().count())
+    } //charlen
+
+    /// returns a copy of the string as an owned `String`
+    #[allow(clippy::inherent_to_string_shadow_display)] // no ToString import needed
+    pub fn to_string(&self) -> String {
+        self.with_str(|s| String::from(s))
+    } //to_string
+
+    /// retrieves a copy of the underlying fixed string, if it is a fixed string.
+    pub fn get_str(&self) -> Option<tstr<N>> {
+        if let fixed(s) = &*self.inner.read() {
+            Some(*s)
+        } else {
+            None
+        }
+    } //get_str
+
+    /// returns the nth char of the string, if it exists
+    pub fn nth(&self, n: usize) -> Option<char> {
+        self.with_str(|s| s.chars().nth(n))
+    }
+
+    /// changes a character at character position i to c.  This function
+    /// requires that c is in the same character class (ascii or unicode)
+    /// as the char being replaced.  It never shuffles the bytes underneath.
+    /// The function returns true if the change was successful.
+    pub fn set(&mut self, i: usize, c: char) -> bool {
+        match &mut *self.inner.write() {
+            fixed(s) => s.set(i, c),
+            owned(s) => {
+                let cbuf = &mut [0u8; 4];
+                let clen = c.encode_utf8(cbuf).len();
+                if let Some((bi, rc)) = s.char_indices().nth(i)
+                    && clen == rc.len_utf8()
+                {
+                    s.replace_range(bi..bi + clen, c.encode_utf8(cbuf));
+                    return true;
+                }
+                false
+            }
+        } //match
+    } //set
+
+    /// returns whether the internal representation is a fixed string (tstr)
+    pub fn is_fixed(&self) -> bool {
+        matches!(&*self.inner.read(), fixed(_))
+    } //is_fixed
+
+    /// returns whether the internal representation is an owned String
+    pub fn is_owned(&self) -> bool {
+        !self.is_fixed()
+    }
+
+    /// applies closure f if the internal representation is a fixed string,
+    /// or closure g if the internal representation is an owned string.
+    pub fn map_or<F, G, U>(&self, f: F, g: G) -> U
+    where
+        F: FnOnce(&tstr<N>) -> U,
+        G: FnOnce(&str) -> U,
+    {
+        match &*self.inner.read() {
+            fixed(s) => f(s),
+            owned(s) => g(&s[..]),
+        } //match
+    } //map_or
+
+    /// appends the SyncSharedstr with the given slice,
+    /// switching to the owned-String representation if necessary.  The function
+    /// returns true if the resulting string uses a `tstr<N>` type, and
+    /// false if the representation is an owned string.
+    pub fn push_str(&mut self, s: &str) -> bool {
+        let mut guard = self.inner.write();
+        match &mut *guard {
+            fixed(fs) if fs.len() + s.len() < N => {
+                fs.push(s);
+                true
+            }
+            fixed(fs) => {
+                let fss = fs.to_string() + s;
+                *guard = owned(fss);
+                false
+            }
+            owned(ns) => {
+                ns.push_str(s);
+                false
+            }
+        } //match
+    } //push_str
+
+    /// appends string with a single character, switching to the String
+    /// representation if necessary.  Returns true if resulting string
+    /// remains fixed.
+    pub fn push(&mut self, c: char) -> bool {
+        let mut buf = [0u8; 4];
+        self.push_str(c.encode_utf8(&mut buf))
+    } //push
+
+    /// alias for push
+    pub fn push_char(&mut self, c: char) -> bool {
+        self.push(c)
+    }
+
+    /// remove and return last character in string, if it exists
+    pub fn pop(&mut self) -> Option<char> {
+        let mut guard = self.inner.write();
+        match &mut *guard {
+            fixed(s) => s.pop_char(),
+            owned(s) if s.len() > N => s.pop(),
+            owned(s) => {
+                // change representation
+                let answer = s.pop();
+                *guard = Strunion::from_string(core::mem::take(s));
+                answer
+            }
+        } //match
+    } //pop
+
+    /// alias for [Self::pop]
+    pub fn pop_char(&mut self) -> Option<char> {
+        self.pop()
+    }
+
+    /// this function truncates a string at the indicated byte position,
+    /// returning true if the truncated string is fixed, and false if owned.
+    /// The operation has no effect if n is larger than the length of the
+    /// string.  The operation will **panic** if n is not on a character
+    /// boundary, similar to [String::truncate].
+    pub fn truncate(&mut self, n: usize) -> bool {
+        let mut guard = self.inner.write();
+        match &mut *guard {
+            fixed(fs) => {
+                if n < fs.len() {
+                    fs.truncate_bytes(n);
+                }
+                true
+            }
+            owned(s) => {
+                if n < s.len() {
+                    s.truncate(n);
+                }
+                *guard = Strunion::from_string(core::mem::take(s));
+                matches!(&*guard, fixed(_))
+            }
+        } //match
+    } //truncate
+
+    /// resets string to empty
+    pub fn clear(&mut self) {
+        *self.inner.write() = Strunion::make("");
+    } //clear
+
+    /// tests strings for content equality
+    pub fn equals<T: AsRef<str>>(&self, other: &T) -> bool {
+        self == other.as_ref()
+    }
+
+    /// tests if two instances of SyncSharedstr point to the same location,
+    /// contrasts with `==`, which always tests for content-equality
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+
+    // calls f with the contents of both strings without ever holding both
+    // locks: with a writer-preferring or spin lock, a writer queued on
+    // `other` between two nested read locks could otherwise deadlock.  The
+    // contents of self are copied out (without allocating if they fit in a
+    // tstr) and its lock is released before other is locked.
+    fn with_both<U>(&self, other: &Self, f: impl FnOnce(&str, &str) -> U) -> U {
+        match self.get_str() {
+            Some(a) => other.with_str(|b| f(&a, b)),
+            None => {
+                let a = self.to_string();
+                other.with_str(|b| f(&a, b))
+            }
+        }
+    } //with_both
+
+    /// creates a new, non-shared copy of the string
+    pub fn deep_clone(&self) -> Self {
+        Self::wrap(self.inner.read().clone())
+    }
+
+    /// returns the number of shared references to this string (strong `Arc`
+    /// pointers).  The count can change at any time if clones are held by
+    /// other threads.
+    pub fn ptr_count(&self) -> usize {
+        Arc::strong_count(&self.inner)
+    }
+
+    /// in-place modification of ascii characters to lower-case
+    pub fn make_ascii_lowercase(&mut self) {
+        match &mut *self.inner.write() {
+            fixed(s) => s.make_ascii_lowercase(),
+            owned(s) => s.make_ascii_lowercase(),
+        } //match
+    } //make_ascii_lowercase
+
+    /// in-place modification of ascii characters to upper-case
+    pub fn make_ascii_uppercase(&mut self) {
+        match &mut *self.inner.write() {
+            fixed(s) => s.make_ascii_uppercase(),
+            owned(s) => s.make_ascii_uppercase(),
+        } //match
+    }
+
+    /// Tests for ascii case-insensitive equality with another string.
+    pub fn case_insensitive_eq<TA>(&self, other: TA) -> bool
+    where
+        TA: AsRef<str>,
+    {
+        self.with_str(|s| s.eq_ignore_ascii_case(other.as_ref()))
+    } //case_insensitive_eq
+
+    /// returns a copy/clone of the string with new fixed capacity M.
+    pub fn resize<const M: usize>(&self) -> SyncSharedstr<M> {
+        self.with_str(SyncSharedstr::make)
+    }
+} //impl SyncSharedstr
+
+impl<const N: usize> Default for SyncSharedstr<N> {
+    fn default() -> Self {
+        Self::wrap(Strunion::make(""))
+    }
+}
+
+#[cfg(feature = "flex-str")]
+use crate::Flexstr;
+#[cfg(feature = "flex-str")]
+impl<const N: usize> SyncSharedstr<N> {
+    /// converts and consumes SyncSharedstr into a Flexstr *if* there is
+    /// exactly one strong reference to the SyncSharedstr.  On failure,
+    /// the same SyncSharedstr is returned as a error.  This function is
+    /// only available with the `flex-str` option.
+    pub fn to_flexstr(self) -> Result<Flexstr<N>, SyncSharedstr<N>> {
+        match Arc::try_unwrap(self.inner) {
+            Ok(x) => match x.into_inner() {
+                fixed(s) => Ok(Flexstr::from_tstr(s)),
+                owned(s) => Ok(Flexstr::from_string(s)),
+            },
+            Err(r) => Err(SyncSharedstr { inner: r }),
+        } //match
+    } //to_flexstr
+} //impl SyncSharedstr
+
+#[cfg(feature = "shared-str")]
+impl<const N: usize> SyncSharedstr<N> {
+    /// creates a (non-shared) [crate::Sharedstr] with the same contents.
+    /// This function is only available with the `shared-str` option.
+    pub fn to_sharedstr(&self) -> crate::Sharedstr<N> {
+        self.with_str(crate::Sharedstr::make)
+    }
+}
+
+impl<T: AsRef<str> + ?Sized, const N: usize> core::convert::From<&T> for SyncSharedstr<N> {
+    fn from(s: &T) -> Self {
+        Self::make(s.as_ref())
+    }
+}
+
+impl<const N: usize> core::convert::From<String> for SyncSharedstr<N> {
+    /// *will consume owned string and convert it to a fixed
+    /// representation if its length is less than N*
+    fn from(s: String) -> Self {
+        SyncSharedstr::from_string(s)
+    }
+} //from String
+
+impl<const N: usize> core::cmp::PartialEq for SyncSharedstr<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr_eq(other) || self.with_both(other, |a, b| a == b)
+    }
+} //eq
+impl<const N: usize> Eq for SyncSharedstr<N> {}
+
+impl<const N: usize> core::cmp::PartialOrd for SyncSharedstr<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<const N: usize> core::cmp::Ord for SyncSharedstr<N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        if self.ptr_eq(other) {
+            return Ordering::Equal;
+        }
+        self.with_both(other, |a, b| a.cmp(b))
+    }
+}
+
+impl<const N: usize> PartialEq<&str> for SyncSharedstr<N> {
+    fn eq(&self, other: &&str) -> bool {
+        self.with_str(|s| s == *other)
+    } //eq
+}
+impl<const N: usize> PartialEq<SyncSharedstr<N>> for &str {
+    fn eq(&self, other: &SyncSharedstr<N>) -> bool {
+        other == self
+    }
+}
+
+impl<const N: usize> core::hash::Hash for SyncSharedstr<N> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.with_str(|s| s.hash(state));
+    }
+} //hash
+
+/// The contents of a `SyncSharedstr` are behind a lock, so it cannot implement
+/// `Borrow<str>`: with the `equivalent` option, maps keyed by `SyncSharedstr`
+/// such as those of hashbrown and indexmap can be looked up by `&str`.
+#[cfg(feature = "equivalent")]
+impl<const N: usize> equivalent::Equivalent<SyncSharedstr<N>> for str {
+    fn equivalent(&self, key: &SyncSharedstr<N>) -> bool {
+        key == self
+    }
+}
+
+impl<const N: usize> core::fmt::Debug for SyncSharedstr<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.with_str(|s| f.pad(s))
+    }
+} // Debug impl
+
+impl<const N: usize> core::fmt::Display for SyncSharedstr<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.with_str(|s| f.pad(s))
+    }
+}
+
+impl<const N: usize> core::fmt::Write for SyncSharedstr<N> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.push_str(s);
+        Ok(())
+    } //write_str
+} //core::fmt::Write trait
+
+impl<const N: usize, TA: AsRef<str>> Add<TA> for &SyncSharedstr<N> {
+    type Output = SyncSharedstr<N>;
+    fn add(self, other: TA) -> Self::Output {
+        let mut a2 = self.deep_clone();
+        a2.push_str(other.as_ref());
+        a2
+    }
+} //Add, Rhs = &str
+
+impl<const N: usize> core::str::FromStr for SyncSharedstr<N> {
+    type Err = CapacityError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(SyncSharedstr::from(s))
+    }
+}
+
+impl<const N: usize> crate::Fixedstr for SyncSharedstr<N> {
+    fn len(&self) -> usize {
+        self.len()
+    }
+    /// the inline capacity: strings longer than this are stored on the heap
+    fn capacity(&self) -> usize {
+        N - 1
+    }
+    fn with_segments(&self, f: &mut dyn FnMut(&str, &str)) {
+        self.with_str(|s| f(s, ""))
+    }
+    fn push_str<'t>(&mut self, src: &'t str) -> &'t str {
+        SyncSharedstr::push_str(self, src);
+        ""
+    }
+    fn push_char(&mut self, c: char) -> bool {
+        self.push(c);
+        true
+    }
+    fn pop_char(&mut self) -> Option<char> {
+        self.pop()
+    }
+    fn truncate_bytes(&mut self, n: usize) {
+        self.truncate(n);
+    }
+    fn clear(&mut self) {
+        self.clear()
+    }
+}
+
+impl<const N: usize> crate::FixedstrMake for SyncSharedstr<N> {
+    fn make(s: &str) -> Self {
+        SyncSharedstr::make(s)
+    }
+    fn try_make(s: &str) -> Result<Self, CapacityError> {
+        Ok(SyncSharedstr::make(s))
+    }
+    fn new() -> Self {
+        SyncSharedstr::new()
+    }
+}
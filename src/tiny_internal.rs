@@ -1,13 +1,13 @@
-//! This module is intended for internal use only.  Only a few
-//! type aliases are exported.  A tiny string or `tstr<N>`, with N<=256,
+//! A tiny string or `tstr<N>`, with N<=256,
 //! is a version of fixed str that represents the best compromise between
 //! memory and runtime efficiency.  Each `tstr<N>` can hold a string of up to
 //! N-1 bytes, with max N=256.  A `tstr<N>` is represented underneath
 //! by a `[u8;N]` with the first byte always representing the length of the
 //! string.  A tstr is not necessarily zero-terminated.
 //! Because currently Rust does not allow conditions on const generics
-//! such as `where N<=256`, this type is not fully exported and one can
-//! only use the type aliases.
+//! such as `where N<=256`, the bound is enforced by compile-time assertions
+//! in the constructors: creating a `tstr<N>` with N outside of 1..=256
+//! fails to compile.
 
 #![allow(unused_variables)]
 #![allow(non_snake_case)]
@@ -31,119 +31,193 @@ use crate::fstr;
 
 use crate::zstr;
 use crate::{str12, str128, str16, str192, str24, str256, str32, str4, str48, str64, str8, str96};
+use crate::const_fns::ConstBytes;
+use crate::niche::NonMaxU8;
+use crate::CapacityError;
+use crate::{FixedstrMake, SplitInto, StrPattern};
+use crate::{OverflowMode, OverflowPolicy, Truncate};
 use core::cmp::{min, Ordering};
 use core::ops::{Add, Index, IndexMut, Range, RangeFrom, RangeFull, RangeTo};
-use core::ops::{RangeInclusive, RangeToInclusive};
+use core::ops::{RangeBounds, RangeInclusive, RangeToInclusive};
+use core::marker::PhantomData;
 
 
-/// **This structure is normally only accessible through the
-/// public types [str4] through [str256].**  These types alias internal
-/// types [tstr]\<4\> through [tstr]\<256\> respectively.  The purpose here
-/// is to guarantee that the maximum size of the structure does not exceed
+/// Strings of up to N-1 bytes, where N must be between 1 and 256.  The
+/// types [str4] through [str256] alias [tstr]\<4\> through [tstr]\<256\>
+/// respectively.  The maximum size of the structure cannot exceed
 /// 256 bytes for it uses the first byte of a u8 array to hold the length of
-/// the string.  The tstr type can be made directly public with the
-/// **`pub-tstr` option**.
+/// the string.  This limit is checked at compile time:
+/// ```compile_fail
+///   # use fixedstr::*;
+///   let s = tstr::<300>::make("too big");
+/// ```
 ///
 /// A feature unique to the tstr type aliases is the ability to concatenate
 /// strings by generating higher-capacity types. Concatenating two strN
 /// strings will always generate a strM with M=2*N, for str4 - str128.
 /// ```
 ///   # use fixedstr::*;
-///   let a = str8::from("aaaaaa");
-///   let b = str8::from("bbbbbb");
+///   let a = str8::make("aaaaaa");
+///   let b = str8::make("bbbbbb");
 ///   let c = a + b;  // type of c will be str16
 ///   assert_eq!(c,"aaaaaabbbbbb");
 ///   assert_eq!(c.capacity(), 15);
 /// ```
 /// In contrast, concatenating other string types such as zstr will always
 /// produce strings of the same type and capacity.
+///
+/// As the length byte never takes every possible value, an `Option<tstr<N>>`
+/// is no larger than a `tstr<N>`:
+/// ```
+///   # use fixedstr::*;
+///   assert_eq!(core::mem::size_of::<Option<str8>>(), 8);
+/// ```
+///
+/// The optional second parameter selects the [OverflowPolicy] (by default
+/// [Truncate]) that determines what happens when the capacity is exceeded:
+/// ```
+///   # use fixedstr::*;
+///   let s = tstr::<8, Saturate>::make("abcdefghij");
+///   assert_eq!(s, "abcd…");
+/// ```
 #[derive(Copy, Clone, Eq)]
-pub struct tstr<const N:usize = 256> {
-    chrs: [u8; N],
+pub struct tstr<const N:usize = 256, P: OverflowPolicy = Truncate> {
+    chrs: [NonMaxU8; N],
+    policy: PhantomData<P>,
 } //tstr
-impl<const N: usize> tstr<N> {
+impl<const N: usize, P: OverflowPolicy> tstr<N, P> {
     /// creates a new `tstr<N>` with given &str.  If the length of s exceeds
-    /// N, the extra characters are ignored.  This function is also called by
-    /// several others including [tstr::from].  This function can now handle
-    /// utf8 strings properly.
-    pub fn make(s: &str) -> tstr<N> {
-        let mut chars = [0u8; N];
-        let bytes = s.as_bytes(); // &[u8]
-        let blen = bytes.len();
-        let limit = min(N - 1, blen);
-        chars[1..limit + 1].copy_from_slice(&bytes[..limit]);
-        chars[0] = limit as u8;
-        //if chars[0] == 0 {
-        //    chars[0] = blen as u8;
-        //}
-        tstr { chrs: chars }
+    /// N-1, the overflow is handled according to the policy `P`: by default
+    /// the extra characters are ignored, truncating at a character
+    /// boundary.  This function is also called by
+    /// several others including [tstr::resize].  This function can now handle
+    /// utf8 strings properly.  To fail instead of truncating, use
+    /// [tstr::try_make] or `TryFrom`.
+    pub fn make(s: &str) -> tstr<N, P> {
+        let mut t = Self::new();
+        t.push_policy(s, false);
+        t
     } //make
 
+    /// version of [Self::make] without the compile-time check on N, for
+    /// internal use by types (`Flexstr`, `Sharedstr`) that allow N>256
+    /// but never create a `tstr<N>` for such N.
+    pub(crate) fn make_unchecked(s: &str) -> tstr<N, P> {
+        let mut t = tstr { chrs: [NonMaxU8::_00; N], policy: PhantomData };
+        let bytes = s.as_bytes(); // &[u8]
+        let limit = crate::floor_char_boundary(bytes, N - 1);
+        t.bytes_mut()[1..limit + 1].copy_from_slice(&bytes[..limit]);
+        t.set_len(limit);
+        t
+    } //make_unchecked
+
     /// alias for [Self::make]
-    pub fn create(s: &str) -> tstr<N> {
-        let mut chars = [0u8; N];
-        let bytes = s.as_bytes();
-        let blen = bytes.len();
-        let limit = min(N - 1, blen);
-        chars[1..limit + 1].copy_from_slice(&bytes[..limit]);
-        chars[0] = limit as u8;
-        //if chars[0] == 0 {
-        //    chars[0] = blen as u8;
-        //}
-        tstr { chrs: chars }
+    #[inline]
+    pub fn create(s: &str) -> tstr<N, P> {
+        Self::make(s)
     } //create
 
-    /// version of make that returns the original string slice in an `Err(_)` if
-    /// truncation is requried, or in an `Ok(_)` if no truncation is required
-    pub fn try_make(s: &str) -> Result<tstr<N>, &str> {
-        if s.len()+1 > N {
-            Err(s)
-        } else {
-            Ok(tstr::make(s))
-        }
+    /// truncating constructor that takes the place of the `From`
+    /// conversions of versions before 0.6.0, which are now `TryFrom`
+    /// conversions that fail instead of truncating.  Since a type cannot
+    /// implement both `From<&str>` and `TryFrom<&str>`, this function keeps
+    /// calls of the form `tstr::from(s)` working (but not `s.into()`) until it
+    /// is removed.
+    #[deprecated(since = "0.6.0", note = "use `make` to truncate, or `try_make` or `try_from` to detect overflow")]
+    pub fn from<T: AsRef<str>>(s: T) -> Self {
+        Self::make(s.as_ref())
+    }
+
+    /// version of make that returns a [CapacityError] if
+    /// truncation is requried, or the string in an `Ok(_)` if no
+    /// truncation is required
+    pub fn try_make(s: &str) -> Result<tstr<N, P>, CapacityError> {
+        Self::const_try_make(s)
+    }
+
+    /// version of [FromIterator] that returns a [CapacityError] instead of
+    /// applying the [OverflowPolicy] if the items do not fit.
+    /// See [crate::StrPiece] for the types of items.
+    pub fn try_from_iter<T: crate::StrPiece>(iter: impl IntoIterator<Item = T>) -> Result<tstr<N, P>, CapacityError> {
+        let mut s = tstr::new();
+        s.try_extend(iter)?;
+        Ok(s)
+    }
+
+    /// version of [Extend] that pushes either all of the items or, if they
+    /// do not fit, none of them, returning a [CapacityError]
+    pub fn try_extend<T: crate::StrPiece>(&mut self, iter: impl IntoIterator<Item = T>) -> Result<(), CapacityError> {
+        crate::try_extend_pieces(self, N - 1, false, iter)
     }
 
 /// const constructor, to be called from const contexts.  However, as
 /// const functions are restricted from using iterators, it's slightly
 /// better to call the non-const constructors in non-const contexts.
-/// Truncates automatically.
-    pub const fn const_make(s:&str) -> tstr<N> {
-      let mut t = tstr::<N>::new();
-      let mut len = s.len();
-      if len>N-1 { len = N-1; } // fix max length
-      t.chrs[0] = len as u8;
+/// Overflow is handled according to the policy `P`, so that under
+/// [crate::Strict] or [crate::Panic] an oversized string fails to compile
+/// when evaluated in a const context.
+    pub const fn const_make(s:&str) -> tstr<N, P> {
+      let mut t = tstr::<N, P>::new();
       let bytes = s.as_bytes();
-      let mut i = 0;
-      while i<len {
-        t.chrs[i+1] = bytes[i];
-        i += 1;
+      let (_, len, ellipsis) = crate::plan_push(0, bytes, N-1, P::MODE, false);
+      crate::copy_bytes(t.bytes_mut(), 1, bytes, len);
+      if ellipsis {
+        crate::copy_bytes(t.bytes_mut(), len+1, crate::ELLIPSIS.as_bytes(), crate::ELLIPSIS.len());
+        t.set_len(len + crate::ELLIPSIS.len());
       }
+      else { t.set_len(len); }
       t
     }//const_make
 
     /// Version of `const_make` that does not truncate.
-    /// Additionally, because this operation is meant to be evaluated at
-    /// compile time, N is checked to be at least 1 and at most 256: `None`
-    /// is returned if conditions are violated.
-    pub const fn const_try_make(s:&str) -> Option<tstr<N>> {
-      if N==0 || N>256 || s.len()+1>N {None}
-      else { Some(tstr::const_make(s)) }
+    pub const fn const_try_make(s:&str) -> Result<tstr<N, P>, CapacityError> {
+      if s.len()+1>N {
+        Err(CapacityError::Overflow{required: s.len(), available: N-1})
+      }
+      else { Ok(tstr::const_make(s)) }
     }
     
     /// creates an empty string; equivalent to tstr::default() but can
     /// also be called from a const context.
     #[inline]
-    pub const fn new() -> tstr<N> {
+    pub const fn new() -> tstr<N, P> {
+        const { assert!(1 <= N && N <= 256, "tstr<N> requires 1 <= N <= 256") };
         tstr {
-          chrs : [0;N]
+          chrs : [NonMaxU8::_00; N],
+          policy: PhantomData,
         }
     }
 
     /// length of the string in bytes (consistent with [str::len]). This
     /// is a constant-time operation.
     #[inline]
+    #[allow(clippy::len_without_is_empty)] // is_empty is provided by Fixedstr
     pub const fn len(&self) -> usize {
-        self.chrs[0] as usize
+        let n = self.bytes()[0] as usize;
+        if N == 256 && n == 254 && self.bytes()[255] != FULL_MARK { 255 } else { n }
+    }
+
+    // The length byte cannot be 0xFF (see [NonMaxU8]), so a full tstr<256>
+    // also stores 254 as its length, which is told apart from a string of
+    // length 254 by its last byte: FULL_MARK, which never occurs in utf8,
+    // marks the latter.
+    const fn set_len(&mut self, n: usize) {
+        let b = self.bytes_mut();
+        if N == 256 && n >= 254 {
+            b[0] = 254;
+            if n == 254 { b[255] = FULL_MARK; }
+        }
+        else { b[0] = n as u8; }
+    }
+
+    // the bytes underneath
+    const fn bytes(&self) -> &[u8; N] {
+        crate::niche::as_u8(&self.chrs)
+    }
+
+    // mutable bytes underneath: only utf8 and lengths (< 255) are written
+    const fn bytes_mut(&mut self) -> &mut [u8; N] {
+        unsafe { crate::niche::as_u8_mut(&mut self.chrs) }
     }
 
     /// returns the number of characters in the string regardless of
@@ -160,113 +234,122 @@ impl<const N: usize> tstr<N> {
 
     /// converts tstr to an alloc::string::string
     #[cfg(not(feature = "no-alloc"))]
+    #[allow(clippy::inherent_to_string_shadow_display)] // no ToString import needed
     pub fn to_string(&self) -> alloc::string::String {
         alloc::string::String::from(self.to_str())
     }
 
     /// returns slice of u8 the array underneath the tstr
-    pub fn as_bytes(&self) -> &[u8] {
-        &self.chrs[1..self.len()+1]
+    pub const fn as_bytes(&self) -> &[u8] {
+        self.bytes().split_at(1).1.split_at(self.len()).0
     }
    
-    /// returns mutable slice of the u8 array underneath (use with care)
-    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+    /// returns mutable slice of the u8 array underneath
+    ///
+    /// # Safety
+    /// The bytes must remain valid utf8.  In particular, the byte 0xFF,
+    /// which never occurs in utf8, is used as a niche by `Option<tstr<N>>`:
+    /// writing it is undefined behavior, even temporarily.
+    pub unsafe fn as_bytes_mut(&mut self) -> &mut [u8] {
         let n = self.len() + 1;
-        &mut self.chrs[1..n]
+        &mut self.bytes_mut()[1..n]
     }
 
     /// converts tstr to &str using [core::str::from_utf8_unchecked]
-    pub fn to_str(&self) -> &str {
-        unsafe { core::str::from_utf8_unchecked(&self.chrs[1..self.len() + 1]) }
+    pub const fn to_str(&self) -> &str {
+        unsafe { core::str::from_utf8_unchecked(self.as_bytes()) }
     }
     /// checked version of [tstr::to_str], but may panic
     pub fn as_str(&self) -> &str {
-        core::str::from_utf8(&self.chrs[1..self.len() + 1]).unwrap()
+        core::str::from_utf8(self.as_bytes()).unwrap()
     }
     /// version of [tstr::as_str] that does not call `unwrap`
     pub fn as_str_safe(&self) -> Result<&str,core::str::Utf8Error> {
-        core::str::from_utf8(&self.chrs[1..self.len() + 1])    
+        core::str::from_utf8(self.as_bytes())
     }
 
     /// changes a character at *character position* i to c.  This function
     /// requires that c is in the same character class (ascii or unicode)
     /// as the char being replaced.  It never shuffles the bytes underneath.
+    /// See [tstr::replace_nth] for a version without this requirement.
     /// The function returns true if the change was successful.
     pub fn set(&mut self, i: usize, c: char) -> bool {
-        let ref mut cbuf = [0u8; 4];
+        let cbuf = &mut [0u8; 4];
         c.encode_utf8(cbuf);
         let clen = c.len_utf8();
-        if let Some((bi, rc)) = self.to_str().char_indices().nth(i) {
-            if clen == rc.len_utf8() {
-                self.chrs[bi + 1..bi + clen + 1].copy_from_slice(&cbuf[..clen]);
-                //for k in 0..clen {self.chrs[bi+k+1] = cbuf[k];}
-                return true;
-            }
+        if let Some((bi, rc)) = self.to_str().char_indices().nth(i)
+            && clen == rc.len_utf8()
+        {
+            self.bytes_mut()[bi + 1..bi + clen + 1].copy_from_slice(&cbuf[..clen]);
+            return true;
         }
-        return false;
+        false
     } //set
 
     /// version of [tstr::set] that assumes that the char is a single byte.
-    /// Sets the char at the given *byte* index.    
-    /// Also does not check for index bounds.  This function is designed
-    /// to be fast.
+    /// Sets the char at the given *byte* index, and does nothing if the
+    /// byte at that index is not an ascii character of the string (which
+    /// includes the case that the index is not less than its length), so
+    /// that a multibyte character is never overwritten.  This function is
+    /// designed to be fast.  **Panics** if c is not an ascii character.
     pub const fn set_byte_char(&mut self, i:usize, c:char) {
-      self.chrs[i+1] = c as u8;
+      assert!(c.is_ascii());
+      if i < self.len() && self.bytes()[i+1].is_ascii() {
+        self.bytes_mut()[i+1] = c as u8;
+      }
     }
 
     /// adds chars to end of current string up to maximum size N of `tstr<N>`,
     /// returns the portion of the push string that was NOT pushed due to
     /// capacity, so
     /// if "" is returned then all characters were pushed successfully.
+    /// A multibyte character is never split: the returned remainder always
+    /// starts at a character boundary.  How much is pushed when the
+    /// capacity is exceeded depends on the [OverflowPolicy] `P`.
     pub fn push<'t>(&mut self, s: &'t str) -> &'t str {
         self.push_str(s)
     } //push
 
     /// alias for [Self::push]
     pub fn push_str<'t>(&mut self, src: &'t str) -> &'t str {
-        //self.push(s)
-        let srclen = src.len();
-        let slen = self.len();
-        let bytes = &src.as_bytes();
-        let length = core::cmp::min(slen + srclen, N - 1);
-        let remain = if N - 1 >= (slen + srclen) {
-            0
-        } else {
-            (srclen + slen) - N + 1
-        };
-        let mut i = 0;
-        while i < srclen && i + slen + 1 < N {
-            self.chrs[slen + i + 1] = bytes[i];
-            i += 1;
-        } //while
-        self.chrs[0] += i as u8;
-        &src[srclen - remain..]
+        self.push_policy(src, true)
     } //push_str
 
+    // push according to the overflow policy; `fallible` is false for
+    // callers that cannot report the part that was not pushed
+    fn push_policy<'t>(&mut self, src: &'t str, fallible: bool) -> &'t str {
+        let (keep, n, ellipsis) =
+            crate::plan_push(self.len(), src.as_bytes(), N - 1, P::MODE, fallible);
+        if keep < self.len() {
+            self.set_len(crate::floor_char_boundary(self.as_bytes(), keep));
+        }
+        self.push_bytes(&src.as_bytes()[..n]);
+        if ellipsis {
+            self.push_bytes(crate::ELLIPSIS.as_bytes());
+        }
+        &src[n..]
+    } //push_policy
+
+    fn push_bytes(&mut self, bytes: &[u8]) {
+        let slen = self.len();
+        self.bytes_mut()[slen + 1..slen + bytes.len() + 1].copy_from_slice(bytes);
+        self.set_len(slen + bytes.len());
+    }
+
     /// pushes a single character to the end of the string, returning
     /// true on success.
     pub fn push_char(&mut self, c: char) -> bool {
-        let clen = c.len_utf8();
-        let slen = self.len();
-        if slen + clen >= N {
-            return false;
-        }
         let mut buf = [0u8; 4]; // char buffer
-        c.encode_utf8(&mut buf);
-        for i in 0..clen {
-            self.chrs[slen + i + 1] = buf[i];
-        }
-        self.chrs[0] = (slen + clen) as u8;
-        true
+        self.push_str(c.encode_utf8(&mut buf)).is_empty()
     } // push_char
 
     /// remove and return last character in string, if it exists
     pub fn pop_char(&mut self) -> Option<char> {
-        if self.len() == 0 {
+        if self.is_empty() {
             return None;
         }
         let (ci, lastchar) = self.char_indices().last().unwrap();
-        self.chrs[0] = ci as u8;
+        self.set_len(ci);
         Some(lastchar)
     } //pop
 
@@ -281,12 +364,12 @@ impl<const N: usize> tstr<N> {
     /// check n against the length of the string. Nor does it check
     /// if the value returned is a valid character.
     pub const fn nth_bytechar(&self, n: usize) -> char {
-        self.chrs[n + 1] as char
+        self.bytes()[n + 1] as char
     }
 
     /// alias for [Self::nth_bytechar] (for backwards compatibility)
     pub const fn nth_ascii(&self, n: usize) -> char {
-        self.chrs[n + 1] as char
+        self.bytes()[n + 1] as char
     }
 
     /// determines if string is an ascii string
@@ -300,16 +383,16 @@ impl<const N: usize> tstr<N> {
     pub fn truncate(&mut self, n: usize) // n is char position, not binary position
     {
         if let Some((bi, c)) = self.to_str().char_indices().nth(n) {
-            self.chrs[0] = bi as u8;
+            self.set_len(bi);
         }
     }
 
     /// truncates string up to *byte* position n.  **Panics** if n is
     /// not on a character boundary, similar to truncate on owned Strings.
     pub fn truncate_bytes(&mut self, n: usize) {
-        if (n < self.chrs[0] as usize) {
+        if (n < self.len()) {
             assert!(self.is_char_boundary(n));
-            self.chrs[0] = n as u8;
+            self.set_len(n);
         }
     }
 
@@ -317,26 +400,25 @@ impl<const N: usize> tstr<N> {
     /// regards all bytes as single chars.  The operation panics if
     /// the resulting string does not end on a character boundary.
     pub fn right_ascii_trim(&mut self) {
-        let mut n = self.chrs[0] as usize;
-        while n > 0 && (self.chrs[n] as char).is_ascii_whitespace() {
-            //self.chrs[n-1] = 0;
+        let mut n = self.len();
+        while n > 0 && (self.bytes()[n] as char).is_ascii_whitespace() {
             n -= 1;
         }
         assert!(self.is_char_boundary(n));
-        self.chrs[0] = n as u8;
+        self.set_len(n);
     } //right_trim
 
     /// resets string to empty string
     pub fn clear(&mut self) {
-        self.chrs[0] = 0;
+        self.set_len(0);
     }
 
     /// in-place modification of ascii characters to lower-case. Panics if
     /// the string is not ascii.
     pub fn make_ascii_lowercase(&mut self) {
         assert!(self.is_ascii());
-        let end = (self.chrs[0] as usize) + 1;
-        for b in &mut self.chrs[1..end] {
+        let n = self.len() + 1;
+        for b in &mut self.bytes_mut()[1..n] {
             if *b >= 65 && *b <= 90 {
                 *b |= 32;
             }
@@ -347,8 +429,8 @@ impl<const N: usize> tstr<N> {
     /// the string is not ascii.
     pub fn make_ascii_uppercase(&mut self) {
         assert!(self.is_ascii());
-        let end = (self.chrs[0] as usize) + 1;
-        for b in &mut self.chrs[1..end] {
+        let n = self.len() + 1;
+        for b in &mut self.bytes_mut()[1..n] {
             if *b >= 97 && *b <= 122 {
                 *b -= 32;
             }
@@ -359,7 +441,7 @@ impl<const N: usize> tstr<N> {
     /// characters.  This contrasts with [str::to_ascii_uppercase],
     /// which creates an owned String.
     pub fn to_ascii_upper(&self) -> Self {
-        let mut cp = self.clone();
+        let mut cp = *self;
         cp.make_ascii_uppercase();
         cp
     }
@@ -383,14 +465,13 @@ impl<const N: usize> tstr<N> {
             return false;
         }
         let obytes = other.as_ref().as_bytes();
-        for i in 0..self.len() {
-            let mut c = self.chrs[i + 1];
+        for (i, mut d) in obytes.iter().copied().enumerate() {
+            let mut c = self.bytes()[i + 1];
             if (c > 64 && c < 91) {
-                c = c | 32;
+                c |= 32;
             } // make lowercase
-            let mut d = obytes[i];
             if (d > 64 && d < 91) {
-                d = d | 32;
+                d |= 32;
             } // make lowercase
             if c != d {
                 return false;
@@ -399,191 +480,182 @@ impl<const N: usize> tstr<N> {
         true
     } //case_insensitive_eq
 
-    /// Decodes a UTF-16 encodeded slice. If a decoding error is encountered
-    /// or capacity exceeded, an `Err(s)` is returned where s is the
-    /// the encoded string up to the point of the error.
-    pub fn from_utf16(v: &[u16]) -> Result<Self, Self> {
+    /// Decodes a UTF-16 encodeded slice.  Returns an error if the slice is
+    /// not valid utf16 or if the decoded string exceeds the capacity.
+    pub fn from_utf16(v: &[u16]) -> Result<Self, CapacityError> {
+        let required = crate::utf16_len(v)?;
+        if required > N - 1 {
+            return Err(CapacityError::Overflow {
+                required,
+                available: N - 1,
+            });
+        }
         let mut s = Self::new();
-        for c in char::decode_utf16(v.iter().cloned()) {
-            if let Ok(c1) = c {
-                if !s.push_char(c1) {
-                    return Err(s);
-                }
-            } else {
-                return Err(s);
-            }
+        for c in char::decode_utf16(v.iter().cloned()).flatten() {
+            s.push_char(c);
         }
         Ok(s)
     } //from_utf16
 
 
-/*
-   // These functions will replace calls to to_str (internal)
-   // ... but both are unsafe because of unwrap
-   #[cfg(not(feature = "prioritize-safety"))]
-   #[inline(always)]
-   fn makestr(&self) -> &str {
-     unsafe { core::str::from_utf8_unchecked(&self.chrs[1..self.len() + 1]) }
-  }
-   #[cfg(feature = "prioritize-safety")]
-   #[inline(always)]
-   fn makestr(&self) -> &str {
-     core::str::from_utf8(&self.chrs[1..self.len() + 1]).unwrap()
-   }
-*/
  
 } //impl tstr<N>
+
+// last byte of a tstr<256> of length 254 (see tstr::set_len)
+const FULL_MARK: u8 = 0xC0;
   ///////////////////////
 
-impl<const N: usize> core::ops::Deref for tstr<N> {
+impl<const N: usize, P: OverflowPolicy> core::ops::Deref for tstr<N, P> {
     type Target = str;
     fn deref(&self) -> &Self::Target {
         self.to_str()
     }
 }
 
-impl<const N: usize> core::convert::AsRef<str> for tstr<N> {
+impl<const N: usize, P: OverflowPolicy> core::convert::AsRef<str> for tstr<N, P> {
     fn as_ref(&self) -> &str {
         self.to_str()
     }
 }
-impl<const N: usize> core::convert::AsMut<str> for tstr<N> {
-    fn as_mut(&mut self) -> &mut str {
-        let blen = self.len() + 1;
-        unsafe { core::str::from_utf8_unchecked_mut(&mut self.chrs[1..blen]) }
-    }
-}
-impl<T: AsRef<str> + ?Sized, const N: usize> core::convert::From<&T> for tstr<N> {
-    fn from(s: &T) -> tstr<N> {
-        tstr::make(s.as_ref())
-    }
-}
-impl<T: AsMut<str> + ?Sized, const N: usize> core::convert::From<&mut T> for tstr<N> {
-    fn from(s: &mut T) -> tstr<N> {
-        tstr::make(s.as_mut())
+
+/// Allows a `tstr` key of a `HashMap` or `BTreeMap` to be looked up by
+/// `&str`, as hashing and comparison are consistent with those of `str`.
+impl<const N: usize, P: OverflowPolicy> core::borrow::Borrow<str> for tstr<N, P> {
+    fn borrow(&self) -> &str {
+        self.to_str()
     }
 }
 
-#[cfg(not(feature = "no-alloc"))]
-impl<const N: usize> core::convert::From<alloc::string::String> for tstr<N> {
-    fn from(s: alloc::string::String) -> tstr<N> {
-        tstr::<N>::make(&s[..])
+/// Collects characters or strings (see [crate::StrPiece]), truncating at
+/// the first item that does not fit as the [OverflowPolicy] dictates
+///
+/// # Panics
+/// Panics if an item does not fit under the [crate::Strict] or
+/// [crate::Panic] policy: use [tstr::try_from_iter] to detect overflow.
+impl<T: crate::StrPiece, const N: usize, P: OverflowPolicy> core::iter::FromIterator<T> for tstr<N, P> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut s = tstr::new();
+        s.extend(iter);
+        s
     }
 }
 
-#[cfg(feature = "std")]
-#[cfg(not(feature = "no-alloc"))]
-impl<const N: usize, const M: usize> std::convert::From<fstr<M>> for tstr<N> {
-    fn from(s: fstr<M>) -> tstr<N> {
-        tstr::<N>::make(s.to_str())
+/// Pushes the items until one does not fit, which is handled according
+/// to the [OverflowPolicy].
+///
+/// # Panics
+/// Panics if an item does not fit under the [crate::Strict] or
+/// [crate::Panic] policy: use [tstr::try_extend] to detect overflow.
+impl<T: crate::StrPiece, const N: usize, P: OverflowPolicy> core::iter::Extend<T> for tstr<N, P> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        crate::extend_pieces(iter, P::MODE, |s| self.push_str(s).is_empty());
     }
 }
 
-impl<const N: usize, const M: usize> core::convert::From<zstr<M>> for tstr<N> {
-    fn from(s: zstr<M>) -> tstr<N> {
-        tstr::<N>::make(s.to_str())
+impl<const N: usize, P: OverflowPolicy> core::convert::AsMut<str> for tstr<N, P> {
+    fn as_mut(&mut self) -> &mut str {
+        unsafe { core::str::from_utf8_unchecked_mut(self.as_bytes_mut()) }
     }
 }
-
-impl<const N: usize> core::cmp::PartialOrd for tstr<N> {
+impl<const N: usize, P: OverflowPolicy> core::cmp::PartialOrd for tstr<N, P> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
     }
 }
 
-impl<const N: usize> core::cmp::Ord for tstr<N> {
+impl<const N: usize, P: OverflowPolicy> core::cmp::Ord for tstr<N, P> {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.chrs[1..self.len() + 1].cmp(&other.chrs[1..other.len() + 1])
+        self.as_bytes().cmp(other.as_bytes())
     }
 }
 
-impl<const M: usize> tstr<M> {
+impl<const M: usize, P: OverflowPolicy> tstr<M, P> {
     /// converts an tstr\<M\> to an tstr\<N\>. If the length of the string being
-    /// converted is greater than N-1, the extra characters will be ignored.
+    /// converted is greater than N-1, the overflow is handled according to
+    /// the policy `P` (by default, the extra characters are ignored).
     /// This operation produces a copy (non-destructive).
     /// Example:
     ///```ignore
-    ///  let s1:tstr<8> = tstr::from("abcdefg");
+    ///  let s1:tstr<8> = tstr::make("abcdefg");
     ///  let s2:tstr<16> = s1.resize();
     ///```
-    pub fn resize<const N: usize>(&self) -> tstr<N> {
-        let slen = self.len();
-        let length = if (slen < N - 1) { slen } else { N - 1 };
-        let mut chars = [0u8; N];
-        chars[1..length + 1].copy_from_slice(&self.chrs[1..length + 1]);
-        //for i in 0..length {chars[i+1] = self.chrs[i+1];}
-        chars[0] = (length) as u8;
-        tstr { chrs: chars }
+    pub fn resize<const N: usize>(&self) -> tstr<N, P> {
+        tstr::make(self.to_str())
     } //resize
 
     /// version of resize that does not allow string truncation due to length
-    pub fn reallocate<const N: usize>(&self) -> Option<tstr<N>> {
+    pub fn reallocate<const N: usize>(&self) -> Option<tstr<N, P>> {
         if self.len() < N {
             Some(self.resize())
         } else {
             None
         }
     } //reallocate
+
+    /// converts to a `tstr<N>` at least as large as `self`, which unlike
+    /// [Self::resize] can never truncate: it is checked at compile time
+    /// that a `tstr<N>` can hold any `tstr<M>`.
+    /// ```
+    ///  # use fixedstr::*;
+    ///  let s = str8::make("abcdefg");
+    ///  let t: str16 = s.grow(); // s.grow::<4>() would not compile
+    ///  assert_eq!(t, "abcdefg");
+    /// ```
+    /// ```compile_fail
+    ///  # use fixedstr::*;
+    ///  let t: str4 = str8::make("abc").grow();
+    /// ```
+    pub fn grow<const N: usize>(&self) -> tstr<N, P> {
+        crate::assert_grows::<Self, tstr<N, P>>();
+        self.resize()
+    }
+
+    /// concatenates `self` and `other` into a new `tstr<N>`.  It is checked
+    /// at compile time that a `tstr<N>` can hold the result, so nothing is
+    /// ever truncated.  `other` can be of any size and of any type
+    /// implementing [FixedCapacity](crate::FixedCapacity).
+    /// ```
+    ///  # use fixedstr::*;
+    ///  let a = str8::make("abc");
+    ///  let b = str4::make("def");
+    ///  let c = a.concat::<12>(&b); // a.concat::<10>(&b) would not compile
+    ///  assert_eq!(c, "abcdef");
+    /// ```
+    pub fn concat<const N: usize>(&self, other: &impl crate::FixedCapacity) -> tstr<N, P> {
+        crate::assert_concat_fits::<Self, _, tstr<N, P>>(other);
+        let mut cat: tstr<N, P> = self.resize();
+        crate::append_all(&mut cat, other);
+        cat
+    }
 } //impl tstr<M>
 
-impl<const N: usize> core::fmt::Display for tstr<N> {
+impl<const N: usize, P: OverflowPolicy> core::fmt::Display for tstr<N, P> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         //write!(f, "{}", self.to_str())
         f.pad(self.to_str())
     }
 }
 
-impl<const N: usize> PartialEq<&str> for tstr<N> {
+impl<const N: usize, P: OverflowPolicy> PartialEq<&str> for tstr<N, P> {
     fn eq(&self, other: &&str) -> bool {
         self.to_str() == *other // see below
     } //eq
 }
-impl<const N: usize> PartialEq<&str> for &tstr<N> {
-    fn eq(&self, other: &&str) -> bool {
-        &self.to_str() == other
-    } //eq
-}
-impl<'t, const N: usize> PartialEq<tstr<N>> for &'t str {
-    fn eq(&self, other: &tstr<N>) -> bool {
+impl<const N: usize, P: OverflowPolicy> PartialEq<tstr<N, P>> for &str {
+    fn eq(&self, other: &tstr<N, P>) -> bool {
         &other.to_str() == self
     }
 }
-impl<'t, const N: usize> PartialEq<&tstr<N>> for &'t str {
-    fn eq(&self, other: &&tstr<N>) -> bool {
-        &other.to_str() == self
-    }
-}
-
 /// defaults to empty string
-impl<const N: usize> Default for tstr<N> {
+impl<const N: usize, P: OverflowPolicy> Default for tstr<N, P> {
     fn default() -> Self {
-        tstr::<N>::new()
-    }
-}
-#[cfg(feature = "std")]
-#[cfg(not(feature = "no-alloc"))]
-impl<const N: usize, const M: usize> PartialEq<tstr<N>> for fstr<M> {
-    fn eq(&self, other: &tstr<N>) -> bool {
-        other.to_str() == self.to_str()
-    }
-}
-#[cfg(feature = "std")]
-#[cfg(not(feature = "no-alloc"))]
-impl<const N: usize, const M: usize> PartialEq<fstr<N>> for tstr<M> {
-    fn eq(&self, other: &fstr<N>) -> bool {
-        other.to_str() == self.to_str()
-    }
-}
-
-impl<const N: usize, const M: usize> PartialEq<zstr<N>> for tstr<M> {
-    fn eq(&self, other: &zstr<N>) -> bool {
-        other.to_str() == self.to_str()
+        tstr::<N, P>::new()
     }
 }
 
-impl<const N: usize> core::fmt::Debug for tstr<N> {
+impl<const N: usize, P: OverflowPolicy> core::fmt::Debug for tstr<N, P> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        f.pad(&self.to_str())
+        f.pad(self.to_str())
     }
 } // Debug impl
 
@@ -600,17 +672,15 @@ where
 } //impl Index
 */
 
-impl<const N: usize> tstr<N> {
+impl<const N: usize, P: OverflowPolicy> tstr<N, P> {
     /// returns a copy of the portion of the string, string could be truncated
     /// if indices are out of range. Similar to slice [start..end]
-    pub fn substr(&self, start: usize, end: usize) -> tstr<N> {
-        let mut chars = [0u8; N];
+    pub fn substr(&self, start: usize, end: usize) -> tstr<N, P> {
         let mut inds = self.char_indices();
         let len = self.len();
         if start >= len || end <= start {
-            return tstr { chrs: chars };
+            return tstr::new();
         }
-        chars[0] = (end - start) as u8;
         let (si, _) = inds.nth(start).unwrap();
         let last = if (end >= len) {
             len
@@ -620,136 +690,297 @@ impl<const N: usize> tstr<N> {
                 None => len,
             } //match
         }; //let last =...
-        chars[1..last - si + 1].copy_from_slice(&self.chrs[si + 1..last + 1]);
-        /*
-        for i in si..last
-        {
-          chars[i-si+1] = self.chrs[i+1];
-        }
-        */
-        tstr { chrs: chars }
+        self.const_range(si, last)
     } //substr
 }
 
-impl Add for str8 {
-    type Output = str16;
-    fn add(self, other: Self) -> Self::Output {
-        let mut cat: Self::Output = self.resize();
-        let slen = self.len();
-        let olen = other.len();
-        cat.chrs[slen + 1..slen + olen + 1].copy_from_slice(&other.chrs[1..olen + 1]);
-        cat.chrs[0] = (slen + olen) as u8;
-        cat
+impl<const N: usize, P: OverflowPolicy> tstr<N, P> {
+    /// returns a copy of the string with all matches of the [StrPattern]
+    /// `pat` replaced by `to`, and whether the copy was truncated to fit
+    /// the capacity (according to the overflow policy `P`).  Unlike
+    /// [str::replace], this does not allocate.
+    pub fn replace<Pat: StrPattern>(&self, pat: Pat, to: &str) -> (tstr<N, P>, bool) {
+        crate::replace_into(self.as_str(), pat, to, usize::MAX)
     }
-} //Add
 
-impl Add for str16 {
-    type Output = str32;
-    fn add(self, other: Self) -> Self::Output {
-        let mut cat: Self::Output = self.resize();
-        let slen = self.len();
-        let olen = other.len();
-        cat.chrs[slen + 1..slen + olen + 1].copy_from_slice(&other.chrs[1..olen + 1]);
-        cat.chrs[0] = (slen + olen) as u8;
-        cat
+    /// version of [tstr::replace] that only replaces the first `count` matches
+    pub fn replacen<Pat: StrPattern>(&self, pat: Pat, to: &str, count: usize) -> (tstr<N, P>, bool) {
+        crate::replace_into(self.as_str(), pat, to, count)
     }
-} //Add
 
-impl Add for str32 {
-    type Output = str64;
-    fn add(self, other: Self) -> Self::Output {
-        let mut cat: Self::Output = self.resize();
-        let slen = self.len();
-        let olen = other.len();
-        cat.chrs[slen + 1..slen + olen + 1].copy_from_slice(&other.chrs[1..olen + 1]);
-        cat.chrs[0] = (slen + olen) as u8;
-        cat
+    /// version of [tstr::replace] that replaces every occurrence of one
+    /// character by another.  The copy can only be truncated if `to`
+    /// has a longer utf8 encoding than `from`.
+    pub fn replace_char(&self, from: char, to: char) -> (tstr<N, P>, bool) {
+        let mut buf = [0u8; 4];
+        crate::replace_into(self.as_str(), from, to.encode_utf8(&mut buf), usize::MAX)
     }
-} //Add
 
-impl Add for str64 {
-    type Output = str128;
-    fn add(self, other: Self) -> Self::Output {
-        let mut cat: Self::Output = self.resize();
-        let slen = self.len();
-        let olen = other.len();
-        cat.chrs[slen + 1..slen + olen + 1].copy_from_slice(&other.chrs[1..olen + 1]);
-        cat.chrs[0] = (slen + olen) as u8;
-        cat
+    /// removes all matches of the [StrPattern] `pat` in place
+    pub fn remove_matches<Pat: StrPattern>(&mut self, pat: Pat) {
+        *self = crate::replace_into(self.as_str(), pat, "", usize::MAX).0;
     }
-} //Add
 
-impl Add for str128 {
-    type Output = str256;
-    fn add(self, other: Self) -> Self::Output {
-        let mut cat: Self::Output = self.resize();
-        let slen = self.len();
-        let olen = other.len();
-        cat.chrs[slen + 1..slen + olen + 1].copy_from_slice(&other.chrs[1..olen + 1]);
-        cat.chrs[0] = (slen + olen) as u8;
-        cat
+    /// splits the string at the first match of `pat`, returning the parts
+    /// before and after the match, or `None` if there is no match.  The
+    /// parts are never truncated.
+    pub fn split_once<Pat: StrPattern>(&self, pat: Pat) -> Option<(tstr<N, P>, tstr<N, P>)> {
+        let s = self.as_str();
+        pat.find_in(s).map(|m| crate::split_around(s, m))
     }
-} //Add
 
-impl Add for str4 {
-    type Output = str8;
-    fn add(self, other: Self) -> Self::Output {
-        let mut cat: Self::Output = self.resize();
-        let slen = self.len();
-        let olen = other.len();
-        cat.chrs[slen + 1..slen + olen + 1].copy_from_slice(&other.chrs[1..olen + 1]);
-        cat.chrs[0] = (slen + olen) as u8;
-        cat
+    /// splits the string at the last match of `pat`, returning the parts
+    /// before and after the match, or `None` if there is no match.
+    pub fn rsplit_once<Pat: StrPattern>(&self, pat: Pat) -> Option<(tstr<N, P>, tstr<N, P>)> {
+        let s = self.as_str();
+        pat.rfind_in(s).map(|m| crate::split_around(s, m))
     }
-} //Add
 
-impl Add for str12 {
-    type Output = str24;
-    fn add(self, other: Self) -> Self::Output {
-        let mut cat: Self::Output = self.resize();
-        let slen = self.len();
-        let olen = other.len();
-        cat.chrs[slen + 1..slen + olen + 1].copy_from_slice(&other.chrs[1..olen + 1]);
-        cat.chrs[0] = (slen + olen) as u8;
-        cat
+    /// returns a copy of the string without the prefix `pat`, or `None` if
+    /// the string does not start with it
+    pub fn strip_prefix<Pat: StrPattern>(&self, pat: Pat) -> Option<tstr<N, P>> {
+        let s = self.as_str();
+        match pat.find_in(s) {
+            Some(m) if m.start == 0 => Some(tstr::make(&s[m.end..])),
+            _ => None,
+        }
     }
-} //Add
 
-impl Add for str24 {
-    type Output = str48;
-    fn add(self, other: Self) -> Self::Output {
-        let mut cat: Self::Output = self.resize();
-        let slen = self.len();
-        let olen = other.len();
-        cat.chrs[slen + 1..slen + olen + 1].copy_from_slice(&other.chrs[1..olen + 1]);
-        cat.chrs[0] = (slen + olen) as u8;
-        cat
+    /// returns a copy of the string without the suffix `pat`, or `None` if
+    /// the string does not end with it
+    pub fn strip_suffix<Pat: StrPattern>(&self, pat: Pat) -> Option<tstr<N, P>> {
+        let s = self.as_str();
+        match pat.rfind_in(s) {
+            Some(m) if m.end == s.len() => Some(tstr::make(&s[..m.start])),
+            _ => None,
+        }
     }
-} //Add
 
-impl Add for str48 {
-    type Output = str96;
-    fn add(self, other: Self) -> Self::Output {
-        let mut cat: Self::Output = self.resize();
-        let slen = self.len();
-        let olen = other.len();
-        cat.chrs[slen + 1..slen + olen + 1].copy_from_slice(&other.chrs[1..olen + 1]);
-        cat.chrs[0] = (slen + olen) as u8;
-        cat
+    /// iterator over the parts of the string separated by the [StrPattern]
+    /// `pat`, as with [str::split], but which yields strings of type `T`
+    /// instead of slices.  See [SplitInto].
+    pub fn split_into<T: FixedstrMake>(&self, pat: impl StrPattern) -> SplitInto<'_, T, impl StrPattern> {
+        SplitInto::new(self.as_str(), "", pat, usize::MAX)
+    }
+
+    /// version of [tstr::split_into] that yields at most `n` parts, the last
+    /// being the rest of the string, as with [str::splitn]
+    pub fn splitn_into<T: FixedstrMake>(&self, n: usize, pat: impl StrPattern) -> SplitInto<'_, T, impl StrPattern> {
+        SplitInto::new(self.as_str(), "", pat, n)
+    }
+
+    /// iterator over the whitespace-separated words of the string, as with
+    /// [str::split_whitespace], that yields strings of type `T`
+    pub fn split_whitespace_into<T: FixedstrMake>(&self) -> SplitInto<'_, T, fn(char) -> bool> {
+        SplitInto::new(self.as_str(), "", char::is_whitespace as fn(char) -> bool, usize::MAX).skipping_empty()
+    }
+
+    /// iterator over the lines of the string, as with [str::lines], that
+    /// yields strings of type `T`
+    pub fn lines_into<T: FixedstrMake>(&self) -> SplitInto<'_, T, char> {
+        SplitInto::new(self.as_str(), "", '\n', usize::MAX).splitting_lines()
+    }
+} //pattern methods
+
+impl<const N: usize, P: OverflowPolicy> tstr<N, P> {
+    // replaces the bytes in range r with `with`
+    fn splice(&mut self, r: Range<usize>, with: &str) -> Result<(), CapacityError> {
+        let len = self.len();
+        let n = crate::splice_bytes(&mut self.bytes_mut()[1..], len, r, with.as_bytes())?;
+        self.set_len(n);
+        Ok(())
     }
-} //Add
 
-impl Add for str96 {
-    type Output = str192;
-    fn add(self, other: Self) -> Self::Output {
-        let mut cat: Self::Output = self.resize();
+    /// inserts a character at byte index `idx`, shifting the rest of the
+    /// string, as `String::insert` does.  Returns false (leaving the
+    /// string unchanged) if the result would not fit.  **Panics** if `idx`
+    /// is larger than the length or not on a character boundary.
+    pub fn insert(&mut self, idx: usize, c: char) -> bool {
+        let mut buf = [0u8; 4];
+        self.insert_str(idx, c.encode_utf8(&mut buf)).is_ok()
+    }
+
+    /// inserts a string at byte index `idx`, as `String::insert_str` does,
+    /// or returns a [CapacityError] (leaving the string unchanged) if the
+    /// result would not fit.  **Panics** if `idx` is larger than the
+    /// length or not on a character boundary.
+    pub fn insert_str(&mut self, idx: usize, s: &str) -> Result<(), CapacityError> {
+        self.replace_range(idx..idx, s)
+    }
+
+    /// removes and returns the character at byte index `idx`, as
+    /// `String::remove` does.  **Panics** if `idx` is not less than the
+    /// length or not on a character boundary.
+    pub fn remove(&mut self, idx: usize) -> char {
+        let c = self.as_str()[idx..].chars().next().expect("cannot remove a char from the end of a string");
+        self.drain(idx..idx + c.len_utf8());
+        c
+    }
+
+    /// removes the given range of bytes from the string and returns it as
+    /// a new string.  Unlike `String::drain`, the removal is immediate and
+    /// no iterator is involved.  **Panics** if the range is out of bounds
+    /// or not on character boundaries.
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> tstr<N, P> {
+        let s = self.as_str();
+        let r = crate::byte_range(s.len(), range, |i| s.is_char_boundary(i));
+        let drained = tstr::make(&s[r.clone()]);
+        self.splice(r, "").unwrap();
+        drained
+    }
+
+    /// keeps only the characters for which `f` returns true, as
+    /// `String::retain` does
+    pub fn retain<F: FnMut(char) -> bool>(&mut self, mut f: F) {
+        let mut kept = tstr::<N, P>::new();
+        for c in self.as_str().chars().filter(|c| f(*c)) {
+            kept.push_char(c);
+        }
+        *self = kept;
+    }
+
+    /// replaces the given range of bytes with a string, as
+    /// `String::replace_range` does, or returns a [CapacityError] (leaving
+    /// the string unchanged) if the result would not fit.  **Panics** if
+    /// the range is out of bounds or not on character boundaries.
+    pub fn replace_range<R: RangeBounds<usize>>(&mut self, range: R, with: &str) -> Result<(), CapacityError> {
+        let s = self.as_str();
+        let r = crate::byte_range(s.len(), range, |i| s.is_char_boundary(i));
+        self.splice(r, with)
+    }
+
+    /// changes the character at *character position* n to c, returning
+    /// true on success.  Unlike [tstr::set], the new character may have a
+    /// different utf8 length than the one it replaces, in which case the
+    /// rest of the string is shifted.  Returns false (leaving the string
+    /// unchanged) only if n is out of range or the result would not fit.
+    pub fn replace_nth(&mut self, n: usize, c: char) -> bool {
+        match self.as_str().char_indices().nth(n) {
+            Some((i, _)) => self.replace_at(i, c),
+            None => false,
+        }
+    }
+
+    /// version of [tstr::replace_nth] that changes the character starting
+    /// at *byte* index `idx`.  Returns false if `idx` is not the start of a
+    /// character of the string or the result would not fit.
+    pub fn replace_at(&mut self, idx: usize, c: char) -> bool {
+        let mut buf = [0u8; 4];
+        match self.as_str().get(idx..).and_then(|t| t.chars().next()) {
+            Some(old) => self.replace_range(idx..idx + old.len_utf8(), c.encode_utf8(&mut buf)).is_ok(),
+            None => false,
+        }
+    }
+} //editing methods
+
+impl<const N: usize, P: OverflowPolicy> tstr<N, P> {
+    /// version of [tstr::push_str] that can be called from a const context.
+    /// Together with the other `const_` methods, this allows tables of
+    /// normalized strings to be built at compile time:
+    /// ```
+    ///  # use fixedstr::*;
+    ///  const fn key(s: &str) -> str16 {
+    ///     let mut k = str16::const_make("id:");
+    ///     k.const_push_str(str16::const_make(s).const_trim().const_to_ascii_lower().to_str());
+    ///     k
+    ///  }
+    ///  const KEYS: [str16; 2] = [key("  Alpha "), key("BETA")];
+    ///  const _: () = assert!(KEYS[1].const_eq("id:beta"));
+    ///  assert_eq!(KEYS[0], "id:alpha");
+    ///  assert_eq!(KEYS[0].const_find("al"), Some(3));
+    ///  assert!(KEYS[0].const_cmp("id:b").is_lt() && KEYS[0].const_starts_with("id:"));
+    /// ```
+    pub const fn const_push_str<'t>(&mut self, src: &'t str) -> &'t str {
         let slen = self.len();
-        let olen = other.len();
-        cat.chrs[slen + 1..slen + olen + 1].copy_from_slice(&other.chrs[1..olen + 1]);
-        cat.chrs[0] = (slen + olen) as u8;
-        cat
+        let (keep, n, ellipsis) = crate::plan_push(slen, src.as_bytes(), N - 1, P::MODE, true);
+        let mut end = slen;
+        if keep < slen {
+            end = crate::floor_char_boundary(self.as_bytes(), keep);
+        }
+        crate::copy_bytes(self.bytes_mut(), end + 1, src.as_bytes(), n);
+        end += n;
+        if ellipsis {
+            crate::copy_bytes(self.bytes_mut(), end + 1, crate::ELLIPSIS.as_bytes(), crate::ELLIPSIS.len());
+            end += crate::ELLIPSIS.len();
+        }
+        self.set_len(end);
+        src.split_at(n).1
+    } //const_push_str
+
+    /// version of `==` with a `&str` that can be called from a const context
+    pub const fn const_eq(&self, other: &str) -> bool {
+        crate::const_fns::eq(self.const_bytes(), ConstBytes::of(other))
+    }
+
+    /// version of [Ord::cmp] with a `&str` that can be called from a
+    /// const context
+    pub const fn const_cmp(&self, other: &str) -> Ordering {
+        crate::const_fns::cmp(self.const_bytes(), ConstBytes::of(other))
+    }
+
+    /// version of [str::starts_with] that can be called from a const context
+    pub const fn const_starts_with(&self, prefix: &str) -> bool {
+        crate::const_fns::starts_with(self.const_bytes(), ConstBytes::of(prefix))
+    }
+
+    /// returns the byte position of the first occurrence of `pat`, like
+    /// [str::find] but callable from a const context
+    pub const fn const_find(&self, pat: &str) -> Option<usize> {
+        crate::const_fns::find(self.const_bytes(), ConstBytes::of(pat))
+    }
+
+    /// version of [tstr::substr] that can be called from a const context:
+    /// returns a copy of the characters at (character) positions
+    /// `start..end`, which are clamped to the length of the string
+    pub const fn const_substr(&self, start: usize, end: usize) -> tstr<N, P> {
+        if end <= start {
+            return tstr::new();
+        }
+        let b = self.const_bytes();
+        self.const_range(crate::const_fns::char_to_byte(b, start), crate::const_fns::char_to_byte(b, end))
+    }
+
+    /// version of [tstr::to_ascii_upper] that can be called from a const context
+    pub const fn const_to_ascii_upper(&self) -> tstr<N, P> {
+        self.const_map_ascii(true)
+    }
+
+    /// version of [tstr::to_ascii_lower] that can be called from a const context
+    pub const fn const_to_ascii_lower(&self) -> tstr<N, P> {
+        self.const_map_ascii(false)
+    }
+
+    /// returns a copy of the string without leading and trailing
+    /// whitespace, like [str::trim] but callable from a const context
+    pub const fn const_trim(&self) -> tstr<N, P> {
+        let (start, end) = crate::const_fns::trim(self.const_bytes());
+        self.const_range(start, end)
+    }
+
+    // the bytes of the string, for the const methods
+    const fn const_bytes(&self) -> ConstBytes<'_> {
+        ConstBytes::new(self.bytes(), 1, self.len())
+    }
+
+    // copy of bytes start..end, which must be at character boundaries
+    const fn const_range(&self, start: usize, end: usize) -> tstr<N, P> {
+        let mut s = tstr::new();
+        crate::copy_bytes(s.bytes_mut(), 1, self.bytes().split_at(start + 1).1, end - start);
+        s.set_len(end - start);
+        s
+    }
+
+    const fn const_map_ascii(&self, upper: bool) -> tstr<N, P> {
+        let mut s = *self;
+        let b = s.bytes_mut();
+        let mut i = 1;
+        while i <= self.len() {
+            b[i] = if upper { b[i].to_ascii_uppercase() } else { b[i].to_ascii_lowercase() };
+            i += 1;
+        }
+        s
     }
-} //Add
+} //const methods
 
 /* conflicting impl
 impl<const N: usize,TA:AsRef<str>> Add<TA> for tstr<N> {
@@ -762,29 +993,29 @@ impl<const N: usize,TA:AsRef<str>> Add<TA> for tstr<N> {
 } //Add &str
 */
 
-impl<const N: usize> Add<&str> for tstr<N> {
-    type Output = tstr<N>;
-    fn add(self, other: &str) -> tstr<N> {
+impl<const N: usize, TA: AsRef<str> + ?Sized, P: OverflowPolicy> Add<&TA> for tstr<N, P> {
+    type Output = tstr<N, P>;
+    fn add(self, other: &TA) -> tstr<N, P> {
         let mut a2 = self;
-        a2.push(other);
+        a2.push_policy(other.as_ref(), false);
         a2
     }
 } //Add &str
 
-impl<const N: usize> Add<&tstr<N>> for &str {
-    type Output = tstr<N>;
-    fn add(self, other: &tstr<N>) -> tstr<N> {
-        let mut a2 = tstr::from(self);
-        a2.push(other);
+impl<const N: usize, P: OverflowPolicy> Add<&tstr<N, P>> for &str {
+    type Output = tstr<N, P>;
+    fn add(self, other: &tstr<N, P>) -> tstr<N, P> {
+        let mut a2 = tstr::make(self);
+        a2.push_policy(other, false);
         a2
     }
 } //Add &str on left
 
-impl<const N: usize> Add<tstr<N>> for &str {
-    type Output = tstr<N>;
-    fn add(self, other: tstr<N>) -> tstr<N> {
-        let mut a2 = tstr::from(self);
-        a2.push(&other);
+impl<const N: usize, P: OverflowPolicy> Add<tstr<N, P>> for &str {
+    type Output = tstr<N, P>;
+    fn add(self, other: tstr<N, P>) -> tstr<N, P> {
+        let mut a2 = tstr::make(self);
+        a2.push_policy(&other, false);
         a2
     }
 } //Add &str on left
@@ -799,54 +1030,115 @@ impl<const N: usize> Add<tstr<N>> for &str {
 ///   /* or */
 ///   let s2 = str_format!(str32,"abx{}{}{}",1,2,3);
 /// ```
-impl<const N: usize> core::fmt::Write for tstr<N> {
+impl<const N: usize, P: OverflowPolicy> core::fmt::Write for tstr<N, P> {
+    /// pushes `s` according to the overflow policy (by default, as much
+    /// as fits up to a character boundary), returning an error if anything
+    /// had to be left out.
     fn write_str(&mut self, s: &str) -> core::fmt::Result {
-        if s.len() + self.len() + 1 > N {
-            return Err(core::fmt::Error::default());
+        if !self.push(s).is_empty() {
+            return Err(core::fmt::Error);
         }
-        self.push(s);
         Ok(())
     } //write_str
 } //core::fmt::Write trait
 
-impl<const N: usize> core::hash::Hash for tstr<N> {
+impl<const N: usize, P: OverflowPolicy> core::hash::Hash for tstr<N, P> {
     fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
         self.as_ref().hash(state);
     }
 } //hash
 
-impl<const N: usize> core::cmp::PartialEq for tstr<N> {
+impl<const N: usize, P: OverflowPolicy> core::cmp::PartialEq for tstr<N, P> {
     fn eq(&self, other: &Self) -> bool {
         self.as_ref() == other.as_ref()
     }
 }
 
-impl<const N: usize> core::str::FromStr for tstr<N> {
-    type Err = &'static str;
+impl<const N: usize, P: OverflowPolicy> core::str::FromStr for tstr<N, P> {
+    type Err = CapacityError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if N < 257 && s.len() < N {
-            Ok(tstr::from(s))
-        } else {
-            Err("Parse Error: capacity exceeded")
-        }
+        tstr::try_make(s)
+    }
+}
+
+impl<const N: usize, P: OverflowPolicy> crate::FixedCapacity for tstr<N, P> {
+    const CAPACITY: usize = N - 1;
+}
+
+impl<const N: usize, P: OverflowPolicy> crate::Fixedstr for tstr<N, P> {
+    fn len(&self) -> usize {
+        self.len()
+    }
+    fn capacity(&self) -> usize {
+        self.capacity()
+    }
+    fn with_segments(&self, f: &mut dyn FnMut(&str, &str)) {
+        f(self.to_str(), "")
+    }
+    fn push_str<'t>(&mut self, src: &'t str) -> &'t str {
+        self.push_str(src)
+    }
+    fn push_char(&mut self, c: char) -> bool {
+        self.push_char(c)
+    }
+    fn pop_char(&mut self) -> Option<char> {
+        self.pop_char()
+    }
+    fn truncate_bytes(&mut self, n: usize) {
+        self.truncate_bytes(n)
+    }
+    fn clear(&mut self) {
+        self.clear()
+    }
+}
+
+impl<const N: usize, P: OverflowPolicy> crate::FixedstrMake for tstr<N, P> {
+    fn make(s: &str) -> Self {
+        tstr::make(s)
+    }
+    fn try_make(s: &str) -> Result<Self, CapacityError> {
+        tstr::try_make(s)
+    }
+    fn new() -> Self {
+        tstr::new()
+    }
+}
+
+impl<const N: usize, P: OverflowPolicy> crate::ContiguousFixedstr for tstr<N, P> {
+    fn as_str(&self) -> &str {
+        self.to_str()
     }
 }
 
 /// const function to limit usize value to between 1 and 256.
-/// Can be called when tstr is created (under `pub-tstr` feature):
+/// Can be called when tstr is created:
 /// ```
-///   #[cfg(feature = "pub-tstr")]
 ///   # use fixedstr::*;
-///   let ls = tstr::<{tstr_limit(258)}>::from("abcd");
+///   let ls = tstr::<{tstr_limit(258)}>::make("abcd");
 ///   assert_eq!(ls.capacity(),255);
 /// ```
-#[cfg(feature = "pub-tstr")]
 pub const fn tstr_limit(n:usize) -> usize {
   if n==0 {1}
   else if n>256 {256}
   else {n}
 }//const limit_size
 
+/// const function that returns the size N of the smallest of the
+/// [str4]..[str256] aliases of `tstr<N>` that can hold a string of `len`
+/// bytes, or 256 if there is none.  Used by [crate::fixedstr!]:
+/// ```
+///   # use fixedstr::*;
+///   assert_eq!((tstr_fit(0), tstr_fit(3), tstr_fit(4), tstr_fit(100)), (4, 4, 8, 128));
+/// ```
+pub const fn tstr_fit(len: usize) -> usize {
+  const SIZES: [usize; 12] = [4, 8, 12, 16, 24, 32, 48, 64, 96, 128, 192, 256];
+  let mut i = 0;
+  while i < SIZES.len() - 1 && SIZES[i] <= len {
+    i += 1;
+  }
+  SIZES[i]
+}//tstr_fit
+
 
 
 /*   cannot adopt, because it affects type inference of s1 == s2.resize()
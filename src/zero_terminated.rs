@@ -24,8 +24,14 @@
 use crate::fstr;
 
 use crate::tstr;
+use crate::const_fns::ConstBytes;
+use crate::niche::NonMaxU8;
+use crate::CapacityError;
+use crate::{FixedstrMake, SplitInto, StrPattern};
+use crate::{OverflowPolicy, Truncate};
 use core::cmp::{min, Ordering};
-use core::ops::Add;
+use core::marker::PhantomData;
+use core::ops::{Add, Range, RangeBounds};
 
 #[cfg(not(feature = "no-alloc"))]
 extern crate alloc;
@@ -41,83 +47,130 @@ extern crate std;
 /// [utf8 encodings](https://www.ibm.com/docs/en/db2/11.5?topic=support-unicode-character-encoding)
 /// of unicode characters allow single null bytes to be distinguished as
 /// end-of-string.
+///
+/// A `zstr<N>` requires `N >= 1`; constructing a `zstr<0>` is rejected
+/// at compile time:
+/// ```compile_fail
+///   # use fixedstr::*;
+///   let z = zstr::<0>::new();
+/// ```
+///
+/// The optional second parameter selects the [OverflowPolicy] (by default
+/// [Truncate]) that determines what happens when the capacity is exceeded.
 #[derive(Copy, Clone, Eq)]
-pub struct zstr<const N: usize> {
-    chrs: [u8; N],
+pub struct zstr<const N: usize, P: OverflowPolicy = Truncate> {
+    chrs: [NonMaxU8; N],
+    policy: PhantomData<P>,
 } //zstr
-impl<const N: usize> zstr<N> {
+impl<const N: usize, P: OverflowPolicy> zstr<N, P> {
     /// creates a new `zstr<N>` with given &str.  If the length of s exceeds
-    /// N, the extra characters are ignored.
+    /// N-1, the overflow is handled according to the policy `P`: by default
+    /// the extra characters are ignored, truncating at a character
+    /// boundary.
     /// This function is also called by
-    /// several others including [zstr::from].
-    pub fn make(s: &str) -> zstr<N> {
-        let mut chars = [0u8; N];
-        let bytes = s.as_bytes(); // &[u8]
-        let mut i = 0;
-        let limit = if N == 0 { 0 } else { min(N - 1, bytes.len()) };
-        chars[..limit].clone_from_slice(&bytes[..limit]);
-        zstr { chrs: chars }
+    /// several others including [zstr::resize].  To fail instead of
+    /// truncating, use [zstr::try_make] or `TryFrom`.
+    pub fn make(s: &str) -> zstr<N, P> {
+        let mut z = Self::new();
+        z.push_policy(s, false);
+        z
     } //make
 
     /// alias for [zstr::make]
     #[inline]
-    pub fn create(s: &str) -> zstr<N> {
+    pub fn create(s: &str) -> zstr<N, P> {
         Self::make(s)
     }
 
-    /// version of make that returns the original string in an `Err(_)` if
-    /// truncation is requried, or in an `Ok(_)` if no truncation is required
-    pub fn try_make(s: &str) -> Result<zstr<N>, &str> {
-        if s.len() + 1 > N {
-            Err(s)
-        } else {
-            Ok(zstr::make(s))
-        }
+    /// truncating constructor that takes the place of the `From`
+    /// conversions of versions before 0.6.0, which are now `TryFrom`
+    /// conversions that fail instead of truncating.  Since a type cannot
+    /// implement both `From<&str>` and `TryFrom<&str>`, this function keeps
+    /// calls of the form `zstr::from(s)` working (but not `s.into()`) until it
+    /// is removed.
+    #[deprecated(since = "0.6.0", note = "use `make` to truncate, or `try_make` or `try_from` to detect overflow")]
+    pub fn from<T: AsRef<str>>(s: T) -> Self {
+        Self::make(s.as_ref())
+    }
+
+    /// version of make that returns a [CapacityError] if
+    /// truncation is requried or if the string contains a zero byte,
+    /// which cannot be represented by a zero-terminated string.
+    pub fn try_make(s: &str) -> Result<zstr<N, P>, CapacityError> {
+        Self::const_try_make(s)
+    }
+
+    /// version of [FromIterator] that returns a [CapacityError] instead of
+    /// applying the [OverflowPolicy] if the items do not fit or contain a zero byte.
+    /// See [crate::StrPiece] for the types of items.
+    pub fn try_from_iter<T: crate::StrPiece>(iter: impl IntoIterator<Item = T>) -> Result<zstr<N, P>, CapacityError> {
+        let mut s = zstr::new();
+        s.try_extend(iter)?;
+        Ok(s)
+    }
+
+    /// version of [Extend] that pushes either all of the items or, if they
+    /// do not fit or contain a zero byte, none of them, returning a [CapacityError]
+    pub fn try_extend<T: crate::StrPiece>(&mut self, iter: impl IntoIterator<Item = T>) -> Result<(), CapacityError> {
+        crate::try_extend_pieces(self, N - 1, true, iter)
     }
 
     /// creates an empty string, equivalent to zstr::default() but can also
     /// be called in a const context
-    pub const fn new() -> zstr<N> {
+    pub const fn new() -> zstr<N, P> {
+        const { assert!(N >= 1, "zstr<N> requires N >= 1") };
         zstr {
-          chrs: [0;N]
+          chrs: [NonMaxU8::_00; N],
+          policy: PhantomData,
         }
     }
 
 /// const constructor, to be called from const contexts.  However, as
 /// const constructors are restricted from using iterators, it's slightly
 /// better to call the non-const constructors in non-const contexts.
-/// Truncates automatically.
-    pub const fn const_make(s:&str) -> zstr<N> {
-      let mut t = zstr::<N>::new();
-      let mut len = s.len();
-      if len+1>N { len = N-1; } // fix max length
+/// Overflow is handled according to the policy `P`, so that under
+/// [crate::Strict] or [crate::Panic] an oversized string fails to compile
+/// when evaluated in a const context.
+    pub const fn const_make(s:&str) -> zstr<N, P> {
+      let mut t = zstr::<N, P>::new();
       let bytes = s.as_bytes();
-      let mut i = 0;
-      while i<len {
-        t.chrs[i] = bytes[i];
-        i += 1;
+      let (_, len, ellipsis) = crate::plan_push(0, bytes, N-1, P::MODE, false);
+      crate::copy_bytes(t.bytes_mut(), 0, bytes, len);
+      if ellipsis {
+        crate::copy_bytes(t.bytes_mut(), len, crate::ELLIPSIS.as_bytes(), crate::ELLIPSIS.len());
       }
       t
     }//const_make
 
-    /// version of `const_make` that does not truncate.
-    pub const fn const_try_make(s:&str) -> Option<zstr<N>> {
-      if s.len()+1>N {None}
-      else { Some(zstr::const_make(s)) }
+    /// version of `const_make` that does not truncate.  Also rejects
+    /// strings containing a zero byte.
+    pub const fn const_try_make(s:&str) -> Result<zstr<N, P>, CapacityError> {
+      if s.len()+1>N {
+        return Err(CapacityError::Overflow{required: s.len(), available: N-1});
+      }
+      let bytes = s.as_bytes();
+      let mut i = 0;
+      while i<bytes.len() {
+        if bytes[i]==0 { return Err(CapacityError::InteriorNul{position: i}); }
+        i += 1;
+      }
+      Ok(zstr::const_make(s))
     }
 
     /// const function that
     /// creates a new `zstr<N>` with given `&[u8]` slice.  If the length of the
     /// slice exceeds N-1, the extra bytes are ignored.  All bytes of the slice
-    /// following the first zero-byte are also ignored.
+    /// following the first zero-byte are also ignored, as are those from
+    /// the first 0xFF byte on, which can never occur in utf8.
     /// **This operation does not check if the u8 slice is an utf8 source.**
     /// This function is unique to zstr and not available for the
     /// other string types in this crate.
-    pub const fn from_raw(s: &[u8]) -> zstr<N> {
-        let mut z = zstr { chrs: [0; N] };
+    pub const fn from_raw(s: &[u8]) -> zstr<N, P> {
+        const { assert!(N >= 1, "zstr<N> requires N >= 1") };
+        let mut z = zstr::new();
         let mut i = 0;
-        while i + 1 < N && i < s.len() && s[i] != 0 {
-            z.chrs[i] = s[i];
+        while i + 1 < N && i < s.len() && s[i] != 0 && s[i] != 0xFF {
+            z.bytes_mut()[i] = s[i];
             i += 1;
         }
         z
@@ -128,6 +181,7 @@ impl<const N: usize> zstr<N> {
     /// and runs in O(log N) time for each `zstr<N>`.  This function
     /// can be called from a const context.
     #[inline(always)]
+    #[allow(clippy::len_without_is_empty)] // is_empty is provided by Fixedstr
     pub const fn len(&self) -> usize {
         self.blen()
     }
@@ -139,10 +193,10 @@ impl<const N: usize> zstr<N> {
     /// not available for other string types in this crate.
     pub const fn linear_len(&self) -> usize {
         let mut i = 0;
-        while self.chrs[i] != 0 {
+        while self.bytes()[i] != 0 {
             i += 1;
         }
-        return i;
+        i
     } //linear_len
 
     /// const function that checks that the underlying array of the zstr is
@@ -154,7 +208,7 @@ impl<const N: usize> zstr<N> {
             return false;
         }
         while n < N {
-            if self.chrs[n] != 0 {
+            if self.bytes()[n] != 0 {
                 return false;
             }
             n += 1;
@@ -167,10 +221,10 @@ impl<const N: usize> zstr<N> {
     pub fn clean(&mut self) {
         let mut n = self.linear_len();
         if n == N {
-            self.chrs[n - 1] = 0;
+            self.bytes_mut()[n - 1] = 0;
         }
         while n < N {
-            self.chrs[n] = 0;
+            self.bytes_mut()[n] = 0;
             n += 1;
         } //while
     } //clean
@@ -178,12 +232,19 @@ impl<const N: usize> zstr<N> {
     /// returns maximum capacity in bytes
     #[inline(always)]
     pub const fn capacity(&self) -> usize {
-        if N == 0 {
-            return 0;
-        }
         N - 1
     }
 
+    // the bytes underneath
+    const fn bytes(&self) -> &[u8; N] {
+        crate::niche::as_u8(&self.chrs)
+    }
+
+    // mutable bytes underneath: only utf8 and zeros are written
+    const fn bytes_mut(&mut self) -> &mut [u8; N] {
+        unsafe { crate::niche::as_u8_mut(&mut self.chrs) }
+    }
+
     // new blen function uses binary search to find first 0 byte.
     const fn blen(&self) -> usize {
         let (mut min, mut max) = (0, N);
@@ -191,7 +252,7 @@ impl<const N: usize> zstr<N> {
         while min < max {
             //mid = (min + max) / 2;
             mid = min + (max-min)/2;   // no overflow, just in case
-            if self.chrs[mid] == 0 {
+            if self.bytes()[mid] == 0 {
                 // go left
                 max = mid;
             } else {
@@ -204,71 +265,87 @@ impl<const N: usize> zstr<N> {
 
     /// converts zstr to an owned string
     #[cfg(not(feature = "no-alloc"))]
+    #[allow(clippy::inherent_to_string_shadow_display)] // no ToString import needed
     pub fn to_string(&self) -> alloc::string::String {
         alloc::string::String::from(self.to_str())
     }
 
     /// returns slice of u8 array underneath the zstr, **including the terminating 0**
     #[inline]
-    pub fn as_bytes(&self) -> &[u8] {
-        &self.chrs[..self.blen() + 1]
+    pub const fn as_bytes(&self) -> &[u8] {
+        self.bytes().split_at(self.blen() + 1).0
     }
 
-    /// returns mutable slice of the u8 array underneath, including the terminating zero.  **WARNING:** changing a byte to zero in the middle of the string is not enough to zero-terminate the string: the length calculation via binary search will become invalid. All bytes following the first zero must also be zeroed.  Use with care.
-    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+    /// returns mutable slice of the u8 array underneath, including the terminating zero.  **WARNING:** changing a byte to zero in the middle of the string is not enough to zero-terminate the string: the length calculation via binary search will become invalid. All bytes following the first zero must also be zeroed.
+    ///
+    /// # Safety
+    /// The bytes must remain valid utf8.  In particular, the byte 0xFF,
+    /// which never occurs in utf8, is used as a niche by `Option<zstr<N>>`:
+    /// writing it is undefined behavior, even temporarily.
+    pub unsafe fn as_bytes_mut(&mut self) -> &mut [u8] {
         let n = self.blen()+1;
-        &mut self.chrs[0..n]
+        &mut self.bytes_mut()[0..n]
     }
 
     /// returns slice of u8 array underneath the zstr without the terminating zero
     #[inline]
-    pub fn as_bytes_non_terminated(&self) -> &[u8] {
-        &self.chrs[..self.blen()]
+    pub const fn as_bytes_non_terminated(&self) -> &[u8] {
+        self.bytes().split_at(self.blen()).0
     }
 
     /// converts zstr to &str using [core::str::from_utf8_unchecked].
-    pub fn to_str(&self) -> &str {
-        unsafe { core::str::from_utf8_unchecked(&self.chrs[0..self.blen()]) }
+    pub const fn to_str(&self) -> &str {
+        unsafe { core::str::from_utf8_unchecked(self.as_bytes_non_terminated()) }
     }
     /// checked version of [zstr::to_str], but may panic (calls `unwrap`)
     pub fn as_str(&self) -> &str {
-        core::str::from_utf8(&self.chrs[0..self.blen()]).unwrap()
+        core::str::from_utf8(&self.bytes()[0..self.blen()]).unwrap()
     }
     /// version of [zstr::as_str] that does not call `unwrap`
     pub fn as_str_safe(&self) -> Result<&str,core::str::Utf8Error> {
-        core::str::from_utf8(&self.chrs[0..self.blen()])
+        core::str::from_utf8(&self.bytes()[0..self.blen()])
     }
     
 
     /// changes a character at *character position* i to c.  This function
     /// requires that c is in the same character class (ascii or unicode)
     /// as the char being replaced.  It never shuffles the bytes underneath.
+    /// See [zstr::replace_nth] for a version without this requirement.
     /// The function returns true if the change was successful.
     pub fn set(&mut self, i: usize, c: char) -> bool {
-        let ref mut cbuf = [0u8; 4];
+        let cbuf = &mut [0u8; 4];
         c.encode_utf8(cbuf);
         let clen = c.len_utf8();
-        if let Some((bi, rc)) = self.to_str().char_indices().nth(i) {
-            if clen == rc.len_utf8() {
-                self.chrs[bi..bi + clen].clone_from_slice(&cbuf[..clen]);
-                return true;
-            }
+        if let Some((bi, rc)) = self.to_str().char_indices().nth(i)
+            && clen == rc.len_utf8()
+        {
+            self.bytes_mut()[bi..bi + clen].clone_from_slice(&cbuf[..clen]);
+            return true;
         }
-        return false;
+        false
     } //set
 
     /// version of [zstr::set] that assumes that the char is a single byte.
-    /// Sets the char at the given *byte* index.
-    /// Does not check for index bounds, but does check that the byte being set 
-    /// is not zero. This function is designed to be fast.
+    /// Sets the char at the given *byte* index, and does nothing if the
+    /// byte at that index is not an ascii character of the string (which
+    /// includes the case that the index is not less than its length), so
+    /// that a multibyte character is never overwritten.  This function is
+    /// designed to be fast.  **Panics** if c is not an ascii character, or
+    /// is the zero character, which would terminate the string early.
     pub const fn set_byte_char(&mut self, i:usize, c:char) {
-      if self.chrs[i]!=0 {self.chrs[i] = c as u8;}
+      assert!(c.is_ascii() && c != '\0');
+      if i < self.len() && self.bytes()[i].is_ascii() {
+        self.bytes_mut()[i] = c as u8;
+      }
     }
 
     /// adds chars to end of current string up to maximum size N-1 of `zstr<N>`,
     /// returns the portion of the push string that was NOT pushed due to
     /// capacity, so
     /// if "" is returned then all characters were pushed successfully.
+    /// A multibyte character is never split: the returned remainder always
+    /// starts at a character boundary.  How much is pushed when the
+    /// capacity is exceeded depends on the [OverflowPolicy] `P`.
     #[inline]
     pub fn push<'t>(&mut self, s: &'t str) -> &'t str {
         self.push_str(s)
@@ -276,50 +353,45 @@ impl<const N: usize> zstr<N> {
 
     /// alias for [zstr::push]
     pub fn push_str<'t>(&mut self, src: &'t str) -> &'t str {
-        let srclen = src.len();
-        let slen = self.blen();
-        let bytes = &src.as_bytes();
-        let length = core::cmp::min(slen + srclen, N - 1);
-        let remain = if N - 1 >= (slen + srclen) {
-            0
-        } else {
-            (srclen + slen) - N + 1
-        };
-        let mut i = 0;
-        while i < srclen && i + slen + 1 < N {
-            self.chrs[slen + i] = bytes[i];
-            i += 1;
-        } //while
-        &src[srclen - remain..]
+        self.push_policy(src, true)
     } //push_str
 
+    // push according to the overflow policy; `fallible` is false for
+    // callers that cannot report the part that was not pushed
+    fn push_policy<'t>(&mut self, src: &'t str, fallible: bool) -> &'t str {
+        let slen = self.blen();
+        let (keep, n, ellipsis) =
+            crate::plan_push(slen, src.as_bytes(), N - 1, P::MODE, fallible);
+        let mut end = slen;
+        if keep < slen {
+            end = crate::floor_char_boundary(&self.bytes()[..slen], keep);
+            self.bytes_mut()[end..slen].fill(0);
+        }
+        self.bytes_mut()[end..end + n].copy_from_slice(&src.as_bytes()[..n]);
+        if ellipsis {
+            let e = crate::ELLIPSIS.as_bytes();
+            self.bytes_mut()[end + n..end + n + e.len()].copy_from_slice(e);
+        }
+        &src[n..]
+    } //push_policy
+
     /// pushes a single character to the end of the string, returning
     /// true on success.
     pub fn push_char(&mut self, c: char) -> bool {
-        let clen = c.len_utf8();
-        let slen = self.len();
-        if slen + clen >= N {
-            return false;
-        }
         let mut buf = [0u8; 4]; // char buffer
-        c.encode_utf8(&mut buf);
-        for i in 0..clen {
-            self.chrs[slen + i] = buf[i];
-        }
-        self.chrs[slen + clen] = 0;
-        true
+        self.push_str(c.encode_utf8(&mut buf)).is_empty()
     } // push_char
 
     /// remove and return last character in string, if it exists
     pub fn pop_char(&mut self) -> Option<char> {
-        if self.chrs[0] == 0 {
+        if self.bytes()[0] == 0 {
             return None;
         } // length zero
         let (ci, lastchar) = self.char_indices().last().unwrap();
         //self.chrs[ci]=0;
         let mut cm = ci;
-        while cm < N && self.chrs[cm] != 0 {
-            self.chrs[cm] = 0;
+        while cm < N && self.bytes()[cm] != 0 {
+            self.bytes_mut()[cm] = 0;
             cm += 1;
         }
         Some(lastchar)
@@ -344,11 +416,11 @@ impl<const N: usize> zstr<N> {
     /// check n against the length of the string. Nor does it check
     /// if the value returned is a valid character.
     pub const fn nth_bytechar(&self, n: usize) -> char {
-        self.chrs[n] as char
+        self.bytes()[n] as char
     }
     /// alias for nth_bytechar (for backwards compatibility)
     pub const fn nth_ascii(&self, n: usize) -> char {
-        self.chrs[n] as char
+        self.bytes()[n] as char
     }
 
     /// determines if string is an ascii string
@@ -365,8 +437,8 @@ impl<const N: usize> zstr<N> {
     {
         if let Some((bi, c)) = self.to_str().char_indices().nth(n) {
             let mut bm = bi;
-            while bm < N && self.chrs[bm] != 0 {
-                self.chrs[bm] = 0;
+            while bm < N && self.bytes()[bm] != 0 {
+                self.bytes_mut()[bm] = 0;
                 bm += 1;
             }
             //self.chrs[bi] = 0;
@@ -380,12 +452,12 @@ impl<const N: usize> zstr<N> {
     /// tradeoff with a O(log N) [zstr::len] function, which is expected to
     /// have greater impact.
     pub fn truncate_bytes(&mut self, n: usize) {
-        if n < N {
+        if n < self.len() {
             assert!(self.is_char_boundary(n));
             //self.chrs[n] = 0;
             let mut m = n;
-            while m < N && self.chrs[m] != 0 {
-                self.chrs[m] = 0;
+            while m < N && self.bytes()[m] != 0 {
+                self.bytes_mut()[m] = 0;
                 m += 1;
             }
         }
@@ -396,8 +468,8 @@ impl<const N: usize> zstr<N> {
     /// the resulting string does not end on a character boundary.
     pub fn right_ascii_trim(&mut self) {
         let mut n = self.blen();
-        while n > 0 && (self.chrs[n - 1] as char).is_ascii_whitespace() {
-            self.chrs[n - 1] = 0;
+        while n > 0 && (self.bytes()[n - 1] as char).is_ascii_whitespace() {
+            self.bytes_mut()[n - 1] = 0;
             n -= 1;
         }
         assert!(self.is_char_boundary(n));
@@ -412,7 +484,7 @@ impl<const N: usize> zstr<N> {
         let m = n / 2;
         let mut i = 0;
         while i < m {
-            self.chrs.swap(i, n - i - 1);
+            self.bytes_mut().swap(i, n - i - 1);
             i += 1;
         }
     } //reverse_bytes
@@ -421,8 +493,8 @@ impl<const N: usize> zstr<N> {
     /// false if indices are out of bounds.  This function is only available
     /// for zstr strings and not for other string types in this crate.
     pub fn swap_bytes(&mut self, i: usize, k: usize) -> bool {
-        if i != k && i < N && k < N && self.chrs[i] != 0 && self.chrs[k] != 0 {
-            self.chrs.swap(i, k);
+        if i != k && i < N && k < N && self.bytes()[i] != 0 && self.bytes()[k] != 0 {
+            self.bytes_mut().swap(i, k);
             true
         } else {
             false
@@ -431,15 +503,14 @@ impl<const N: usize> zstr<N> {
 
     /// resets string to empty string
     pub fn clear(&mut self) {
-        self.chrs = [0; N];
-        //self.chrs[0]=0;
+        self.chrs = [NonMaxU8::_00; N];
     }
 
     /// in-place modification of ascii characters to lower-case, panics
     /// if the string is not ascii.
     pub fn make_ascii_lowercase(&mut self) {
         assert!(self.is_ascii());
-        for b in &mut self.chrs {
+        for b in self.bytes_mut() {
             if *b == 0 {
                 break;
             } else if *b >= 65 && *b <= 90 {
@@ -452,7 +523,7 @@ impl<const N: usize> zstr<N> {
     /// the string is not ascii.
     pub fn make_ascii_uppercase(&mut self) {
         assert!(self.is_ascii());
-        for b in &mut self.chrs {
+        for b in self.bytes_mut() {
             if *b == 0 {
                 break;
             } else if *b >= 97 && *b <= 122 {
@@ -465,7 +536,7 @@ impl<const N: usize> zstr<N> {
     /// characters.  This contrasts with [str::to_ascii_uppercase],
     /// which creates an owned String.
     pub fn to_ascii_upper(&self) -> Self {
-        let mut cp = self.clone();
+        let mut cp = *self;
         cp.make_ascii_uppercase();
         cp
     }
@@ -489,14 +560,13 @@ impl<const N: usize> zstr<N> {
             return false;
         }
         let obytes = other.as_ref().as_bytes();
-        for i in 0..self.len() {
-            let mut c = self.chrs[i];
+        for (i, mut d) in obytes.iter().copied().enumerate() {
+            let mut c = self.bytes()[i];
             if (c > 64 && c < 91) {
-                c = c | 32;
+                c |= 32;
             } // make lowercase
-            let mut d = obytes[i];
             if (d > 64 && d < 91) {
-                d = d | 32;
+                d |= 32;
             } // make lowercase
             if c != d {
                 return false;
@@ -508,38 +578,43 @@ impl<const N: usize> zstr<N> {
     // new for 0.5.0
     /// converts zstr to a raw pointer to the first byte
     pub const fn to_ptr(&self) -> *const u8 {
-        let ptr = &self.chrs[0] as *const u8;
-        ptr
+        &self.bytes()[0] as *const u8
         //ptr as *const char
     }
 
     /// Converts zstr to a mutable pointer to the first byte.  Although
     /// technically not 'unsafe', this function can be used to alter
     /// the underlying representation so that there are non-zero values
-    /// after the first zero.  Use with care.
+    /// after the first zero.  Use with care: writing a 0xFF byte, which
+    /// never occurs in utf8, is undefined behavior.
     pub fn to_ptr_mut(&mut self) -> *mut u8 {
-        &mut self.chrs[0] as *mut u8
+        &mut self.bytes_mut()[0] as *mut u8
     }
 
     /// Creates a zstr from a raw pointer by copying bytes until the
-    /// first zero is encountered or when maximum capacity (N-1) is reached.
+    /// first zero (or 0xFF, which never occurs in utf8) is encountered or
+    /// when maximum capacity (N-1) is reached.
+    ///
+    /// # Safety
+    /// `ptr` must be valid for reads up to the first zero byte or N-1
+    /// bytes, whichever comes first.
     pub unsafe fn from_ptr(mut ptr: *const u8) -> Self {
         let mut z = zstr::new();
         let mut i = 0;
         unsafe {
-          while *ptr != 0 && i + 1 < N {
-            z.chrs[i] = *ptr; 
+          while *ptr != 0 && *ptr != 0xFF && i + 1 < N {
+            z.bytes_mut()[i] = *ptr; 
             ptr = (ptr as usize + 1) as *const u8;
             i += 1;
           } //while
         }
-        z.chrs[i] = 0;
+        z.bytes_mut()[i] = 0;
         z
     } //unsafe from_raw
 
     /// Creates a [core::ffi::CStr]
     pub fn to_cstr(&self) -> &core::ffi::CStr {
-      &core::ffi::CStr::from_bytes_until_nul(self.chrs.as_slice()).unwrap()
+      core::ffi::CStr::from_bytes_until_nul(self.bytes().as_slice()).unwrap()
     }
 
     /// Converts from a [core::ffi::CStr], truncates as needed
@@ -547,216 +622,199 @@ impl<const N: usize> zstr<N> {
       Self::from_raw(cstr.to_bytes_with_nul())
     }
 
-    /// Decodes a UTF-16 encodeded slice. If a decoding error is encountered
-    /// or capacity exceeded, an `Err(s)` is returned where s is the
-    /// the encoded string up to the point of the error.
-    pub fn from_utf16(v: &[u16]) -> Result<Self, Self> {
+    /// Decodes a UTF-16 encodeded slice.  Returns an error if the slice is
+    /// not valid utf16, if it contains a zero character or if the decoded
+    /// string exceeds the capacity.
+    pub fn from_utf16(v: &[u16]) -> Result<Self, CapacityError> {
+        let required = crate::utf16_len(v)?;
+        if required + 1 > N {
+            return Err(CapacityError::Overflow {
+                required,
+                available: N - 1,
+            });
+        }
         let mut s = Self::new();
         let mut len = 0; // track length without calling zstr::len
-        let mut buf = [0u8; 4];
-        for c in char::decode_utf16(v.iter().cloned()) {
-            if let Ok(c1) = c {
-                let cbytes = c1.encode_utf8(&mut buf);
-                let clen = c1.len_utf8();
-                len += clen;
-                if len + 1 > N {
-                    s.chrs[len - clen] = 0;
-                    return Err(s);
-                } else {
-                    s.chrs[len - clen..len].copy_from_slice(&buf[..clen]);
-                }
-            } else {
-                s.chrs[len] = 0;
-                return Err(s);
+        for c in char::decode_utf16(v.iter().cloned()).flatten() {
+            if c == '\0' {
+                s.bytes_mut()[..len].fill(0);
+                return Err(CapacityError::InteriorNul { position: len });
             }
+            len += c.encode_utf8(&mut s.bytes_mut()[len..]).len();
         }
-        s.chrs[len] = 0;
         Ok(s)
     } //from_utf16
 } //impl zstr<N>
 
-impl<const N: usize> core::ops::Deref for zstr<N> {
+impl<const N: usize, P: OverflowPolicy> core::ops::Deref for zstr<N, P> {
     type Target = str;
     fn deref(&self) -> &Self::Target {
         self.to_str()
     }
 }
 
-impl<const N: usize> core::convert::AsRef<str> for zstr<N> {
+impl<const N: usize, P: OverflowPolicy> core::convert::AsRef<str> for zstr<N, P> {
     fn as_ref(&self) -> &str {
         self.to_str()
     }
 }
-impl<const N: usize> core::convert::AsMut<str> for zstr<N> {
-    fn as_mut(&mut self) -> &mut str {
-        let blen = self.blen();
-        unsafe { core::str::from_utf8_unchecked_mut(&mut self.chrs[0..blen]) }
-    }
-}
 
-impl<T: AsRef<str> + ?Sized, const N: usize> core::convert::From<&T> for zstr<N> {
-    fn from(s: &T) -> zstr<N> {
-        zstr::make(s.as_ref())
-    }
-}
-impl<T: AsMut<str> + ?Sized, const N: usize> core::convert::From<&mut T> for zstr<N> {
-    fn from(s: &mut T) -> zstr<N> {
-        zstr::make(s.as_mut())
+/// Allows a `zstr` key of a `HashMap` or `BTreeMap` to be looked up by
+/// `&str`, as hashing and comparison are consistent with those of `str`.
+impl<const N: usize, P: OverflowPolicy> core::borrow::Borrow<str> for zstr<N, P> {
+    fn borrow(&self) -> &str {
+        self.to_str()
     }
 }
 
-#[cfg(feature = "std")]
-#[cfg(not(feature = "no-alloc"))]
-impl<const N: usize> std::convert::From<std::string::String> for zstr<N> {
-    fn from(s: std::string::String) -> zstr<N> {
-        zstr::<N>::make(&s[..])
+/// Collects characters or strings (see [crate::StrPiece]), truncating at
+/// the first item that does not fit as the [OverflowPolicy] dictates
+///
+/// # Panics
+/// Panics if an item does not fit under the [crate::Strict] or
+/// [crate::Panic] policy: use [zstr::try_from_iter] to detect overflow.
+impl<T: crate::StrPiece, const N: usize, P: OverflowPolicy> core::iter::FromIterator<T> for zstr<N, P> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut s = zstr::new();
+        s.extend(iter);
+        s
     }
 }
-#[cfg(feature = "std")]
-#[cfg(not(feature = "no-alloc"))]
-impl<const N: usize, const M: usize> std::convert::From<fstr<M>> for zstr<N> {
-    fn from(s: fstr<M>) -> zstr<N> {
-        zstr::<N>::make(s.to_str())
+
+/// Pushes the items until one does not fit, which is handled according
+/// to the [OverflowPolicy].
+///
+/// # Panics
+/// Panics if an item does not fit under the [crate::Strict] or
+/// [crate::Panic] policy: use [zstr::try_extend] to detect overflow.
+impl<T: crate::StrPiece, const N: usize, P: OverflowPolicy> core::iter::Extend<T> for zstr<N, P> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        crate::extend_pieces(iter, P::MODE, |s| self.push_str(s).is_empty());
     }
 }
 
-impl<const N: usize, const M: usize> core::convert::From<tstr<M>> for zstr<N> {
-    fn from(s: tstr<M>) -> zstr<N> {
-        zstr::<N>::make(s.to_str())
+impl<const N: usize, P: OverflowPolicy> core::convert::AsMut<str> for zstr<N, P> {
+    fn as_mut(&mut self) -> &mut str {
+        let blen = self.blen();
+        unsafe { core::str::from_utf8_unchecked_mut(&mut self.bytes_mut()[0..blen]) }
     }
 }
 
-impl<const N: usize> core::cmp::PartialOrd for zstr<N> {
+impl<const N: usize, P: OverflowPolicy> core::cmp::PartialOrd for zstr<N, P> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         //Some(self.chrs[0..self.blen()].cmp(other.chrs[0..other.blen()]))
         Some(self.cmp(other))
     }
 }
 
-impl<const N: usize> core::cmp::Ord for zstr<N> {
+impl<const N: usize, P: OverflowPolicy> core::cmp::Ord for zstr<N, P> {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.chrs[0..self.blen()].cmp(&other.chrs[0..other.blen()])
+        self.bytes()[0..self.blen()].cmp(&other.bytes()[0..other.blen()])
     }
 }
 
-impl<const M: usize> zstr<M> {
+impl<const M: usize, P: OverflowPolicy> zstr<M, P> {
     /// converts an zstr\<M\> to an zstr\<N\>. If the length of the string being
-    /// converted is greater than N-1, the extra characters are ignored.
+    /// converted is greater than N-1, the overflow is handled according to
+    /// the policy `P` (by default, the extra characters are ignored).
     /// This operation produces a copy (non-destructive).
     /// Example:
     ///```ignore
-    ///  let s1:zstr<8> = zstr::from("abcdefg");
+    ///  let s1:zstr<8> = zstr::make("abcdefg");
     ///  let s2:zstr<16> = s1.resize();
     ///```
-    pub fn resize<const N: usize>(&self) -> zstr<N> {
-        let slen = self.blen();
-        let length = if slen + 1 < N {
-            slen
-        } else if N == 0 {
-            0
-        } else {
-            N - 1
-        };
-        let mut chars = [0u8; N];
-        chars[..length].clone_from_slice(&self.chrs[..length]);
-        zstr { chrs: chars }
+    pub fn resize<const N: usize>(&self) -> zstr<N, P> {
+        zstr::make(self.to_str())
     } //resize
 
     /// version of resize that does not allow string truncation due to length
-    pub fn reallocate<const N: usize>(&self) -> Option<zstr<N>> {
+    pub fn reallocate<const N: usize>(&self) -> Option<zstr<N, P>> {
         if self.len() < N {
             Some(self.resize())
         } else {
             None
         }
     }
+
+    /// converts to a `zstr<N>` at least as large as `self`, which unlike
+    /// [Self::resize] can never truncate: it is checked at compile time
+    /// that a `zstr<N>` can hold any `zstr<M>`.
+    /// ```
+    ///  # use fixedstr::*;
+    ///  let s = zstr::<8>::make("abcdefg");
+    ///  let t: zstr<16> = s.grow(); // s.grow::<4>() would not compile
+    ///  assert_eq!(t, "abcdefg");
+    /// ```
+    pub fn grow<const N: usize>(&self) -> zstr<N, P> {
+        crate::assert_grows::<Self, zstr<N, P>>();
+        self.resize()
+    }
+
+    /// concatenates `self` and `other` into a new `zstr<N>`.  It is checked
+    /// at compile time that a `zstr<N>` can hold the result, so nothing is
+    /// ever truncated.  `other` can be of any size and of any type
+    /// implementing [FixedCapacity](crate::FixedCapacity).
+    /// ```
+    ///  # use fixedstr::*;
+    ///  let a = zstr::<8>::make("abc");
+    ///  let b = zstr::<4>::make("def");
+    ///  let c = a.concat::<12>(&b); // a.concat::<10>(&b) would not compile
+    ///  assert_eq!(c, "abcdef");
+    /// ```
+    pub fn concat<const N: usize>(&self, other: &impl crate::FixedCapacity) -> zstr<N, P> {
+        crate::assert_concat_fits::<Self, _, zstr<N, P>>(other);
+        let mut cat: zstr<N, P> = self.resize();
+        crate::append_all(&mut cat, other);
+        cat
+    }
 } //impl zstr<M>
 
-impl<const N: usize> core::fmt::Display for zstr<N> {
+impl<const N: usize, P: OverflowPolicy> core::fmt::Display for zstr<N, P> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         //write!(f, "{}", self.to_str())
         f.pad(self.to_str())
     }
 }
 
-impl<const N: usize> PartialEq<&str> for zstr<N> {
+impl<const N: usize, P: OverflowPolicy> PartialEq<&str> for zstr<N, P> {
     fn eq(&self, other: &&str) -> bool {
         self.to_str() == *other // see below
     } //eq
 }
-impl<const N: usize> PartialEq<&str> for &zstr<N> {
-    fn eq(&self, other: &&str) -> bool {
-        &self.to_str() == other
-        /*
-          let obytes = other.as_bytes();
-          let olen = obytes.len();
-          let blen = self.blen();
-          if olen!=blen {return false;}
-          for i in 0..olen
-          {
-             if obytes[i] != self.chrs[i] {return false;}
-          }
-          return true;
-        */
-    } //eq
-}
-impl<'t, const N: usize> PartialEq<zstr<N>> for &'t str {
-    fn eq(&self, other: &zstr<N>) -> bool {
+impl<const N: usize, P: OverflowPolicy> PartialEq<zstr<N, P>> for &str {
+    fn eq(&self, other: &zstr<N, P>) -> bool {
         &other.to_str() == self
     }
 }
-impl<'t, const N: usize> PartialEq<&zstr<N>> for &'t str {
-    fn eq(&self, other: &&zstr<N>) -> bool {
-        &other.to_str() == self
-    }
-}
-
 /// defaults to empty string
-impl<const N: usize> Default for zstr<N> {
+impl<const N: usize, P: OverflowPolicy> Default for zstr<N, P> {
     fn default() -> Self {
-        zstr::<N>::make("")
+        zstr::<N, P>::make("")
     }
 }
 #[cfg(feature = "std")]
 #[cfg(not(feature = "no-alloc"))]
-impl<const N: usize, const M: usize> PartialEq<zstr<N>> for fstr<M> {
-    fn eq(&self, other: &zstr<N>) -> bool {
+impl<const N: usize, const M: usize, P: OverflowPolicy, Q: OverflowPolicy> PartialEq<&fstr<N, Q>> for zstr<M, P> {
+    fn eq(&self, other: &&fstr<N, Q>) -> bool {
         other.to_str() == self.to_str()
     }
 }
 
-#[cfg(feature = "std")]
-#[cfg(not(feature = "no-alloc"))]
-impl<const N: usize, const M: usize> PartialEq<fstr<N>> for zstr<M> {
-    fn eq(&self, other: &fstr<N>) -> bool {
-        other.to_str() == self.to_str()
-    }
-}
-
-#[cfg(feature = "std")]
-#[cfg(not(feature = "no-alloc"))]
-impl<const N: usize, const M: usize> PartialEq<&fstr<N>> for zstr<M> {
-    fn eq(&self, other: &&fstr<N>) -> bool {
-        other.to_str() == self.to_str()
-    }
-}
-
-impl<const N: usize> core::fmt::Debug for zstr<N> {
+impl<const N: usize, P: OverflowPolicy> core::fmt::Debug for zstr<N, P> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        f.pad(&self.to_str())
+        f.pad(self.to_str())
     }
 } // Debug impl
 
-impl<const N: usize> zstr<N> {
+impl<const N: usize, P: OverflowPolicy> zstr<N, P> {
     /// returns a copy of the portion of the string, string could be truncated
     /// if indices are out of range. Similar to slice [start..end]
-    pub fn substr(&self, start: usize, end: usize) -> zstr<N> {
-        let mut chars = [0u8; N];
+    pub fn substr(&self, start: usize, end: usize) -> zstr<N, P> {
         let mut inds = self.char_indices();
         let len = self.len();
         let blen = self.blen();
         if start >= len || end <= start {
-            return zstr { chrs: chars };
+            return zstr::new();
         }
         let (si, _) = inds.nth(start).unwrap();
         let last = if (end >= len) {
@@ -767,11 +825,287 @@ impl<const N: usize> zstr<N> {
                 None => blen,
             } //match
         }; //let last =...
-        chars[..last - si].clone_from_slice(&self.chrs[si..last]);
-        zstr { chrs: chars }
+        self.const_range(si, last)
     } //substr
 }
 
+impl<const N: usize, P: OverflowPolicy> zstr<N, P> {
+    /// returns a copy of the string with all matches of the [StrPattern]
+    /// `pat` replaced by `to`, and whether the copy was truncated to fit
+    /// the capacity (according to the overflow policy `P`).  Unlike
+    /// [str::replace], this does not allocate.
+    pub fn replace<Pat: StrPattern>(&self, pat: Pat, to: &str) -> (zstr<N, P>, bool) {
+        crate::replace_into(self.as_str(), pat, to, usize::MAX)
+    }
+
+    /// version of [zstr::replace] that only replaces the first `count` matches
+    pub fn replacen<Pat: StrPattern>(&self, pat: Pat, to: &str, count: usize) -> (zstr<N, P>, bool) {
+        crate::replace_into(self.as_str(), pat, to, count)
+    }
+
+    /// version of [zstr::replace] that replaces every occurrence of one
+    /// character by another.  The copy can only be truncated if `to`
+    /// has a longer utf8 encoding than `from`.
+    pub fn replace_char(&self, from: char, to: char) -> (zstr<N, P>, bool) {
+        let mut buf = [0u8; 4];
+        crate::replace_into(self.as_str(), from, to.encode_utf8(&mut buf), usize::MAX)
+    }
+
+    /// removes all matches of the [StrPattern] `pat` in place
+    pub fn remove_matches<Pat: StrPattern>(&mut self, pat: Pat) {
+        *self = crate::replace_into(self.as_str(), pat, "", usize::MAX).0;
+    }
+
+    /// splits the string at the first match of `pat`, returning the parts
+    /// before and after the match, or `None` if there is no match.  The
+    /// parts are never truncated.
+    pub fn split_once<Pat: StrPattern>(&self, pat: Pat) -> Option<(zstr<N, P>, zstr<N, P>)> {
+        let s = self.as_str();
+        pat.find_in(s).map(|m| crate::split_around(s, m))
+    }
+
+    /// splits the string at the last match of `pat`, returning the parts
+    /// before and after the match, or `None` if there is no match.
+    pub fn rsplit_once<Pat: StrPattern>(&self, pat: Pat) -> Option<(zstr<N, P>, zstr<N, P>)> {
+        let s = self.as_str();
+        pat.rfind_in(s).map(|m| crate::split_around(s, m))
+    }
+
+    /// returns a copy of the string without the prefix `pat`, or `None` if
+    /// the string does not start with it
+    pub fn strip_prefix<Pat: StrPattern>(&self, pat: Pat) -> Option<zstr<N, P>> {
+        let s = self.as_str();
+        match pat.find_in(s) {
+            Some(m) if m.start == 0 => Some(zstr::make(&s[m.end..])),
+            _ => None,
+        }
+    }
+
+    /// returns a copy of the string without the suffix `pat`, or `None` if
+    /// the string does not end with it
+    pub fn strip_suffix<Pat: StrPattern>(&self, pat: Pat) -> Option<zstr<N, P>> {
+        let s = self.as_str();
+        match pat.rfind_in(s) {
+            Some(m) if m.end == s.len() => Some(zstr::make(&s[..m.start])),
+            _ => None,
+        }
+    }
+
+    /// iterator over the parts of the string separated by the [StrPattern]
+    /// `pat`, as with [str::split], but which yields strings of type `T`
+    /// instead of slices.  See [SplitInto].
+    pub fn split_into<T: FixedstrMake>(&self, pat: impl StrPattern) -> SplitInto<'_, T, impl StrPattern> {
+        SplitInto::new(self.as_str(), "", pat, usize::MAX)
+    }
+
+    /// version of [zstr::split_into] that yields at most `n` parts, the last
+    /// being the rest of the string, as with [str::splitn]
+    pub fn splitn_into<T: FixedstrMake>(&self, n: usize, pat: impl StrPattern) -> SplitInto<'_, T, impl StrPattern> {
+        SplitInto::new(self.as_str(), "", pat, n)
+    }
+
+    /// iterator over the whitespace-separated words of the string, as with
+    /// [str::split_whitespace], that yields strings of type `T`
+    pub fn split_whitespace_into<T: FixedstrMake>(&self) -> SplitInto<'_, T, fn(char) -> bool> {
+        SplitInto::new(self.as_str(), "", char::is_whitespace as fn(char) -> bool, usize::MAX).skipping_empty()
+    }
+
+    /// iterator over the lines of the string, as with [str::lines], that
+    /// yields strings of type `T`
+    pub fn lines_into<T: FixedstrMake>(&self) -> SplitInto<'_, T, char> {
+        SplitInto::new(self.as_str(), "", '\n', usize::MAX).splitting_lines()
+    }
+} //pattern methods
+
+impl<const N: usize, P: OverflowPolicy> zstr<N, P> {
+    // replaces the bytes in range r with `with`, which must not contain
+    // a zero byte
+    fn splice(&mut self, r: Range<usize>, with: &str) -> Result<(), CapacityError> {
+        if let Some(position) = with.bytes().position(|b| b == 0) {
+            return Err(CapacityError::InteriorNul { position });
+        }
+        let len = self.len();
+        crate::splice_bytes(&mut self.bytes_mut()[..N - 1], len, r, with.as_bytes())?;
+        Ok(())
+    }
+
+    /// inserts a character at byte index `idx`, shifting the rest of the
+    /// string, as `String::insert` does.  Returns false (leaving the
+    /// string unchanged) if the result would not fit.  **Panics** if `idx`
+    /// is larger than the length or not on a character boundary.
+    pub fn insert(&mut self, idx: usize, c: char) -> bool {
+        let mut buf = [0u8; 4];
+        self.insert_str(idx, c.encode_utf8(&mut buf)).is_ok()
+    }
+
+    /// inserts a string at byte index `idx`, as `String::insert_str` does,
+    /// or returns a [CapacityError] (leaving the string unchanged) if the
+    /// result would not fit.  **Panics** if `idx` is larger than the
+    /// length or not on a character boundary.
+    pub fn insert_str(&mut self, idx: usize, s: &str) -> Result<(), CapacityError> {
+        self.replace_range(idx..idx, s)
+    }
+
+    /// removes and returns the character at byte index `idx`, as
+    /// `String::remove` does.  **Panics** if `idx` is not less than the
+    /// length or not on a character boundary.
+    pub fn remove(&mut self, idx: usize) -> char {
+        let c = self.as_str()[idx..].chars().next().expect("cannot remove a char from the end of a string");
+        self.drain(idx..idx + c.len_utf8());
+        c
+    }
+
+    /// removes the given range of bytes from the string and returns it as
+    /// a new string.  Unlike `String::drain`, the removal is immediate and
+    /// no iterator is involved.  **Panics** if the range is out of bounds
+    /// or not on character boundaries.
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> zstr<N, P> {
+        let s = self.as_str();
+        let r = crate::byte_range(s.len(), range, |i| s.is_char_boundary(i));
+        let drained = zstr::make(&s[r.clone()]);
+        self.splice(r, "").unwrap();
+        drained
+    }
+
+    /// keeps only the characters for which `f` returns true, as
+    /// `String::retain` does
+    pub fn retain<F: FnMut(char) -> bool>(&mut self, mut f: F) {
+        let mut kept = zstr::<N, P>::new();
+        for c in self.as_str().chars().filter(|c| f(*c)) {
+            kept.push_char(c);
+        }
+        *self = kept;
+    }
+
+    /// replaces the given range of bytes with a string, as
+    /// `String::replace_range` does, or returns a [CapacityError] (leaving
+    /// the string unchanged) if the result would not fit.  **Panics** if
+    /// the range is out of bounds or not on character boundaries.
+    pub fn replace_range<R: RangeBounds<usize>>(&mut self, range: R, with: &str) -> Result<(), CapacityError> {
+        let s = self.as_str();
+        let r = crate::byte_range(s.len(), range, |i| s.is_char_boundary(i));
+        self.splice(r, with)
+    }
+
+    /// changes the character at *character position* n to c, returning
+    /// true on success.  Unlike [zstr::set], the new character may have a
+    /// different utf8 length than the one it replaces, in which case the
+    /// rest of the string is shifted.  Returns false (leaving the string
+    /// unchanged) only if n is out of range or the result would not fit.
+    pub fn replace_nth(&mut self, n: usize, c: char) -> bool {
+        match self.as_str().char_indices().nth(n) {
+            Some((i, _)) => self.replace_at(i, c),
+            None => false,
+        }
+    }
+
+    /// version of [zstr::replace_nth] that changes the character starting
+    /// at *byte* index `idx`.  Returns false if `idx` is not the start of a
+    /// character of the string or the result would not fit.
+    pub fn replace_at(&mut self, idx: usize, c: char) -> bool {
+        let mut buf = [0u8; 4];
+        match self.as_str().get(idx..).and_then(|t| t.chars().next()) {
+            Some(old) => self.replace_range(idx..idx + old.len_utf8(), c.encode_utf8(&mut buf)).is_ok(),
+            None => false,
+        }
+    }
+} //editing methods
+
+impl<const N: usize, P: OverflowPolicy> zstr<N, P> {
+    /// version of [zstr::push_str] that can be called from a const context
+    pub const fn const_push_str<'t>(&mut self, src: &'t str) -> &'t str {
+        let slen = self.blen();
+        let (keep, n, ellipsis) = crate::plan_push(slen, src.as_bytes(), N - 1, P::MODE, true);
+        let mut end = slen;
+        if keep < slen {
+            end = crate::floor_char_boundary(self.as_bytes_non_terminated(), keep);
+            let mut i = end;
+            while i < slen {
+                self.bytes_mut()[i] = 0;
+                i += 1;
+            }
+        }
+        crate::copy_bytes(self.bytes_mut(), end, src.as_bytes(), n);
+        if ellipsis {
+            crate::copy_bytes(self.bytes_mut(), end + n, crate::ELLIPSIS.as_bytes(), crate::ELLIPSIS.len());
+        }
+        src.split_at(n).1
+    } //const_push_str
+
+    /// version of `==` with a `&str` that can be called from a const context
+    pub const fn const_eq(&self, other: &str) -> bool {
+        crate::const_fns::eq(self.const_bytes(), ConstBytes::of(other))
+    }
+
+    /// version of [Ord::cmp] with a `&str` that can be called from a
+    /// const context
+    pub const fn const_cmp(&self, other: &str) -> Ordering {
+        crate::const_fns::cmp(self.const_bytes(), ConstBytes::of(other))
+    }
+
+    /// version of [str::starts_with] that can be called from a const context
+    pub const fn const_starts_with(&self, prefix: &str) -> bool {
+        crate::const_fns::starts_with(self.const_bytes(), ConstBytes::of(prefix))
+    }
+
+    /// returns the byte position of the first occurrence of `pat`, like
+    /// [str::find] but callable from a const context
+    pub const fn const_find(&self, pat: &str) -> Option<usize> {
+        crate::const_fns::find(self.const_bytes(), ConstBytes::of(pat))
+    }
+
+    /// version of [zstr::substr] that can be called from a const context:
+    /// returns a copy of the characters at (character) positions
+    /// `start..end`, which are clamped to the length of the string
+    pub const fn const_substr(&self, start: usize, end: usize) -> zstr<N, P> {
+        if end <= start {
+            return zstr::new();
+        }
+        let b = self.const_bytes();
+        self.const_range(crate::const_fns::char_to_byte(b, start), crate::const_fns::char_to_byte(b, end))
+    }
+
+    /// version of [zstr::to_ascii_upper] that can be called from a const context
+    pub const fn const_to_ascii_upper(&self) -> zstr<N, P> {
+        self.const_map_ascii(true)
+    }
+
+    /// version of [zstr::to_ascii_lower] that can be called from a const context
+    pub const fn const_to_ascii_lower(&self) -> zstr<N, P> {
+        self.const_map_ascii(false)
+    }
+
+    /// returns a copy of the string without leading and trailing
+    /// whitespace, like [str::trim] but callable from a const context
+    pub const fn const_trim(&self) -> zstr<N, P> {
+        let (start, end) = crate::const_fns::trim(self.const_bytes());
+        self.const_range(start, end)
+    }
+
+    // the bytes of the string, for the const methods
+    const fn const_bytes(&self) -> ConstBytes<'_> {
+        ConstBytes::new(self.bytes(), 0, self.blen())
+    }
+
+    // copy of bytes start..end, which must be at character boundaries
+    const fn const_range(&self, start: usize, end: usize) -> zstr<N, P> {
+        let mut s = zstr::new();
+        crate::copy_bytes(s.bytes_mut(), 0, self.bytes().split_at(start).1, end - start);
+        s
+    }
+
+    const fn const_map_ascii(&self, upper: bool) -> zstr<N, P> {
+        let mut s = *self;
+        let b = s.bytes_mut();
+        let mut i = 0;
+        while i < self.blen() {
+            b[i] = if upper { b[i].to_ascii_uppercase() } else { b[i].to_ascii_lowercase() };
+            i += 1;
+        }
+        s
+    }
+} //const methods
+
 /// [zstr] type aliases for convenience
 pub type ztr8 = zstr<8>;
 pub type ztr16 = zstr<16>;
@@ -789,13 +1123,15 @@ pub type ztr128 = zstr<128>;
 ///   /* or */
 ///   let s2 = str_format!(zstr<16>,"abx{}{}{}",1,2,3);
 /// ```
-impl<const N: usize> core::fmt::Write for zstr<N> {
+impl<const N: usize, P: OverflowPolicy> core::fmt::Write for zstr<N, P> {
+    /// pushes `s` according to the overflow policy (by default, as much
+    /// as fits up to a character boundary), returning an error if anything
+    /// had to be left out.
     fn write_str(&mut self, s: &str) -> core::fmt::Result //Result<(),core::fmt::Error>
     {
-        if s.len() + self.len() + 1 > N {
-            return Err(core::fmt::Error::default());
+        if !self.push(s).is_empty() {
+            return Err(core::fmt::Error);
         }
-        self.push(s);
         Ok(())
     } //write_str
 } //core::fmt::Write trait
@@ -806,78 +1142,64 @@ mod special_index {
     use core::ops::{Range, RangeFrom, RangeFull, RangeTo};
     use core::ops::{RangeInclusive, RangeToInclusive};
 
-    impl<const N: usize> core::ops::Index<Range<usize>> for zstr<N> {
+    impl<const N: usize, P: OverflowPolicy> core::ops::Index<Range<usize>> for zstr<N, P> {
         type Output = str;
         fn index(&self, index: Range<usize>) -> &Self::Output {
             &self.to_str()[index]
         }
     } //impl Index
-    impl<const N: usize> core::ops::Index<RangeTo<usize>> for zstr<N> {
+    impl<const N: usize, P: OverflowPolicy> core::ops::Index<RangeTo<usize>> for zstr<N, P> {
         type Output = str;
         fn index(&self, index: RangeTo<usize>) -> &Self::Output {
             &self.to_str()[index]
         }
     } //impl Index
-    impl<const N: usize> core::ops::Index<RangeFrom<usize>> for zstr<N> {
+    impl<const N: usize, P: OverflowPolicy> core::ops::Index<RangeFrom<usize>> for zstr<N, P> {
         type Output = str;
         fn index(&self, index: RangeFrom<usize>) -> &Self::Output {
             &self.to_str()[index]
         }
     } //impl Index
-    impl<const N: usize> core::ops::Index<RangeInclusive<usize>> for zstr<N> {
+    impl<const N: usize, P: OverflowPolicy> core::ops::Index<RangeInclusive<usize>> for zstr<N, P> {
         type Output = str;
         fn index(&self, index: RangeInclusive<usize>) -> &Self::Output {
             &self.to_str()[index]
         }
     } //impl Index
-    impl<const N: usize> core::ops::Index<RangeToInclusive<usize>> for zstr<N> {
+    impl<const N: usize, P: OverflowPolicy> core::ops::Index<RangeToInclusive<usize>> for zstr<N, P> {
         type Output = str;
         fn index(&self, index: RangeToInclusive<usize>) -> &Self::Output {
             &self.to_str()[index]
         }
     } //impl Index
-    impl<const N: usize> core::ops::Index<RangeFull> for zstr<N> {
+    impl<const N: usize, P: OverflowPolicy> core::ops::Index<RangeFull> for zstr<N, P> {
         type Output = str;
         fn index(&self, index: RangeFull) -> &Self::Output {
-            &self.to_str()[index]
+            self.to_str()
         }
     } //impl Index
 
     // must include above to have the following ..
 
     ///The implementation of `Index<usize>` for types `zstr<N>` is different
-    ///from that of `fstr<N>` and `tstr<N>`, to allow `IndexMut` on a single
-    ///byte.  The type returned by this trait is &u8, not &str.  This special
-    ///trait is only available with the `experimental` feature.
-    impl<const N: usize> core::ops::Index<usize> for zstr<N> {
+    ///from that of `fstr<N>` and `tstr<N>`: the type returned by this
+    ///trait is &u8, not &str.  This special trait is only available with
+    ///the `experimental` feature.  There is no `IndexMut<usize>`, as a
+    ///`&mut u8` could be set to 0xFF, the niche of `Option<zstr<N>>`:
+    ///use the unsafe [zstr::as_bytes_mut] instead.
+    impl<const N: usize, P: OverflowPolicy> core::ops::Index<usize> for zstr<N, P> {
         type Output = u8;
         fn index(&self, index: usize) -> &Self::Output {
-            &self.chrs[index]
+            &self.bytes()[index]
         }
     } //impl Index
-
-    /// **This trait is provided with caution**, and only with the
-    /// **`experimental`** feature, as it allows arbitrary changes
-    /// to the bytes of the string.  In particular, the string can become
-    /// corrupted if a premature zero-byte is created using this function,
-    /// which invalidates the [Self::len] function.  Several other operations
-    /// such as [Self::push] depend on a correct length function.
-    impl<const N: usize> core::ops::IndexMut<usize> for zstr<N> {
-        fn index_mut(&mut self, index: usize) -> &mut Self::Output {
-            let ln = self.blen();
-            if index >= ln {
-                panic!("index {} out of range ({})", index, ln);
-            }
-            &mut self.chrs[index]
-        }
-    } //impl IndexMut
 } // special_index submodule (--features experimental)
 
-impl<const N: usize, TA: AsRef<str>> Add<TA> for zstr<N> {
-    type Output = zstr<N>;
-    fn add(self, other: TA) -> zstr<N> {
+impl<const N: usize, TA: AsRef<str> + ?Sized, P: OverflowPolicy> Add<&TA> for zstr<N, P> {
+    type Output = zstr<N, P>;
+    fn add(self, other: &TA) -> zstr<N, P> {
         let mut a2 = self;
-        a2.push(other.as_ref());
+        a2.push_policy(other.as_ref(), false);
         a2
     }
 } //Add &str
@@ -891,25 +1213,25 @@ impl<const N: usize, TA: AsRef<str>> Add<TA> for zstr<N> {
       }
   } //Add &str
   */
-impl<const N: usize> Add<&zstr<N>> for &str {
-    type Output = zstr<N>;
-    fn add(self, other: &zstr<N>) -> zstr<N> {
-        let mut a2 = zstr::from(self);
-        a2.push(other);
+impl<const N: usize, P: OverflowPolicy> Add<&zstr<N, P>> for &str {
+    type Output = zstr<N, P>;
+    fn add(self, other: &zstr<N, P>) -> zstr<N, P> {
+        let mut a2 = zstr::make(self);
+        a2.push_policy(other, false);
         a2
     }
 } //Add &str on left
 
-impl<const N: usize> Add<zstr<N>> for &str {
-    type Output = zstr<N>;
-    fn add(self, other: zstr<N>) -> zstr<N> {
-        let mut a2 = zstr::from(self);
-        a2.push(&other);
+impl<const N: usize, P: OverflowPolicy> Add<zstr<N, P>> for &str {
+    type Output = zstr<N, P>;
+    fn add(self, other: zstr<N, P>) -> zstr<N, P> {
+        let mut a2 = zstr::make(self);
+        a2.push_policy(&other, false);
         a2
     }
 } //Add &str on left
 
-impl<const N: usize> core::hash::Hash for zstr<N> {
+impl<const N: usize, P: OverflowPolicy> core::hash::Hash for zstr<N, P> {
     fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
         self.as_ref().hash(state);
     }
@@ -922,20 +1244,65 @@ impl<const N: usize> core::hash::Hash for zstr<N> {
   }
   */
 
-impl<const N: usize> core::cmp::PartialEq for zstr<N> {
+impl<const N: usize, P: OverflowPolicy> core::cmp::PartialEq for zstr<N, P> {
     fn eq(&self, other: &Self) -> bool {
         self.as_ref() == other.as_ref()
     }
 }
 
-impl<const N: usize> core::str::FromStr for zstr<N> {
-    type Err = &'static str;
+impl<const N: usize, P: OverflowPolicy> core::str::FromStr for zstr<N, P> {
+    type Err = CapacityError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.len() < N {
-            Ok(zstr::from(s))
-        } else {
-            Err("capacity exceeded")
-        }
+        zstr::try_make(s)
+    }
+}
+
+impl<const N: usize, P: OverflowPolicy> crate::FixedCapacity for zstr<N, P> {
+    const CAPACITY: usize = N - 1;
+}
+
+impl<const N: usize, P: OverflowPolicy> crate::Fixedstr for zstr<N, P> {
+    fn len(&self) -> usize {
+        self.len()
+    }
+    fn capacity(&self) -> usize {
+        self.capacity()
+    }
+    fn with_segments(&self, f: &mut dyn FnMut(&str, &str)) {
+        f(self.to_str(), "")
+    }
+    fn push_str<'t>(&mut self, src: &'t str) -> &'t str {
+        self.push_str(src)
+    }
+    fn push_char(&mut self, c: char) -> bool {
+        self.push_char(c)
+    }
+    fn pop_char(&mut self) -> Option<char> {
+        self.pop_char()
+    }
+    fn truncate_bytes(&mut self, n: usize) {
+        self.truncate_bytes(n)
+    }
+    fn clear(&mut self) {
+        self.clear()
+    }
+}
+
+impl<const N: usize, P: OverflowPolicy> crate::FixedstrMake for zstr<N, P> {
+    fn make(s: &str) -> Self {
+        zstr::make(s)
+    }
+    fn try_make(s: &str) -> Result<Self, CapacityError> {
+        zstr::try_make(s)
+    }
+    fn new() -> Self {
+        zstr::new()
+    }
+}
+
+impl<const N: usize, P: OverflowPolicy> crate::ContiguousFixedstr for zstr<N, P> {
+    fn as_str(&self) -> &str {
+        self.to_str()
     }
 }
 
@@ -961,12 +1328,12 @@ impl<'t, const N:usize, const CS:usize> Iterator for ChunkyIter<'t,N,CS> {
 } // impl Iterator for ChunkyIter
 
 #[cfg(feature = "experimental")]
-impl<const N:usize> zstr<N> {
+impl<const N:usize, P: OverflowPolicy> zstr<N, P> {
   /// Creates a [ChunkyIter] iterator over `&[u8]` slices of fixed size `CS`,
   /// except for the final slice, which may also be zero-terminated.
   pub fn chunky_iter<'t,const CS:usize>(&'t self) -> ChunkyIter<'t,N,CS> {
     ChunkyIter {
-      bur : &self.chrs,
+      bur : self.bytes(),
       index : 0,
     }
   }//chunk_iter